@@ -1,15 +1,15 @@
 use std::sync::Arc;
 use axum::{
     extract::State,
-    http::StatusCode,
-    response::{sse::{Event, Sse}, IntoResponse},
+    http::{header, StatusCode},
+    response::{sse::{Event, Sse}, IntoResponse, Response},
     routing::post,
     Json, Router,
 };
 use futures_util::{StreamExt};
 use gaise_core::{
     contracts::{GaiseEmbeddingsRequest, GaiseInstructRequest},
-    GaiseClient,
+    GaiseClient, GaiseError,
 };
 use gaise_client::{GaiseClientService};
 use tracing::error;
@@ -26,6 +26,37 @@ pub fn create_app(state: Arc<AppState>) -> Router {
         .with_state(state)
 }
 
+/// HTTP status reported to API callers for each kind of error.
+pub fn error_status(error: &GaiseError) -> StatusCode {
+    match error {
+        GaiseError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        GaiseError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+        GaiseError::ContextLengthExceeded(_) => StatusCode::PAYLOAD_TOO_LARGE,
+        GaiseError::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+        GaiseError::ProviderUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        GaiseError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        GaiseError::Stream(_) => StatusCode::BAD_GATEWAY,
+        GaiseError::Unsupported(_) => StatusCode::NOT_IMPLEMENTED,
+        GaiseError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn error_body(error: &GaiseError) -> serde_json::Value {
+    serde_json::json!({ "error": { "type": error.kind(), "message": error.message() } })
+}
+
+/// JSON error response, e.g. `{"error": {"type": "rate_limited", "message": "..."}}`.
+/// Rate limits pass the provider's `Retry-After` on.
+pub fn error_response(error: &GaiseError) -> Response {
+    let mut response = (error_status(error), Json(error_body(error))).into_response();
+    if let GaiseError::RateLimited { retry_after: Some(retry_after), .. } = error
+        && let Ok(value) = retry_after.as_secs().max(1).to_string().parse()
+    {
+        response.headers_mut().insert(header::RETRY_AFTER, value);
+    }
+    response
+}
+
 async fn handle_instruct(
     State(state): State<Arc<AppState>>,
     Json(request): Json<GaiseInstructRequest>,
@@ -33,8 +64,8 @@ async fn handle_instruct(
     match state.client_service.instruct(&request).await {
         Ok(response) => Json(response).into_response(),
         Err(e) => {
-            error!("Instruct error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            error!("Instruct error ({}): {}", e.kind(), e);
+            error_response(&e)
         }
     }
 }
@@ -51,15 +82,15 @@ async fn handle_instruct_stream(
                         Event::default().json_data(chunk)
                     }
                     Err(e) => {
-                        Ok(Event::default().event("error").data(e.to_string()))
+                        Event::default().event("error").json_data(error_body(&e))
                     }
                 }
             });
             Sse::new(sse_stream).into_response()
         }
         Err(e) => {
-            error!("Instruct stream error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            error!("Instruct stream error ({}): {}", e.kind(), e);
+            error_response(&e)
         }
     }
 }
@@ -71,8 +102,8 @@ async fn handle_embeddings(
     match state.client_service.embeddings(&request).await {
         Ok(response) => Json(response).into_response(),
         Err(e) => {
-            error!("Embeddings error: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response()
+            error!("Embeddings error ({}): {}", e.kind(), e);
+            error_response(&e)
         }
     }
 }
@@ -8,31 +8,16 @@ use gaise_core::logging::ConsoleGaiseLogger;
 async fn main() {
     tracing_subscriber::fmt::init();
 
-    let ollama_url = std::env::var("OLLAMA_URL").ok();
-    let vertexai_api_url = std::env::var("VERTEXAI_API_URL").ok();
-    let vertexai_sa_path = std::env::var("VERTEXAI_SA_PATH").ok();
-    let openai_api_url = std::env::var("OPENAI_API_URL").ok();
-    let openai_api_key = std::env::var("OPENAI_API_KEY").ok();
-    let bedrock_region = std::env::var("BEDROCK_REGION").ok();
-    let anthropic_api_url = std::env::var("ANTHROPIC_API_URL").ok();
-    let anthropic_api_key = std::env::var("ANTHROPIC_API_KEY").ok();
-
-    let vertexai_sa = vertexai_sa_path.and_then(|path| {
-        let sa_json = std::fs::read_to_string(path).ok()?;
-        serde_json::from_str(&sa_json).ok()
-    });
-
-    let config = GaiseClientConfig {
-        ollama_url,
-        vertexai_api_url,
-        vertexai_sa,
-        openai_api_url,
-        openai_api_key,
-        bedrock_region,
-        anthropic_api_url,
-        anthropic_api_key,
-        logger: Some(Arc::new(ConsoleGaiseLogger::default())),
+    let mut config = match GaiseClientConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Could not load gaise configuration: {}", e);
+            std::process::exit(1);
+        }
     };
+    if config.logger.is_none() {
+        config.logger = Some(Arc::new(ConsoleGaiseLogger));
+    }
 
     let state = Arc::new(AppState {
         client_service: GaiseClientService::new(config),
@@ -2,10 +2,12 @@ use axum::{
     body::Body,
     http::{self, Request, StatusCode},
 };
-use gaise_api::{create_app, AppState};
+use gaise_api::{create_app, error_response, error_status, AppState};
+use gaise_core::GaiseError;
 use gaise_client::{GaiseClientService, GaiseClientConfig};
 use serde_json::json;
 use std::sync::Arc;
+use std::time::Duration;
 use tower::ServiceExt; // for `oneshot`
 
 #[tokio::test]
@@ -49,11 +51,39 @@ async fn test_instruct_post_request() {
         .await
         .unwrap();
 
-    // Since we provided an unknown provider ("nonexistent"), we expect an INTERNAL_SERVER_ERROR (500)
-    // because GaiseClientService returns an error which the handler maps to 500.
-    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    // Since we provided an unknown provider ("nonexistent"), GaiseClientService returns
+    // GaiseError::InvalidRequest, which the handler maps to BAD_REQUEST (400).
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
     let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
-    let body_str = String::from_utf8(body.to_vec()).unwrap();
-    assert!(body_str.contains("Unknown provider: nonexistent"));
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["error"]["type"], "invalid_request");
+    assert!(body["error"]["message"].as_str().unwrap().contains("Unknown or disabled provider: nonexistent"));
+}
+
+#[test]
+fn test_error_status_mapping() {
+    let cases = [
+        (GaiseError::RateLimited { retry_after: None, message: String::new() }, StatusCode::TOO_MANY_REQUESTS),
+        (GaiseError::Unauthorized(String::new()), StatusCode::UNAUTHORIZED),
+        (GaiseError::ContextLengthExceeded(String::new()), StatusCode::PAYLOAD_TOO_LARGE),
+        (GaiseError::InvalidRequest(String::new()), StatusCode::BAD_REQUEST),
+        (GaiseError::ProviderUnavailable(String::new()), StatusCode::SERVICE_UNAVAILABLE),
+        (GaiseError::Timeout(String::new()), StatusCode::GATEWAY_TIMEOUT),
+        (GaiseError::Unsupported(String::new()), StatusCode::NOT_IMPLEMENTED),
+    ];
+    for (error, status) in cases {
+        assert_eq!(error_status(&error), status, "{:?}", error);
+    }
+}
+
+#[test]
+fn test_rate_limited_response_sets_retry_after() {
+    let response = error_response(&GaiseError::RateLimited {
+        retry_after: Some(Duration::from_secs(30)),
+        message: "OpenAI API error (429): slow down".to_string(),
+    });
+
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(response.headers()[http::header::RETRY_AFTER], "30");
 }
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use gaise_core::contracts::GaiseGenerationConfig;
+use gaise_core::logging::ConsoleGaiseLogger;
+#[cfg(feature = "vertexai")]
+use gaise_provider_vertexai::contracts::ServiceAccount;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::{EndpointConfig, GaiseClientConfig, ModelAlias, RetryPolicy};
+
+/// Environment variable listing the configuration files `from_env` loads,
+/// separated like `PATH` (later files override earlier ones).
+pub const CONFIG_PATH_VAR: &str = "GAISE_CONFIG";
+
+/// Error returned when a configuration file cannot be read, parsed or interpolated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigError {
+    /// The file could not be read.
+    Io(String),
+    /// The file is not valid TOML/YAML or does not match the expected layout.
+    Parse(String),
+    /// A `${NAME}` reference names an environment variable that is not set and has no default.
+    MissingVariable(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(message) | Self::Parse(message) => f.write_str(message),
+            Self::MissingVariable(name) => write!(f, "Environment variable {} is not set", name),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Settings of a provider, or of a named endpoint when `provider` is set.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProviderSection {
+    provider: Option<String>,
+    url: Option<String>,
+    api_key: Option<String>,
+    region: Option<String>,
+    service_account_path: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProvidersSection {
+    ollama: Option<ProviderSection>,
+    vertexai: Option<ProviderSection>,
+    openai: Option<ProviderSection>,
+    bedrock: Option<ProviderSection>,
+    anthropic: Option<ProviderSection>,
+}
+
+/// An alias is either just a model (`fast = "openai::gpt-4o-mini"`) or a table with defaults.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AliasSection {
+    Model(String),
+    Full {
+        model: String,
+        generation_config: Option<GaiseGenerationConfig>,
+    },
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetrySection {
+    max_attempts: Option<u32>,
+    base_delay_ms: Option<u64>,
+    max_delay_ms: Option<u64>,
+    jitter: Option<f64>,
+    retry_on: Option<Vec<String>>,
+}
+
+/// The layout of a configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    providers: ProvidersSection,
+    #[serde(default)]
+    endpoints: HashMap<String, ProviderSection>,
+    #[serde(default)]
+    aliases: HashMap<String, AliasSection>,
+    #[serde(default)]
+    fallback_chains: HashMap<String, Vec<String>>,
+    retry: Option<RetrySection>,
+    /// `"console"` for `ConsoleGaiseLogger`.
+    logger: Option<String>,
+}
+
+impl GaiseClientConfig {
+    /// Loads the configuration from a single TOML (`.toml`) or YAML (`.yaml`, `.yml`) file.
+    ///
+    /// ```toml
+    /// logger = "console"
+    ///
+    /// [providers.openai]
+    /// api_key = "${OPENAI_API_KEY}"
+    ///
+    /// [endpoints.ollama-gpu]
+    /// provider = "ollama"
+    /// url = "${GPU_HOST:-http://gpu-box:11434}"
+    ///
+    /// [aliases]
+    /// fast = { model = "ollama-gpu::llama3", generation_config = { temperature = 0.2 } }
+    ///
+    /// [retry]
+    /// max_attempts = 5
+    /// ```
+    ///
+    /// `${NAME}` in a string is replaced by the environment variable `NAME`, and
+    /// `${NAME:-default}` falls back to `default` when it is not set.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(ConfigError::Io(format!("Config file {} does not exist", path.display())));
+        }
+        Self::from_files(&[path])
+    }
+
+    /// Loads and layers several configuration files: a setting in a later file overrides
+    /// the same setting in an earlier one, tables are merged key by key.
+    /// Files that do not exist are skipped.
+    pub fn from_files<P: AsRef<Path>>(paths: &[P]) -> Result<Self, ConfigError> {
+        Self::from_values(read_files(paths)?)
+    }
+
+    /// Like `from_files`, for configuration that is already parsed, e.g. a section of
+    /// another application's config file. Each value has the layout of a configuration file.
+    pub fn from_values(layers: impl IntoIterator<Item = Value>) -> Result<Self, ConfigError> {
+        let mut merged = Value::Object(Default::default());
+        for layer in layers {
+            merge(&mut merged, layer);
+        }
+        let file: ConfigFile = serde_json::from_value(interpolate_value(merged)?)
+            .map_err(|e| ConfigError::Parse(format!("Invalid configuration: {}", e)))?;
+        Self::from_config_file(file)
+    }
+
+    /// Loads the files listed in `GAISE_CONFIG` (if any), then applies the provider
+    /// variables `OLLAMA_URL`, `OPENAI_API_URL`, `OPENAI_API_KEY`, `ANTHROPIC_API_URL`,
+    /// `ANTHROPIC_API_KEY`, `BEDROCK_REGION`, `VERTEXAI_API_URL` and `VERTEXAI_SA_PATH`
+    /// on top. Without `BEDROCK_REGION`, Bedrock uses the AWS SDK's own `AWS_REGION`.
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_env_with(Vec::new())
+    }
+
+    /// Like `from_env`, with `layers` applied after the files in `GAISE_CONFIG`
+    /// and before the environment variables.
+    pub fn from_env_with(layers: Vec<Value>) -> Result<Self, ConfigError> {
+        let paths: Vec<PathBuf> = std::env::var_os(CONFIG_PATH_VAR)
+            .map(|paths| std::env::split_paths(&paths).collect())
+            .unwrap_or_default();
+        for path in &paths {
+            if !path.exists() {
+                return Err(ConfigError::Io(format!("Config file {} does not exist", path.display())));
+            }
+        }
+        let mut values = read_files(&paths)?;
+        values.extend(layers);
+        let mut config = Self::from_values(values)?;
+        config.apply_env()?;
+        Ok(config)
+    }
+
+    fn apply_env(&mut self) -> Result<(), ConfigError> {
+        let var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+        #[cfg(feature = "ollama")]
+        if let Some(url) = var("OLLAMA_URL") {
+            self.ollama_url = Some(url);
+        }
+        #[cfg(feature = "openai")]
+        {
+            if let Some(url) = var("OPENAI_API_URL") {
+                self.openai_api_url = Some(url);
+            }
+            if let Some(key) = var("OPENAI_API_KEY") {
+                self.openai_api_key = Some(key);
+            }
+        }
+        #[cfg(feature = "anthropic")]
+        {
+            if let Some(url) = var("ANTHROPIC_API_URL") {
+                self.anthropic_api_url = Some(url);
+            }
+            if let Some(key) = var("ANTHROPIC_API_KEY") {
+                self.anthropic_api_key = Some(key);
+            }
+        }
+        #[cfg(feature = "bedrock")]
+        if let Some(region) = var("BEDROCK_REGION") {
+            self.bedrock_region = Some(region);
+        }
+        #[cfg(feature = "vertexai")]
+        {
+            if let Some(url) = var("VERTEXAI_API_URL") {
+                self.vertexai_api_url = Some(url);
+            }
+            if let Some(path) = var("VERTEXAI_SA_PATH") {
+                self.vertexai_sa = Some(read_service_account(&path)?);
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(unused_variables)]
+    fn from_config_file(file: ConfigFile) -> Result<Self, ConfigError> {
+        let mut config = Self::default();
+        let ConfigFile { providers, endpoints, aliases, fallback_chains, retry, logger } = file;
+
+        #[cfg(feature = "ollama")]
+        if let Some(ollama) = providers.ollama {
+            config.ollama_url = ollama.url;
+        }
+        #[cfg(feature = "vertexai")]
+        if let Some(vertexai) = providers.vertexai {
+            config.vertexai_api_url = vertexai.url;
+            config.vertexai_sa = vertexai.service_account_path.as_deref().map(read_service_account).transpose()?;
+        }
+        #[cfg(feature = "openai")]
+        if let Some(openai) = providers.openai {
+            config.openai_api_url = openai.url;
+            config.openai_api_key = openai.api_key;
+        }
+        #[cfg(feature = "bedrock")]
+        if let Some(bedrock) = providers.bedrock {
+            config.bedrock_region = bedrock.region;
+        }
+        #[cfg(feature = "anthropic")]
+        if let Some(anthropic) = providers.anthropic {
+            config.anthropic_api_url = anthropic.url;
+            config.anthropic_api_key = anthropic.api_key;
+        }
+
+        for (name, section) in endpoints {
+            let provider = section
+                .provider
+                .ok_or_else(|| ConfigError::Parse(format!("Endpoint '{}' has no provider", name)))?;
+            let endpoint = EndpointConfig {
+                provider,
+                url: section.url,
+                api_key: section.api_key,
+                region: section.region,
+                #[cfg(feature = "vertexai")]
+                vertexai_sa: section.service_account_path.as_deref().map(read_service_account).transpose()?,
+            };
+            config.endpoints.insert(name, endpoint);
+        }
+
+        for (name, alias) in aliases {
+            let alias = match alias {
+                AliasSection::Model(model) => ModelAlias::new(&model),
+                AliasSection::Full { model, generation_config } => ModelAlias { model, generation_config },
+            };
+            config.aliases.insert(name, alias);
+        }
+
+        config.fallback_chains = fallback_chains;
+
+        if let Some(retry) = retry {
+            let defaults = RetryPolicy::default();
+            config.retry = RetryPolicy {
+                max_attempts: retry.max_attempts.unwrap_or(defaults.max_attempts),
+                base_delay: retry.base_delay_ms.map(Duration::from_millis).unwrap_or(defaults.base_delay),
+                max_delay: retry.max_delay_ms.map(Duration::from_millis).unwrap_or(defaults.max_delay),
+                jitter: retry.jitter.unwrap_or(defaults.jitter),
+                retry_on: retry.retry_on.unwrap_or(defaults.retry_on),
+            };
+        }
+
+        config.logger = match logger.as_deref() {
+            None => None,
+            Some("console") => Some(Arc::new(ConsoleGaiseLogger)),
+            Some(other) => return Err(ConfigError::Parse(format!("Unknown logger: {}", other))),
+        };
+
+        Ok(config)
+    }
+}
+
+fn read_files<P: AsRef<Path>>(paths: &[P]) -> Result<Vec<Value>, ConfigError> {
+    paths.iter().map(AsRef::as_ref).filter(|path| path.exists()).map(read_file).collect()
+}
+
+fn read_file(path: &Path) -> Result<Value, ConfigError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::Io(format!("Could not read config file {}: {}", path.display(), e)))?;
+    let parse_error = |e: &dyn fmt::Display| ConfigError::Parse(format!("Could not parse config file {}: {}", path.display(), e));
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml") | Some("yml") => serde_yaml::from_str(&content).map_err(|e| parse_error(&e)),
+        _ => toml::from_str(&content).map_err(|e| parse_error(&e)),
+    }
+}
+
+#[cfg(feature = "vertexai")]
+fn read_service_account(path: &str) -> Result<ServiceAccount, ConfigError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::Io(format!("Could not read service account {}: {}", path, e)))?;
+    serde_json::from_str(&content)
+        .map_err(|e| ConfigError::Parse(format!("Could not parse service account {}: {}", path, e)))
+}
+
+/// Merges `layer` into `base`: objects key by key, anything else is replaced.
+fn merge(base: &mut Value, layer: Value) {
+    match (base, layer) {
+        (Value::Object(base), Value::Object(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+fn interpolate_value(value: Value) -> Result<Value, ConfigError> {
+    Ok(match value {
+        Value::String(text) => Value::String(interpolate(&text, |name| std::env::var(name).ok())?),
+        Value::Array(items) => Value::Array(items.into_iter().map(interpolate_value).collect::<Result<_, _>>()?),
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| Ok((key, interpolate_value(value)?)))
+                .collect::<Result<_, ConfigError>>()?,
+        ),
+        other => other,
+    })
+}
+
+/// Doubles every `$` in the strings of `value`, so a layer passed to `from_values` keeps
+/// `${NAME}` as written instead of reading the environment. Meant for layers from files
+/// the user did not write, such as a project's checked-in config.
+pub fn escape_variables(value: Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(text.replace('$', "$$")),
+        Value::Array(items) => Value::Array(items.into_iter().map(escape_variables).collect()),
+        Value::Object(map) => Value::Object(map.into_iter().map(|(key, value)| (key, escape_variables(value))).collect()),
+        other => other,
+    }
+}
+
+/// Replaces `${NAME}` and `${NAME:-default}` in `text` with the values `lookup` returns.
+/// `$$` stands for a literal `$`.
+fn interpolate(text: &str, lookup: impl Fn(&str) -> Option<String>) -> Result<String, ConfigError> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('$') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("$$") {
+            result.push('$');
+            rest = after;
+        } else if let (Some(body), Some(end)) = (rest.strip_prefix("${"), rest.find('}')) {
+            let reference = &body[..end - 2];
+            let (name, default) = match reference.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (reference, None),
+            };
+            let value = lookup(name)
+                .filter(|value| !value.is_empty())
+                .or_else(|| default.map(str::to_string))
+                .ok_or_else(|| ConfigError::MissingVariable(name.to_string()))?;
+            result.push_str(&value);
+            rest = &rest[end + 1..];
+        } else {
+            result.push('$');
+            rest = &rest[1..];
+        }
+    }
+    result.push_str(rest);
+    Ok(result)
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use async_trait::async_trait;
@@ -7,6 +8,7 @@ use std::pin::Pin;
 
 use gaise_core::{
     GaiseClient,
+    GaiseError,
     contracts::{
         GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest,
         GaiseInstructResponse, GaiseInstructStreamResponse,
@@ -26,6 +28,13 @@ use gaise_provider_anthropic::anthropic_client::GaiseClientAnthropic;
 #[cfg(feature = "vertexai")]
 pub use gaise_provider_vertexai::contracts::ServiceAccount;
 
+mod config;
+pub use config::{ConfigError, CONFIG_PATH_VAR, escape_variables};
+mod retry;
+pub use retry::RetryPolicy;
+mod routing;
+pub use routing::{EndpointConfig, ModelAlias};
+
 /// Configuration for the GAISe client service.
 /// This struct holds the necessary URLs and credentials for different AI providers.
 #[derive(Debug, Clone, Default)]
@@ -54,17 +63,27 @@ pub struct GaiseClientConfig {
     /// API key for Anthropic.
     #[cfg(feature = "anthropic")]
     pub anthropic_api_key: Option<String>,
-    /// Optional logger for requests and responses.
+    /// Optional logger for requests, responses and retries.
     pub logger: Option<Arc<dyn IGaiseLogger>>,
+    /// Retries for transient failures such as rate limits and provider outages.
+    pub retry: RetryPolicy,
+    /// Named fallback chains, e.g. `"resilient"` -> `["openai::gpt-4o", "anthropic::claude-sonnet-4-5", "ollama::llama3"]`.
+    /// A request whose `model` is a chain name is tried against each model in turn
+    /// until one succeeds or fails with an error the retry policy does not retry.
+    pub fallback_chains: HashMap<String, Vec<String>>,
+    /// Named endpoints, e.g. `"ollama-gpu"` for a second Ollama host, used as `ollama-gpu::llama3`.
+    pub endpoints: HashMap<String, EndpointConfig>,
+    /// Model aliases, e.g. `"fast"` -> `openai::gpt-4o-mini` with a low temperature.
+    pub aliases: HashMap<String, ModelAlias>,
 }
 
 /// A service that manages and routes requests to multiple Generative AI providers.
 ///
 /// `GaiseClientService` implements the `GaiseClient` trait and uses a provider-prefix
 /// routing mechanism (e.g., "openai::gpt-4o") to delegate calls to the appropriate
-/// provider implementation.
+/// provider implementation. The prefix can also name one of the configured endpoints
+/// (e.g., "ollama-gpu::llama3"), and the whole model string can be an alias (e.g., "fast").
 pub struct GaiseClientService {
-    #[allow(dead_code)]
     config: GaiseClientConfig,
     clients: RwLock<HashMap<String, Arc<dyn GaiseClient>>>,
     logger: Option<Arc<dyn IGaiseLogger>>,
@@ -81,10 +100,11 @@ impl GaiseClientService {
         }
     }
 
-    /// Retrieves an existing client for the specified provider or initializes a new one.
+    /// Retrieves an existing client for the specified provider or endpoint, or initializes a new one.
     ///
     /// Supported providers: "ollama", "vertexai", "openai", "bedrock", "anthropic".
-    pub async fn get_client(&self, provider: &str) -> Result<Arc<dyn GaiseClient>, Box<dyn std::error::Error + Send + Sync>> {
+    /// Any name in `endpoints` is accepted as well.
+    pub async fn get_client(&self, provider: &str) -> Result<Arc<dyn GaiseClient>, GaiseError> {
         {
             let clients = self.clients.read().await;
             if let Some(client) = clients.get(provider) {
@@ -92,41 +112,50 @@ impl GaiseClientService {
             }
         }
 
+        let endpoint = self.config.endpoints.get(provider);
+        let kind = endpoint.map_or(provider, |e| e.provider.as_str());
+        #[allow(unused_variables)]
+        let url = endpoint.and_then(|e| e.url.as_deref());
+        #[allow(unused_variables)]
+        let api_key = endpoint.and_then(|e| e.api_key.as_deref());
+
         #[allow(unused_variables)]
-        let client: Arc<dyn GaiseClient> = match provider {
+        let client: Arc<dyn GaiseClient> = match kind {
             #[cfg(feature = "ollama")]
             "ollama" => {
-                let url = self.config.ollama_url.as_deref().unwrap_or("http://localhost:11434");
+                let url = url.or(self.config.ollama_url.as_deref()).unwrap_or("http://localhost:11434");
                 Arc::new(GaiseClientOllama::new(url.to_string()))
             }
             #[cfg(feature = "vertexai")]
             "vertexai" => {
-                let sa = self.config.vertexai_sa.as_ref().ok_or("VertexAI Service Account not configured")?;
-                let url = self.config.vertexai_api_url.as_deref().ok_or("VertexAI API URL not configured")?;
+                let sa = endpoint
+                    .and_then(|e| e.vertexai_sa.as_ref())
+                    .or(self.config.vertexai_sa.as_ref())
+                    .ok_or_else(|| GaiseError::Unauthorized("VertexAI Service Account not configured".to_string()))?;
+                let url = url.or(self.config.vertexai_api_url.as_deref()).ok_or_else(|| GaiseError::InvalidRequest("VertexAI API URL not configured".to_string()))?;
                 Arc::new(GaiseClientVertexAI::new(sa, url.to_string()).await)
             }
             #[cfg(feature = "openai")]
             "openai" => {
-                let url = self.config.openai_api_url.as_deref().unwrap_or("https://api.openai.com/v1");
-                let key = self.config.openai_api_key.as_deref().ok_or("OpenAI API Key not configured")?;
+                let url = url.or(self.config.openai_api_url.as_deref()).unwrap_or("https://api.openai.com/v1");
+                let key = api_key.or(self.config.openai_api_key.as_deref()).ok_or_else(|| GaiseError::Unauthorized("OpenAI API Key not configured".to_string()))?;
                 Arc::new(GaiseClientOpenAI::new(url.to_string(), key.to_string()))
             }
             #[cfg(feature = "bedrock")]
             "bedrock" => {
-                if let Some(region) = &self.config.bedrock_region {
-                    unsafe {
-                        std::env::set_var("AWS_REGION", region);
-                    }
+                match endpoint.and_then(|e| e.region.as_ref()).or(self.config.bedrock_region.as_ref()) {
+                    Some(region) => Arc::new(GaiseClientBedrock::with_region(region.clone()).await),
+                    None => Arc::new(GaiseClientBedrock::new().await),
                 }
-                Arc::new(GaiseClientBedrock::new().await)
             }
             #[cfg(feature = "anthropic")]
             "anthropic" => {
-                let url = self.config.anthropic_api_url.as_deref().unwrap_or("https://api.anthropic.com/v1");
-                let key = self.config.anthropic_api_key.as_deref().ok_or("Anthropic API Key not configured")?;
+                let url = url.or(self.config.anthropic_api_url.as_deref()).unwrap_or("https://api.anthropic.com/v1");
+                let key = api_key.or(self.config.anthropic_api_key.as_deref()).ok_or_else(|| GaiseError::Unauthorized("Anthropic API Key not configured".to_string()))?;
                 Arc::new(GaiseClientAnthropic::new(url.to_string(), key.to_string()))
             }
-            _ => return Err(format!("Unknown or disabled provider: {}", provider).into()),
+            _ if endpoint.is_some() => return Err(GaiseError::InvalidRequest(format!("Endpoint '{}' has an unknown or disabled provider: {}", provider, kind))),
+            _ => return Err(GaiseError::InvalidRequest(format!("Unknown or disabled provider: {}", provider))),
         };
 
         #[allow(unreachable_code)]
@@ -143,41 +172,139 @@ impl GaiseClientService {
         clients.insert(provider.to_string(), client);
     }
 
-    /// Helper to parse a model string into (provider, model_name).
-    /// The expected format is "provider::model_name".
-    fn parse_model(model: &str) -> Result<(&str, &str), Box<dyn std::error::Error + Send + Sync>> {
-        let parts: Vec<&str> = model.splitn(2, "::").collect();
-        if parts.len() < 2 {
-            return Err("Model name must be in the format 'provider::model'".into());
+    /// Runs `call` under the configured retry policy, logging every retry.
+    async fn with_retry<T, F, Fut>(
+        &self,
+        request_type: &str,
+        model: &str,
+        correlation_id: Option<&str>,
+        call: F,
+    ) -> Result<T, GaiseError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GaiseError>>,
+    {
+        self.config
+            .retry
+            .run(call, |attempt, delay, error| {
+                if let Some(logger) = &self.logger {
+                    logger.log_retry(correlation_id, request_type, model, attempt, delay, error);
+                }
+            })
+            .await
+    }
+
+    /// Resolves `model` if it is an alias; any other model stands for itself, without defaults.
+    pub fn resolve_alias(&self, model: &str) -> ModelAlias {
+        self.config
+            .aliases
+            .get(model)
+            .cloned()
+            .unwrap_or_else(|| ModelAlias::new(model))
+    }
+
+    /// `model` as `provider::model`, e.g. for looking up its price: an alias is resolved and a
+    /// named endpoint replaced by its provider, so `fast` standing for `ollama-gpu::llama3`
+    /// gives `ollama::llama3`.
+    pub fn provider_model(&self, model: &str) -> String {
+        let model = self.resolve_alias(model).model;
+        match model.split_once("::") {
+            Some((endpoint, name)) => match self.config.endpoints.get(endpoint) {
+                Some(config) => format!("{}::{}", config.provider, name),
+                None => model,
+            },
+            None => model,
         }
-        Ok((parts[0], parts[1]))
     }
-}
 
-#[async_trait]
-impl GaiseClient for GaiseClientService {
-    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let (provider, actual_model) = Self::parse_model(&request.model)?;
+    /// Resolves `model` (a fallback chain name, an alias or `provider::model`) down to the
+    /// clients it needs, so that an unknown provider or missing credentials are reported
+    /// before the first request.
+    pub async fn check_model(&self, model: &str) -> Result<(), GaiseError> {
+        let models = match self.config.fallback_chains.get(model) {
+            Some(chain) => chain.clone(),
+            None => vec![model.to_string()],
+        };
+        for model in models {
+            let target = self.resolve_alias(&model);
+            let (provider, _) = Self::parse_model(&target.model)?;
+            self.get_client(provider).await?;
+        }
+        Ok(())
+    }
+
+    /// The models to try for `request`, in order: the named chain `request.model` refers to
+    /// (or `request.model` itself), followed by `request.fallback_models`, with aliases resolved.
+    fn model_chain(&self, request: &GaiseInstructRequest) -> Result<Vec<ModelAlias>, GaiseError> {
+        let mut models = match self.config.fallback_chains.get(&request.model) {
+            Some(chain) => chain.clone(),
+            None => vec![request.model.clone()],
+        };
+        models.extend(request.fallback_models.iter().flatten().cloned());
+        if models.is_empty() {
+            return Err(GaiseError::InvalidRequest(format!("Fallback chain '{}' has no models", request.model)));
+        }
+        Ok(models.iter().map(|model| self.resolve_alias(model)).collect())
+    }
+
+    /// Calls `call` with each model of the request's fallback chain until one succeeds.
+    /// A model that fails with a retryable error (after its own retries) hands over to the next;
+    /// any other error, or a failure of the last model, is returned. On success, the model that
+    /// served the request is returned with the result.
+    async fn with_fallback<T, F, Fut>(
+        &self,
+        request_type: &str,
+        request: &GaiseInstructRequest,
+        mut call: F,
+    ) -> Result<(T, String), GaiseError>
+    where
+        F: FnMut(ModelAlias) -> Fut,
+        Fut: Future<Output = Result<T, GaiseError>>,
+    {
+        let models = self.model_chain(request)?;
+        let mut models = models.into_iter().peekable();
+        while let Some(target) = models.next() {
+            let model = target.model.clone();
+            match call(target).await {
+                Ok(value) => return Ok((value, model)),
+                Err(error) => match models.peek() {
+                    Some(next) if self.config.retry.is_retryable(&error) => {
+                        if let Some(logger) = &self.logger {
+                            logger.log_fallback(request.correlation_id.as_deref(), request_type, &model, &next.model, &error);
+                        }
+                    }
+                    _ => return Err(error),
+                },
+            }
+        }
+        unreachable!("model_chain never returns an empty chain")
+    }
+
+    async fn instruct_model(&self, request: &GaiseInstructRequest, target: ModelAlias) -> Result<GaiseInstructResponse, GaiseError> {
+        let model = target.model.clone();
+        let (provider, actual_model) = Self::parse_model(&model)?;
         let client = self.get_client(provider).await?;
 
         if let Some(logger) = &self.logger {
             logger.log_request(
                 request.correlation_id.as_deref(),
                 "instruct",
-                &request.model,
+                &model,
                 serde_json::to_value(request).unwrap_or(serde_json::Value::Null),
             );
         }
 
-        let mut req = request.clone();
-        req.model = actual_model.to_string();
-        let response = client.instruct(&req).await?;
+        let req = Self::provider_request(request, actual_model, target);
+        let mut response = self
+            .with_retry("instruct", &model, request.correlation_id.as_deref(), || client.instruct(&req))
+            .await?;
+        response.model = Some(model.clone());
 
         if let Some(logger) = &self.logger {
             logger.log_response(
                 request.correlation_id.as_deref(),
                 "instruct",
-                &request.model,
+                &model,
                 serde_json::to_value(&response).unwrap_or(serde_json::Value::Null),
                 serde_json::to_value(&response.usage).ok(),
             );
@@ -186,36 +313,83 @@ impl GaiseClient for GaiseClientService {
         Ok(response)
     }
 
-    async fn instruct_stream(
+    async fn instruct_stream_model(
         &self,
         request: &GaiseInstructRequest,
+        target: ModelAlias,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>,
-        Box<dyn std::error::Error + Send + Sync>,
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>,
+        GaiseError,
     > {
-        let (provider, actual_model) = Self::parse_model(&request.model)?;
+        let model = target.model.clone();
+        let (provider, actual_model) = Self::parse_model(&model)?;
         let client = self.get_client(provider).await?;
 
         if let Some(logger) = &self.logger {
             logger.log_request(
                 request.correlation_id.as_deref(),
                 "instruct_stream",
-                &request.model,
+                &model,
                 serde_json::to_value(request).unwrap_or(serde_json::Value::Null),
             );
         }
 
+        let req = Self::provider_request(request, actual_model, target);
+
+        // Only connecting is retried; once chunks flow, a failure is reported as it happens.
+        self.with_retry("instruct_stream", &model, request.correlation_id.as_deref(), || client.instruct_stream(&req))
+            .await
+    }
+
+    /// The request as sent to a provider: its model name without prefix, no fallbacks,
+    /// and the alias's generation defaults filled in.
+    fn provider_request(request: &GaiseInstructRequest, actual_model: &str, target: ModelAlias) -> GaiseInstructRequest {
         let mut req = request.clone();
         req.model = actual_model.to_string();
+        req.fallback_models = None;
+        if let Some(defaults) = target.generation_config {
+            req.generation_config = Some(req.generation_config.unwrap_or_default().with_defaults(&defaults));
+        }
+        req
+    }
 
-        let stream = client.instruct_stream(&req).await?;
+    /// Helper to parse a model string into (provider, model_name).
+    /// The expected format is "provider::model_name".
+    fn parse_model(model: &str) -> Result<(&str, &str), GaiseError> {
+        let parts: Vec<&str> = model.splitn(2, "::").collect();
+        if parts.len() < 2 {
+            return Err(GaiseError::InvalidRequest("Model name must be in the format 'provider::model'".to_string()));
+        }
+        Ok((parts[0], parts[1]))
+    }
+}
+
+#[async_trait]
+impl GaiseClient for GaiseClientService {
+    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
+        let (response, _) = self
+            .with_fallback("instruct", request, |model| self.instruct_model(request, model))
+            .await?;
+        Ok(response)
+    }
+
+    async fn instruct_stream(
+        &self,
+        request: &GaiseInstructRequest,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>,
+        GaiseError,
+    > {
+        // Falling back happens while connecting; a stream that breaks off later is not restarted.
+        let (stream, model) = self
+            .with_fallback("instruct_stream", request, |model| self.instruct_stream_model(request, model))
+            .await?;
 
         use futures_util::StreamExt;
         use gaise_core::contracts::GaiseStreamChunk;
 
         let logger = self.logger.clone();
         let correlation_id = request.correlation_id.clone();
-        let model = request.model.clone();
 
         let filtered_stream = stream.filter_map(move |item| {
             let logger = logger.clone();
@@ -224,7 +398,8 @@ impl GaiseClient for GaiseClientService {
 
             async move {
                 match item {
-                    Ok(resp) => {
+                    Ok(mut resp) => {
+                        resp.model = Some(model.clone());
                         if let Some(logger) = logger {
                             logger.log_stream_chunk(
                                 correlation_id.as_deref(),
@@ -234,10 +409,10 @@ impl GaiseClient for GaiseClientService {
                             );
                         }
 
-                        if let GaiseStreamChunk::Text(ref t) = resp.chunk {
-                            if t.is_empty() {
-                                return None;
-                            }
+                        if let GaiseStreamChunk::Text(ref t) = resp.chunk
+                            && t.is_empty()
+                        {
+                            return None;
                         }
                         Some(Ok(resp))
                     }
@@ -249,28 +424,31 @@ impl GaiseClient for GaiseClientService {
         Ok(Box::pin(filtered_stream))
     }
 
-    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let (provider, actual_model) = Self::parse_model(&request.model)?;
+    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
+        let model = self.resolve_alias(&request.model).model;
+        let (provider, actual_model) = Self::parse_model(&model)?;
         let client = self.get_client(provider).await?;
 
         if let Some(logger) = &self.logger {
             logger.log_request(
                 request.correlation_id.as_deref(),
                 "embeddings",
-                &request.model,
+                &model,
                 serde_json::to_value(request).unwrap_or(serde_json::Value::Null),
             );
         }
 
         let mut req = request.clone();
         req.model = actual_model.to_string();
-        let response = client.embeddings(&req).await?;
+        let response = self
+            .with_retry("embeddings", &model, request.correlation_id.as_deref(), || client.embeddings(&req))
+            .await?;
 
         if let Some(logger) = &self.logger {
             logger.log_response(
                 request.correlation_id.as_deref(),
                 "embeddings",
-                &request.model,
+                &model,
                 serde_json::to_value(&response).unwrap_or(serde_json::Value::Null),
                 serde_json::to_value(&response.usage).ok(),
             );
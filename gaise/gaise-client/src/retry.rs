@@ -0,0 +1,84 @@
+use std::future::Future;
+use std::time::Duration;
+
+use gaise_core::GaiseError;
+
+/// When and how often `GaiseClientService` repeats a failed request.
+///
+/// The n-th retry waits `base_delay * 2^(n-1)`, capped at `max_delay` and spread by `jitter`.
+/// A provider's `Retry-After` replaces the computed delay (still capped at `max_delay`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first one. `1` disables retries.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub base_delay: Duration,
+    /// Upper bound for a single delay.
+    pub max_delay: Duration,
+    /// Fraction (0.0 to 1.0) by which a computed delay is randomly shortened or lengthened,
+    /// so that clients failing together do not retry together.
+    pub jitter: f64,
+    /// Error kinds that are retried, as returned by `GaiseError::kind` (e.g. `"rate_limited"`).
+    pub retry_on: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.2,
+            retry_on: vec![
+                "rate_limited".to_string(),
+                "provider_unavailable".to_string(),
+                "timeout".to_string(),
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    pub fn is_retryable(&self, error: &GaiseError) -> bool {
+        self.retry_on.iter().any(|kind| kind == error.kind())
+    }
+
+    /// Delay before retry number `retry` (1-based) after `error`.
+    pub fn delay(&self, retry: u32, error: &GaiseError) -> Duration {
+        if let GaiseError::RateLimited { retry_after: Some(retry_after), .. } = error {
+            return (*retry_after).min(self.max_delay);
+        }
+
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        delay.mul_f64(1.0 - jitter + 2.0 * jitter * fastrand::f64()).min(self.max_delay)
+    }
+
+    /// Runs `call` until it succeeds, fails with an error this policy does not retry,
+    /// or `max_attempts` is reached. `on_retry` is told about every retry before its delay.
+    pub async fn run<T, F, Fut>(&self, mut call: F, mut on_retry: impl FnMut(u32, Duration, &GaiseError)) -> Result<T, GaiseError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, GaiseError>>,
+    {
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(error) if attempt < self.max_attempts && self.is_retryable(&error) => {
+                    let delay = self.delay(attempt, &error);
+                    on_retry(attempt, delay, &error);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
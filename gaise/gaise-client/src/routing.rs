@@ -0,0 +1,64 @@
+use gaise_core::contracts::GaiseGenerationConfig;
+#[cfg(feature = "vertexai")]
+use gaise_provider_vertexai::contracts::ServiceAccount;
+
+/// A named connection to a provider, addressed like a provider as `name::model`
+/// (e.g. `ollama-gpu::llama3`).
+///
+/// Settings left unset fall back to the provider-level ones in `GaiseClientConfig`,
+/// so an endpoint only needs what sets it apart, such as its URL.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointConfig {
+    /// Provider type: "ollama", "vertexai", "openai", "bedrock" or "anthropic".
+    pub provider: String,
+    /// Base URL of the API (ollama, vertexai, openai, anthropic).
+    pub url: Option<String>,
+    /// API key (openai, anthropic).
+    pub api_key: Option<String>,
+    /// AWS region (bedrock).
+    pub region: Option<String>,
+    /// Service Account credentials (vertexai).
+    #[cfg(feature = "vertexai")]
+    pub vertexai_sa: Option<ServiceAccount>,
+}
+
+impl EndpointConfig {
+    pub fn new(provider: &str) -> Self {
+        Self { provider: provider.to_string(), ..Default::default() }
+    }
+
+    pub fn with_url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    pub fn with_api_key(mut self, api_key: &str) -> Self {
+        self.api_key = Some(api_key.to_string());
+        self
+    }
+
+    pub fn with_region(mut self, region: &str) -> Self {
+        self.region = Some(region.to_string());
+        self
+    }
+}
+
+/// A short name (e.g. `fast`) that stands for a model and default generation settings.
+#[derive(Debug, Clone, Default)]
+pub struct ModelAlias {
+    /// The model the alias stands for, as `provider::model` or `endpoint::model`.
+    pub model: String,
+    /// Defaults for the generation settings a request leaves unset.
+    pub generation_config: Option<GaiseGenerationConfig>,
+}
+
+impl ModelAlias {
+    pub fn new(model: &str) -> Self {
+        Self { model: model.to_string(), generation_config: None }
+    }
+
+    pub fn with_generation_config(mut self, generation_config: GaiseGenerationConfig) -> Self {
+        self.generation_config = Some(generation_config);
+        self
+    }
+}
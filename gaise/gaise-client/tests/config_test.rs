@@ -0,0 +1,160 @@
+use gaise_client::{ConfigError, GaiseClientConfig, escape_variables};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn write(dir: &TempDir, name: &str, content: &str) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_toml_file_covers_providers_endpoints_aliases_and_retry() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "gaise.toml", r#"
+logger = "console"
+
+[providers.openai]
+api_key = "sk-test"
+
+[endpoints.ollama-gpu]
+provider = "ollama"
+url = "http://gpu-box:11434"
+
+[aliases]
+smart = "openai::gpt-4o"
+fast = { model = "ollama-gpu::llama3", generation_config = { temperature = 0.2 } }
+
+[fallback_chains]
+resilient = ["openai::gpt-4o", "ollama-gpu::llama3"]
+
+[retry]
+max_attempts = 5
+base_delay_ms = 100
+"#);
+
+    let config = GaiseClientConfig::from_file(&path).unwrap();
+
+    assert_eq!(config.openai_api_key.as_deref(), Some("sk-test"));
+    assert_eq!(config.endpoints["ollama-gpu"].provider, "ollama");
+    assert_eq!(config.endpoints["ollama-gpu"].url.as_deref(), Some("http://gpu-box:11434"));
+    assert_eq!(config.aliases["smart"].model, "openai::gpt-4o");
+    assert!(config.aliases["smart"].generation_config.is_none());
+    assert_eq!(config.aliases["fast"].generation_config.as_ref().unwrap().temperature, Some(0.2));
+    assert_eq!(config.fallback_chains["resilient"].len(), 2);
+    assert_eq!(config.retry.max_attempts, 5);
+    assert_eq!(config.retry.base_delay, Duration::from_millis(100));
+    assert_eq!(config.retry.max_delay, Duration::from_secs(30));
+    assert!(config.logger.is_some());
+}
+
+#[test]
+fn test_yaml_file() {
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "gaise.yaml", "
+providers:
+  anthropic:
+    api_key: sk-ant
+endpoints:
+  bedrock-eu:
+    provider: bedrock
+    region: eu-west-1
+");
+
+    let config = GaiseClientConfig::from_file(&path).unwrap();
+
+    assert_eq!(config.anthropic_api_key.as_deref(), Some("sk-ant"));
+    assert_eq!(config.endpoints["bedrock-eu"].region.as_deref(), Some("eu-west-1"));
+}
+
+#[test]
+fn test_later_files_override_earlier_ones() {
+    let dir = TempDir::new().unwrap();
+    let global = write(&dir, "global.toml", r#"
+[providers.openai]
+url = "https://api.openai.com/v1"
+api_key = "global-key"
+
+[aliases]
+fast = "openai::gpt-4o-mini"
+"#);
+    let project = write(&dir, "project.yml", "
+providers:
+  openai:
+    api_key: project-key
+aliases:
+  smart: openai::gpt-4o
+");
+    let missing = dir.path().join("missing.toml");
+
+    let config = GaiseClientConfig::from_files(&[global, project, missing]).unwrap();
+
+    assert_eq!(config.openai_api_url.as_deref(), Some("https://api.openai.com/v1"));
+    assert_eq!(config.openai_api_key.as_deref(), Some("project-key"));
+    assert_eq!(config.aliases.len(), 2);
+}
+
+#[test]
+fn test_env_interpolation() {
+    unsafe {
+        std::env::set_var("GAISE_CONFIG_TEST_KEY", "sk-from-env");
+        std::env::remove_var("GAISE_CONFIG_TEST_UNSET");
+    }
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "gaise.toml", r#"
+[providers.openai]
+api_key = "${GAISE_CONFIG_TEST_KEY}"
+url = "${GAISE_CONFIG_TEST_UNSET:-http://localhost:8080}/v1"
+
+[providers.ollama]
+url = "http://host/$$literal"
+"#);
+
+    let config = GaiseClientConfig::from_file(&path).unwrap();
+
+    assert_eq!(config.openai_api_key.as_deref(), Some("sk-from-env"));
+    assert_eq!(config.openai_api_url.as_deref(), Some("http://localhost:8080/v1"));
+    assert_eq!(config.ollama_url.as_deref(), Some("http://host/$literal"));
+}
+
+#[test]
+fn test_escaped_layer_keeps_variables_as_written() {
+    unsafe {
+        std::env::set_var("GAISE_CONFIG_TEST_SECRET", "sk-secret");
+    }
+    let layer = serde_json::json!({
+        "endpoints": { "box": { "provider": "openai", "url": "http://evil/${GAISE_CONFIG_TEST_SECRET}", "api_key": "$$x" } }
+    });
+
+    let config = GaiseClientConfig::from_values([escape_variables(layer)]).unwrap();
+
+    let endpoint = &config.endpoints["box"];
+    assert_eq!(endpoint.url.as_deref(), Some("http://evil/${GAISE_CONFIG_TEST_SECRET}"));
+    assert_eq!(endpoint.api_key.as_deref(), Some("$$x"));
+}
+
+#[test]
+fn test_missing_variable_is_an_error() {
+    unsafe {
+        std::env::remove_var("GAISE_CONFIG_TEST_MISSING");
+    }
+    let dir = TempDir::new().unwrap();
+    let path = write(&dir, "gaise.toml", "[providers.openai]\napi_key = \"${GAISE_CONFIG_TEST_MISSING}\"\n");
+
+    let err = GaiseClientConfig::from_file(&path).unwrap_err();
+
+    assert_eq!(err, ConfigError::MissingVariable("GAISE_CONFIG_TEST_MISSING".to_string()));
+}
+
+#[test]
+fn test_unknown_keys_and_endpoints_without_provider_are_rejected() {
+    let dir = TempDir::new().unwrap();
+    let typo = write(&dir, "typo.toml", "[providers.openai]\napi_kye = \"sk\"\n");
+    let endpoint = write(&dir, "endpoint.toml", "[endpoints.gpu]\nurl = \"http://gpu\"\n");
+
+    assert!(matches!(GaiseClientConfig::from_file(&typo), Err(ConfigError::Parse(_))));
+    assert!(matches!(GaiseClientConfig::from_file(&endpoint), Err(ConfigError::Parse(_))));
+    assert!(matches!(GaiseClientConfig::from_file(dir.path().join("none.toml")), Err(ConfigError::Io(_))));
+}
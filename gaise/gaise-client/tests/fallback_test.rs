@@ -0,0 +1,197 @@
+use gaise_client::{GaiseClientConfig, GaiseClientService, RetryPolicy};
+use gaise_core::contracts::{
+    GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest, GaiseInstructResponse,
+    GaiseInstructStreamResponse, GaiseMessage, GaiseStreamAccumulator, GaiseStreamChunk, OneOrMany,
+};
+use gaise_core::logging::IGaiseLogger;
+use gaise_core::{GaiseClient, GaiseError};
+use futures_util::{Stream, StreamExt};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct FallbackLogger {
+    fallbacks: Arc<Mutex<Vec<String>>>,
+}
+impl IGaiseLogger for FallbackLogger {
+    fn log_request(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value) {}
+    fn log_response(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value, _usage: Option<Value>) {}
+    fn log_stream_chunk(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value) {}
+    fn log_retry(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _attempt: u32, _delay: Duration, _error: &GaiseError) {}
+    fn log_fallback(&self, _cid: Option<&str>, req_type: &str, model: &str, next_model: &str, error: &GaiseError) {
+        let mut fallbacks = self.fallbacks.lock().unwrap();
+        fallbacks.push(format!("type={}, model={}, next={}, error={}", req_type, model, next_model, error.kind()));
+    }
+}
+
+/// Fails every call with `error`, or succeeds if there is none. Records the models it was asked for.
+struct MockClient {
+    error: Option<GaiseError>,
+    models: Mutex<Vec<String>>,
+}
+impl MockClient {
+    fn new(error: Option<GaiseError>) -> Arc<Self> {
+        Arc::new(Self { error, models: Mutex::new(Vec::new()) })
+    }
+    fn call(&self, req: &GaiseInstructRequest) -> Result<(), GaiseError> {
+        self.models.lock().unwrap().push(req.model.clone());
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
+    }
+    fn calls(&self) -> usize {
+        self.models.lock().unwrap().len()
+    }
+}
+#[async_trait]
+impl GaiseClient for MockClient {
+    async fn instruct(&self, req: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
+        self.call(req)?;
+        Ok(GaiseInstructResponse {
+            output: OneOrMany::One(GaiseMessage::default()),
+            external_id: None,
+            usage: None,
+            model: None,
+        })
+    }
+    async fn instruct_stream(&self, req: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>, GaiseError> {
+        self.call(req)?;
+        let chunks = vec![Ok(GaiseInstructStreamResponse {
+            chunk: GaiseStreamChunk::Text("hi".to_string()),
+            external_id: None,
+            model: None,
+        })];
+        Ok(Box::pin(futures_util::stream::iter(chunks)))
+    }
+    async fn embeddings(&self, _req: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
+        Err(GaiseError::Unsupported("no embeddings".to_string()))
+    }
+}
+
+async fn service_with(clients: &[(&str, Arc<MockClient>)], fallback_chains: HashMap<String, Vec<String>>, logger: Option<Arc<FallbackLogger>>) -> GaiseClientService {
+    let config = GaiseClientConfig {
+        retry: RetryPolicy::none(),
+        fallback_chains,
+        logger: logger.map(|l| l as Arc<dyn IGaiseLogger>),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+    for (provider, client) in clients {
+        service.add_client(provider, client.clone()).await;
+    }
+    service
+}
+
+fn request(model: &str, fallback_models: Option<Vec<&str>>) -> GaiseInstructRequest {
+    GaiseInstructRequest {
+        model: model.to_string(),
+        fallback_models: fallback_models.map(|models| models.into_iter().map(String::from).collect()),
+        input: OneOrMany::One(GaiseMessage::default()),
+        ..Default::default()
+    }
+}
+
+fn unavailable() -> GaiseError {
+    GaiseError::ProviderUnavailable("down".to_string())
+}
+
+#[tokio::test]
+async fn test_falls_back_to_next_model_and_reports_it() {
+    let primary = MockClient::new(Some(unavailable()));
+    let backup = MockClient::new(None);
+    let logger = Arc::new(FallbackLogger::default());
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], HashMap::new(), Some(logger.clone())).await;
+
+    let response = service.instruct(&request("primary::big", Some(vec!["backup::small"]))).await.unwrap();
+
+    assert_eq!(response.model.as_deref(), Some("backup::small"));
+    assert_eq!(*primary.models.lock().unwrap(), vec!["big".to_string()]);
+    assert_eq!(*backup.models.lock().unwrap(), vec!["small".to_string()]);
+    assert_eq!(*logger.fallbacks.lock().unwrap(), vec![
+        "type=instruct, model=primary::big, next=backup::small, error=provider_unavailable".to_string(),
+    ]);
+}
+
+#[tokio::test]
+async fn test_named_chain_from_config() {
+    let primary = MockClient::new(Some(GaiseError::RateLimited { retry_after: None, message: "slow down".to_string() }));
+    let backup = MockClient::new(None);
+    let chains = HashMap::from([(
+        "resilient".to_string(),
+        vec!["primary::big".to_string(), "backup::small".to_string()],
+    )]);
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], chains, None).await;
+
+    let response = service.instruct(&request("resilient", None)).await.unwrap();
+
+    assert_eq!(response.model.as_deref(), Some("backup::small"));
+    assert_eq!(primary.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_first_model_serves_without_fallback() {
+    let primary = MockClient::new(None);
+    let backup = MockClient::new(None);
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], HashMap::new(), None).await;
+
+    let response = service.instruct(&request("primary::big", Some(vec!["backup::small"]))).await.unwrap();
+
+    assert_eq!(response.model.as_deref(), Some("primary::big"));
+    assert_eq!(backup.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_does_not_fall_back_on_other_errors() {
+    let primary = MockClient::new(Some(GaiseError::ContextLengthExceeded("too long".to_string())));
+    let backup = MockClient::new(None);
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], HashMap::new(), None).await;
+
+    let err = service.instruct(&request("primary::big", Some(vec!["backup::small"]))).await.unwrap_err();
+
+    assert_eq!(err.kind(), "context_length_exceeded");
+    assert_eq!(backup.calls(), 0);
+}
+
+#[tokio::test]
+async fn test_returns_last_error_when_every_model_fails() {
+    let primary = MockClient::new(Some(unavailable()));
+    let backup = MockClient::new(Some(GaiseError::Timeout("slow".to_string())));
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], HashMap::new(), None).await;
+
+    let err = service.instruct(&request("primary::big", Some(vec!["backup::small"]))).await.unwrap_err();
+
+    assert_eq!(err, GaiseError::Timeout("slow".to_string()));
+    assert_eq!(primary.calls(), 1);
+    assert_eq!(backup.calls(), 1);
+}
+
+#[tokio::test]
+async fn test_stream_falls_back_and_tags_chunks() {
+    let primary = MockClient::new(Some(unavailable()));
+    let backup = MockClient::new(None);
+    let service = service_with(&[("primary", primary.clone()), ("backup", backup.clone())], HashMap::new(), None).await;
+
+    let mut stream = service.instruct_stream(&request("primary::big", Some(vec!["backup::small"]))).await.unwrap();
+    let mut accumulator = GaiseStreamAccumulator::new();
+    while let Some(chunk) = stream.next().await {
+        accumulator.push(&chunk.unwrap());
+    }
+
+    assert_eq!(accumulator.model.as_deref(), Some("backup::small"));
+    assert_eq!(accumulator.text, "hi");
+}
+
+#[tokio::test]
+async fn test_empty_named_chain_is_rejected() {
+    let chains = HashMap::from([("empty".to_string(), Vec::new())]);
+    let service = service_with(&[], chains, None).await;
+
+    let err = service.instruct(&request("empty", None)).await.unwrap_err();
+
+    assert_eq!(err.kind(), "invalid_request");
+}
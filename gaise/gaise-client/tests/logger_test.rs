@@ -1,5 +1,5 @@
 use gaise_client::{GaiseClientConfig, GaiseClientService};
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
 use gaise_core::contracts::{
     GaiseInstructRequest, GaiseInstructResponse, GaiseEmbeddingsRequest, GaiseEmbeddingsResponse,
     GaiseInstructStreamResponse, GaiseStreamChunk, OneOrMany, GaiseMessage,
@@ -9,6 +9,7 @@ use futures_util::{Stream, StreamExt};
 use async_trait::async_trait;
 use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use serde_json::Value;
 #[derive(Debug, Default)]
 struct TestLogger {
@@ -27,27 +28,37 @@ impl IGaiseLogger for TestLogger {
         let mut logs = self.logs.lock().unwrap();
         logs.push(format!("CHUNK: cid={:?}, type={}, model={}", cid, req_type, model));
     }
+    fn log_retry(&self, cid: Option<&str>, req_type: &str, model: &str, attempt: u32, _delay: Duration, error: &GaiseError) {
+        let mut logs = self.logs.lock().unwrap();
+        logs.push(format!("RETRY: cid={:?}, type={}, model={}, attempt={}, error={}", cid, req_type, model, attempt, error.kind()));
+    }
+    fn log_fallback(&self, cid: Option<&str>, req_type: &str, model: &str, next_model: &str, error: &GaiseError) {
+        let mut logs = self.logs.lock().unwrap();
+        logs.push(format!("FALLBACK: cid={:?}, type={}, model={}, next={}, error={}", cid, req_type, model, next_model, error.kind()));
+    }
 }
 struct MockClient;
 #[async_trait]
 impl GaiseClient for MockClient {
-    async fn instruct(&self, _req: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn instruct(&self, _req: &GaiseInstructRequest) -> Result<GaiseInstructResponse, gaise_core::GaiseError> {
         Ok(GaiseInstructResponse {
             output: OneOrMany::One(GaiseMessage::default()),
             external_id: None,
             usage: None,
+            model: None,
         })
     }
-    async fn instruct_stream(&self, _req: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn instruct_stream(&self, _req: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, gaise_core::GaiseError>> + Send>>, gaise_core::GaiseError> {
         let chunks = vec![
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text("hi".to_string()),
                 external_id: None,
+                model: None,
             }),
         ];
         Ok(Box::pin(futures_util::stream::iter(chunks)))
     }
-    async fn embeddings(&self, _req: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn embeddings(&self, _req: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, gaise_core::GaiseError> {
         Ok(GaiseEmbeddingsResponse {
             external_id: None,
             output: vec![vec![0.1]],
@@ -81,7 +92,7 @@ async fn test_logger_integration() {
     }
     // Test Stream
     let mut stream = service.instruct_stream(&req).await.unwrap();
-    while let Some(_) = stream.next().await {}
+    while stream.next().await.is_some() {}
     {
         let l = logs.lock().unwrap();
         assert!(l.contains(&"REQ: cid=Some(\"cid1\"), type=instruct_stream, model=mock::model".to_string()));
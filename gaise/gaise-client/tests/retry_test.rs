@@ -0,0 +1,205 @@
+use gaise_client::{GaiseClientConfig, GaiseClientService, RetryPolicy};
+use gaise_core::contracts::{
+    GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest, GaiseInstructResponse,
+    GaiseInstructStreamResponse, GaiseMessage, GaiseStreamChunk, OneOrMany,
+};
+use gaise_core::logging::IGaiseLogger;
+use gaise_core::{GaiseClient, GaiseError};
+use futures_util::{Stream, StreamExt};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(Debug, Default)]
+struct RetryLogger {
+    retries: Arc<Mutex<Vec<String>>>,
+}
+impl IGaiseLogger for RetryLogger {
+    fn log_request(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value) {}
+    fn log_response(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value, _usage: Option<Value>) {}
+    fn log_stream_chunk(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _json: Value) {}
+    fn log_retry(&self, cid: Option<&str>, req_type: &str, model: &str, attempt: u32, _delay: Duration, error: &GaiseError) {
+        let mut retries = self.retries.lock().unwrap();
+        retries.push(format!("cid={:?}, type={}, model={}, attempt={}, error={}", cid, req_type, model, attempt, error.kind()));
+    }
+    fn log_fallback(&self, _cid: Option<&str>, _req_type: &str, _model: &str, _next_model: &str, _error: &GaiseError) {}
+}
+
+/// Fails every call with `error` until `failures` calls have failed, then succeeds.
+struct FlakyClient {
+    error: GaiseError,
+    failures: usize,
+    calls: AtomicUsize,
+}
+impl FlakyClient {
+    fn new(error: GaiseError, failures: usize) -> Self {
+        Self { error, failures, calls: AtomicUsize::new(0) }
+    }
+    fn call(&self) -> Result<(), GaiseError> {
+        if self.calls.fetch_add(1, Ordering::SeqCst) < self.failures {
+            Err(self.error.clone())
+        } else {
+            Ok(())
+        }
+    }
+}
+#[async_trait]
+impl GaiseClient for FlakyClient {
+    async fn instruct(&self, _req: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
+        self.call()?;
+        Ok(GaiseInstructResponse {
+            output: OneOrMany::One(GaiseMessage::default()),
+            external_id: None,
+            usage: None,
+            model: None,
+        })
+    }
+    async fn instruct_stream(&self, _req: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>, GaiseError> {
+        self.call()?;
+        let chunks = vec![Ok(GaiseInstructStreamResponse {
+            chunk: GaiseStreamChunk::Text("hi".to_string()),
+            external_id: None,
+            model: None,
+        })];
+        Ok(Box::pin(futures_util::stream::iter(chunks)))
+    }
+    async fn embeddings(&self, _req: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
+        self.call()?;
+        Ok(GaiseEmbeddingsResponse {
+            external_id: None,
+            output: vec![vec![0.1]],
+            usage: None,
+        })
+    }
+}
+
+fn fast_policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        base_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(10),
+        ..Default::default()
+    }
+}
+
+async fn service_with(client: Arc<FlakyClient>, retry: RetryPolicy, logger: Option<Arc<RetryLogger>>) -> GaiseClientService {
+    let config = GaiseClientConfig {
+        retry,
+        logger: logger.map(|l| l as Arc<dyn IGaiseLogger>),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+    service.add_client("mock", client).await;
+    service
+}
+
+fn instruct_request() -> GaiseInstructRequest {
+    GaiseInstructRequest {
+        model: "mock::model".to_string(),
+        correlation_id: Some("cid1".to_string()),
+        input: OneOrMany::One(GaiseMessage::default()),
+        ..Default::default()
+    }
+}
+
+fn rate_limited() -> GaiseError {
+    GaiseError::RateLimited { retry_after: Some(Duration::from_millis(1)), message: "slow down".to_string() }
+}
+
+#[tokio::test]
+async fn test_instruct_retries_transient_errors_and_logs_them() {
+    let client = Arc::new(FlakyClient::new(rate_limited(), 2));
+    let logger = Arc::new(RetryLogger::default());
+    let service = service_with(client.clone(), fast_policy(3), Some(logger.clone())).await;
+
+    service.instruct(&instruct_request()).await.unwrap();
+
+    assert_eq!(client.calls.load(Ordering::SeqCst), 3);
+    let retries = logger.retries.lock().unwrap();
+    assert_eq!(*retries, vec![
+        "cid=Some(\"cid1\"), type=instruct, model=mock::model, attempt=1, error=rate_limited".to_string(),
+        "cid=Some(\"cid1\"), type=instruct, model=mock::model, attempt=2, error=rate_limited".to_string(),
+    ]);
+}
+
+#[tokio::test]
+async fn test_gives_up_after_max_attempts() {
+    let client = Arc::new(FlakyClient::new(GaiseError::ProviderUnavailable("down".to_string()), 10));
+    let service = service_with(client.clone(), fast_policy(2), None).await;
+
+    let err = service.instruct(&instruct_request()).await.unwrap_err();
+
+    assert_eq!(err, GaiseError::ProviderUnavailable("down".to_string()));
+    assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_does_not_retry_other_error_kinds() {
+    let client = Arc::new(FlakyClient::new(GaiseError::Unauthorized("bad key".to_string()), 1));
+    let service = service_with(client.clone(), fast_policy(3), None).await;
+
+    assert!(service.instruct(&instruct_request()).await.is_err());
+    assert_eq!(client.calls.load(Ordering::SeqCst), 1);
+
+    let client = Arc::new(FlakyClient::new(rate_limited(), 1));
+    let policy = RetryPolicy { retry_on: vec!["timeout".to_string()], ..fast_policy(3) };
+    let service = service_with(client.clone(), policy, None).await;
+
+    assert!(service.instruct(&instruct_request()).await.is_err());
+    assert_eq!(client.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_stream_connect_and_embeddings_are_retried() {
+    let client = Arc::new(FlakyClient::new(GaiseError::Timeout("slow".to_string()), 1));
+    let service = service_with(client.clone(), fast_policy(3), None).await;
+
+    let mut stream = service.instruct_stream(&instruct_request()).await.unwrap();
+    while stream.next().await.is_some() {}
+    assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+
+    let client = Arc::new(FlakyClient::new(GaiseError::Timeout("slow".to_string()), 1));
+    let service = service_with(client.clone(), fast_policy(3), None).await;
+    let request = GaiseEmbeddingsRequest {
+        model: "mock::model".to_string(),
+        correlation_id: None,
+        input: OneOrMany::One("test".to_string()),
+    };
+
+    service.embeddings(&request).await.unwrap();
+    assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_delay_backs_off_and_honours_retry_after() {
+    let policy = RetryPolicy {
+        base_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(2),
+        jitter: 0.0,
+        ..Default::default()
+    };
+    let unavailable = GaiseError::ProviderUnavailable("down".to_string());
+
+    assert_eq!(policy.delay(1, &unavailable), Duration::from_millis(100));
+    assert_eq!(policy.delay(2, &unavailable), Duration::from_millis(200));
+    assert_eq!(policy.delay(3, &unavailable), Duration::from_millis(400));
+    assert_eq!(policy.delay(10, &unavailable), Duration::from_secs(2));
+
+    let retry_after = |secs| GaiseError::RateLimited { retry_after: Some(Duration::from_secs(secs)), message: String::new() };
+    assert_eq!(policy.delay(1, &retry_after(1)), Duration::from_secs(1));
+    assert_eq!(policy.delay(1, &retry_after(60)), Duration::from_secs(2));
+}
+
+#[test]
+fn test_delay_jitter_stays_in_range() {
+    let policy = RetryPolicy { base_delay: Duration::from_millis(1000), jitter: 0.5, ..Default::default() };
+    let error = GaiseError::Timeout("slow".to_string());
+
+    for _ in 0..100 {
+        let delay = policy.delay(1, &error);
+        assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_millis(1500), "{:?}", delay);
+    }
+}
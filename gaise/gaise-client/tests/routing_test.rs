@@ -0,0 +1,172 @@
+use gaise_client::{EndpointConfig, GaiseClientConfig, GaiseClientService, ModelAlias, RetryPolicy};
+use gaise_core::contracts::{
+    GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseGenerationConfig, GaiseInstructRequest,
+    GaiseInstructResponse, GaiseInstructStreamResponse, GaiseMessage, OneOrMany,
+};
+use gaise_core::{GaiseClient, GaiseError};
+use futures_util::Stream;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+/// Succeeds every call and records the requests it was sent.
+#[derive(Default)]
+struct RecordingClient {
+    requests: Mutex<Vec<GaiseInstructRequest>>,
+}
+#[async_trait]
+impl GaiseClient for RecordingClient {
+    async fn instruct(&self, req: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
+        self.requests.lock().unwrap().push(req.clone());
+        Ok(GaiseInstructResponse {
+            output: OneOrMany::One(GaiseMessage::default()),
+            external_id: None,
+            usage: None,
+            model: None,
+        })
+    }
+    async fn instruct_stream(&self, _req: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>, GaiseError> {
+        Err(GaiseError::Unsupported("no streaming".to_string()))
+    }
+    async fn embeddings(&self, _req: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
+        Err(GaiseError::Unsupported("no embeddings".to_string()))
+    }
+}
+
+fn fast_alias() -> ModelAlias {
+    ModelAlias::new("ollama-gpu::llama3").with_generation_config(GaiseGenerationConfig {
+        temperature: Some(0.2),
+        max_tokens: Some(512),
+        ..Default::default()
+    })
+}
+
+async fn service_with(client: Arc<RecordingClient>) -> GaiseClientService {
+    let config = GaiseClientConfig {
+        retry: RetryPolicy::none(),
+        endpoints: HashMap::from([(
+            "ollama-gpu".to_string(),
+            EndpointConfig::new("ollama").with_url("http://gpu-box:11434"),
+        )]),
+        aliases: HashMap::from([("fast".to_string(), fast_alias())]),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+    service.add_client("ollama-gpu", client).await;
+    service
+}
+
+fn request(model: &str, generation_config: Option<GaiseGenerationConfig>) -> GaiseInstructRequest {
+    GaiseInstructRequest {
+        model: model.to_string(),
+        input: OneOrMany::One(GaiseMessage::default()),
+        generation_config,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn test_alias_resolves_to_model_and_defaults() {
+    let client = Arc::new(RecordingClient::default());
+    let service = service_with(client.clone()).await;
+
+    let response = service.instruct(&request("fast", None)).await.unwrap();
+
+    assert_eq!(response.model.as_deref(), Some("ollama-gpu::llama3"));
+    let requests = client.requests.lock().unwrap();
+    assert_eq!(requests[0].model, "llama3");
+    let config = requests[0].generation_config.as_ref().unwrap();
+    assert_eq!(config.temperature, Some(0.2));
+    assert_eq!(config.max_tokens, Some(512));
+}
+
+#[tokio::test]
+async fn test_request_settings_override_alias_defaults() {
+    let client = Arc::new(RecordingClient::default());
+    let service = service_with(client.clone()).await;
+    let overrides = GaiseGenerationConfig { temperature: Some(0.9), ..Default::default() };
+
+    service.instruct(&request("fast", Some(overrides))).await.unwrap();
+
+    let requests = client.requests.lock().unwrap();
+    let config = requests[0].generation_config.as_ref().unwrap();
+    assert_eq!(config.temperature, Some(0.9));
+    assert_eq!(config.max_tokens, Some(512));
+}
+
+#[tokio::test]
+async fn test_endpoint_model_without_alias_is_sent_as_is() {
+    let client = Arc::new(RecordingClient::default());
+    let service = service_with(client.clone()).await;
+
+    service.instruct(&request("ollama-gpu::llama3", None)).await.unwrap();
+
+    let requests = client.requests.lock().unwrap();
+    assert_eq!(requests[0].model, "llama3");
+    assert!(requests[0].generation_config.is_none());
+}
+
+#[tokio::test]
+async fn test_provider_model_resolves_aliases_and_endpoints() {
+    let service = service_with(Arc::new(RecordingClient::default())).await;
+
+    assert_eq!(service.provider_model("fast"), "ollama::llama3");
+    assert_eq!(service.provider_model("ollama-gpu::llama3"), "ollama::llama3");
+    assert_eq!(service.provider_model("openai::gpt-4o"), "openai::gpt-4o");
+    assert_eq!(service.provider_model("llama3"), "llama3");
+}
+
+#[tokio::test]
+async fn test_endpoint_builds_client_of_its_provider() {
+    let config = GaiseClientConfig {
+        endpoints: HashMap::from([
+            ("ollama-gpu".to_string(), EndpointConfig::new("ollama").with_url("http://gpu-box:11434")),
+            ("mystery".to_string(), EndpointConfig::new("mystery")),
+        ]),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+
+    assert!(service.get_client("ollama-gpu").await.is_ok());
+    let err = service.get_client("mystery").await.err().unwrap();
+    assert_eq!(err.kind(), "invalid_request");
+}
+
+#[tokio::test]
+async fn test_endpoint_falls_back_to_provider_credentials() {
+    let config = GaiseClientConfig {
+        endpoints: HashMap::from([(
+            "openai-azure".to_string(),
+            EndpointConfig::new("openai").with_url("https://example.openai.azure.com/v1"),
+        )]),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+
+    let err = service.get_client("openai-azure").await.err().unwrap();
+    assert!(err.to_string().contains("OpenAI API Key not configured"));
+}
+
+#[tokio::test]
+async fn test_check_model_resolves_aliases_and_chains() {
+    let config = GaiseClientConfig {
+        endpoints: HashMap::from([(
+            "ollama-gpu".to_string(),
+            EndpointConfig::new("ollama").with_url("http://gpu-box:11434"),
+        )]),
+        aliases: HashMap::from([("fast".to_string(), fast_alias())]),
+        fallback_chains: HashMap::from([(
+            "resilient".to_string(),
+            vec!["fast".to_string(), "openai::gpt-4o".to_string()],
+        )]),
+        ..Default::default()
+    };
+    let service = GaiseClientService::new(config);
+
+    assert!(service.check_model("fast").await.is_ok());
+    assert!(service.check_model("ollama-gpu::llama3").await.is_ok());
+    assert!(service.check_model("llama3").await.is_err());
+    let err = service.check_model("resilient").await.unwrap_err();
+    assert!(err.to_string().contains("OpenAI API Key not configured"));
+}
@@ -16,21 +16,24 @@ impl GaiseClient for MockClient {
         &self,
         _request: &GaiseInstructRequest,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>,
-        Box<dyn std::error::Error + Send + Sync>,
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, gaise_core::GaiseError>> + Send>>,
+        gaise_core::GaiseError,
     > {
         let chunks = vec![
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text("Hello".to_string()),
                 external_id: None,
+                model: None,
             }),
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text("".to_string()),
                 external_id: None,
+                model: None,
             }),
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text(" World".to_string()),
                 external_id: None,
+                model: None,
             }),
         ];
         let stream = futures_util::stream::iter(chunks);
@@ -40,14 +43,14 @@ impl GaiseClient for MockClient {
     async fn instruct(
         &self,
         _request: &GaiseInstructRequest,
-    ) -> Result<gaise_core::contracts::GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<gaise_core::contracts::GaiseInstructResponse, gaise_core::GaiseError> {
         todo!()
     }
 
     async fn embeddings(
         &self,
         _request: &gaise_core::contracts::GaiseEmbeddingsRequest,
-    ) -> Result<gaise_core::contracts::GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<gaise_core::contracts::GaiseEmbeddingsResponse, gaise_core::GaiseError> {
         todo!()
     }
 }
@@ -22,4 +22,19 @@ pub struct GaiseGenerationConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_key:Option<String>,
-}
\ No newline at end of file
+}
+
+impl GaiseGenerationConfig {
+    /// Fills every field that is not set with the value from `defaults`.
+    pub fn with_defaults(self, defaults: &GaiseGenerationConfig) -> Self {
+        Self {
+            temperature: self.temperature.or(defaults.temperature),
+            top_k: self.top_k.or(defaults.top_k),
+            top_p: self.top_p.or(defaults.top_p),
+            max_tokens: self.max_tokens.or(defaults.max_tokens),
+            thinking_tokens: self.thinking_tokens.or(defaults.thinking_tokens),
+            thinking_effort: self.thinking_effort.or_else(|| defaults.thinking_effort.clone()),
+            cache_key: self.cache_key.or_else(|| defaults.cache_key.clone()),
+        }
+    }
+}
@@ -19,5 +19,9 @@ pub struct GaiseInstructRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub generation_config:Option<GaiseGenerationConfig>,
 
+    /// Models to try in order, as `provider::model`, if `model` fails with a retryable error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_models:Option<Vec<String>>,
+
     pub input:OneOrMany<GaiseMessage>
 }
@@ -9,5 +9,9 @@ pub struct GaiseInstructResponse {
     pub external_id:Option<String>,    
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub usage:Option<GaiseUsage>
+    pub usage:Option<GaiseUsage>,
+
+    /// The `provider::model` that served the request, set by `GaiseClientService`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model:Option<String>
 }
\ No newline at end of file
@@ -22,6 +22,10 @@ pub struct GaiseInstructStreamResponse {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub external_id: Option<String>,
+
+    /// The `provider::model` that served the request, set by `GaiseClientService`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -31,6 +35,7 @@ pub struct GaiseStreamAccumulator {
     pub tool_calls: std::collections::BTreeMap<usize, GaiseToolCall>,
     pub usage: Option<GaiseUsage>,
     pub external_id: Option<String>,
+    pub model: Option<String>,
 }
 
 impl GaiseStreamAccumulator {
@@ -45,6 +50,9 @@ impl GaiseStreamAccumulator {
         if self.external_id.is_none() {
             self.external_id = response.external_id.clone();
         }
+        if self.model.is_none() {
+            self.model = response.model.clone();
+        }
 
         match &response.chunk {
             GaiseStreamChunk::Text(t) => {
@@ -68,19 +76,7 @@ impl GaiseStreamAccumulator {
                 }
             }
             GaiseStreamChunk::Usage(u) => {
-                let current_usage = self.usage.get_or_insert_with(GaiseUsage::default);
-                if let Some(input) = &u.input {
-                    let cur_input = current_usage.input.get_or_insert_with(std::collections::HashMap::new);
-                    for (k, v) in input {
-                        *cur_input.entry(k.clone()).or_insert(0) += v;
-                    }
-                }
-                if let Some(output) = &u.output {
-                    let cur_output = current_usage.output.get_or_insert_with(std::collections::HashMap::new);
-                    for (k, v) in output {
-                        *cur_output.entry(k.clone()).or_insert(0) += v;
-                    }
-                }
+                self.usage.get_or_insert_with(GaiseUsage::default).add(u);
             }
         }
     }
@@ -1,6 +1,18 @@
 use std::collections::HashMap;
 
-#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default)]
+/// Token usage reported by a provider.
+///
+/// The `*_tokens` fields mean the same thing for every provider, so usage can be summed
+/// without knowing where it came from:
+/// - `input_tokens`: all prompt tokens, including tokens read from or written to the cache
+/// - `output_tokens`: all generated tokens, including reasoning tokens
+/// - `cache_read_tokens` / `cache_write_tokens`: the part of `input_tokens` served from / added to the cache
+/// - `reasoning_tokens`: the part of `output_tokens` spent on reasoning
+/// - `total_tokens`: `input_tokens + output_tokens`
+///
+/// `input` and `output` keep the raw counters under the provider's own names
+/// (e.g. `prompt_tokens`, `cache_read_input_tokens`, `thoughtsTokenCount`).
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Default, PartialEq)]
 pub struct GaiseUsage {
 
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -8,4 +20,72 @@ pub struct GaiseUsage {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub output:Option<HashMap<String, usize>>,
-}
\ No newline at end of file
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_tokens:Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_tokens:Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_tokens:Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_write_tokens:Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens:Option<usize>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_tokens:Option<usize>,
+}
+
+impl GaiseUsage {
+    /// Usage with the canonical input and output counts set and `total_tokens` derived from them.
+    pub fn new(input_tokens: usize, output_tokens: usize) -> Self {
+        Self {
+            input_tokens: Some(input_tokens),
+            output_tokens: Some(output_tokens),
+            total_tokens: Some(input_tokens + output_tokens),
+            ..Default::default()
+        }
+    }
+
+    /// Keeps a raw provider counter in the `input` extras.
+    pub fn with_raw_input(mut self, key: &str, value: usize) -> Self {
+        self.input.get_or_insert_with(HashMap::new).insert(key.to_string(), value);
+        self
+    }
+
+    /// Keeps a raw provider counter in the `output` extras.
+    pub fn with_raw_output(mut self, key: &str, value: usize) -> Self {
+        self.output.get_or_insert_with(HashMap::new).insert(key.to_string(), value);
+        self
+    }
+
+    /// Adds `other` to this usage, field by field. A field stays `None` only if it is `None` in both.
+    pub fn add(&mut self, other: &GaiseUsage) {
+        fn add_count(current: &mut Option<usize>, other: Option<usize>) {
+            if let Some(v) = other {
+                *current = Some(current.unwrap_or(0) + v);
+            }
+        }
+        fn add_raw(current: &mut Option<HashMap<String, usize>>, other: &Option<HashMap<String, usize>>) {
+            if let Some(other) = other {
+                let current = current.get_or_insert_with(HashMap::new);
+                for (k, v) in other {
+                    *current.entry(k.clone()).or_insert(0) += v;
+                }
+            }
+        }
+
+        add_raw(&mut self.input, &other.input);
+        add_raw(&mut self.output, &other.output);
+        add_count(&mut self.input_tokens, other.input_tokens);
+        add_count(&mut self.output_tokens, other.output_tokens);
+        add_count(&mut self.cache_read_tokens, other.cache_read_tokens);
+        add_count(&mut self.cache_write_tokens, other.cache_write_tokens);
+        add_count(&mut self.reasoning_tokens, other.reasoning_tokens);
+        add_count(&mut self.total_tokens, other.total_tokens);
+    }
+}
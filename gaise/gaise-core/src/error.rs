@@ -0,0 +1,115 @@
+use std::fmt;
+use std::time::Duration;
+
+/// Error returned by every `GaiseClient` call.
+///
+/// The variant tells the caller how to react (wait and retry, fix credentials, shrink the
+/// prompt, ...); the message keeps the provider's own description of the problem.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GaiseError {
+    /// The provider throttled the request. `retry_after` is the wait it asked for, if any.
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// Credentials are missing, invalid or lack permission for the model.
+    Unauthorized(String),
+    /// The prompt does not fit in the model's context window.
+    ContextLengthExceeded(String),
+    /// The provider rejected the request, e.g. an unknown model or an invalid parameter.
+    InvalidRequest(String),
+    /// The provider could not be reached or failed on its side (5xx, overloaded).
+    ProviderUnavailable(String),
+    /// The request timed out.
+    Timeout(String),
+    /// A streamed response broke off or carried a chunk that could not be read.
+    Stream(String),
+    /// The provider does not support the operation, e.g. embeddings on Anthropic.
+    Unsupported(String),
+    /// Anything that fits none of the above, e.g. a response that could not be parsed.
+    Other(String),
+}
+
+/// Phrases providers use when a prompt is longer than the context window.
+const CONTEXT_LENGTH_MARKERS: &[&str] = &[
+    "context_length_exceeded",
+    "context length",
+    "context window",
+    "maximum context",
+    "prompt is too long",
+    "input is too long",
+    "too many tokens",
+    "too many input tokens",
+    "exceeds the maximum number of tokens",
+];
+
+impl GaiseError {
+    /// Classifies an error response from `provider` by its HTTP status and body.
+    pub fn from_status(provider: &str, status: u16, body: &str, retry_after: Option<Duration>) -> Self {
+        let message = format!("{} API error ({}): {}", provider, status, body);
+        let lower = body.to_lowercase();
+
+        if CONTEXT_LENGTH_MARKERS.iter().any(|marker| lower.contains(marker)) {
+            return Self::ContextLengthExceeded(message);
+        }
+
+        match status {
+            401 | 403 => Self::Unauthorized(message),
+            429 => Self::RateLimited { retry_after, message },
+            408 | 504 => Self::Timeout(message),
+            500..=599 => Self::ProviderUnavailable(message),
+            _ => Self::InvalidRequest(message),
+        }
+    }
+
+    /// Parses a `Retry-After` header given in seconds, e.g. `"20"` or `"1.5"`.
+    /// HTTP dates are not supported and yield `None`.
+    pub fn parse_retry_after(value: &str) -> Option<Duration> {
+        value
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+            .map(Duration::from_secs_f64)
+    }
+
+    /// Short snake_case name of the variant, e.g. `rate_limited`, for logs and API responses.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::RateLimited { .. } => "rate_limited",
+            Self::Unauthorized(_) => "unauthorized",
+            Self::ContextLengthExceeded(_) => "context_length_exceeded",
+            Self::InvalidRequest(_) => "invalid_request",
+            Self::ProviderUnavailable(_) => "provider_unavailable",
+            Self::Timeout(_) => "timeout",
+            Self::Stream(_) => "stream",
+            Self::Unsupported(_) => "unsupported",
+            Self::Other(_) => "other",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            Self::RateLimited { message, .. } => message,
+            Self::Unauthorized(message)
+            | Self::ContextLengthExceeded(message)
+            | Self::InvalidRequest(message)
+            | Self::ProviderUnavailable(message)
+            | Self::Timeout(message)
+            | Self::Stream(message)
+            | Self::Unsupported(message)
+            | Self::Other(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for GaiseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl std::error::Error for GaiseError {}
+
+impl From<serde_json::Error> for GaiseError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Other(format!("Invalid JSON: {}", e))
+    }
+}
@@ -2,7 +2,11 @@ use async_trait::async_trait;
 
 use crate::contracts::{GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest, GaiseInstructResponse, GaiseInstructStreamResponse};
 pub mod contracts;
+pub mod error;
 pub mod logging;
+pub mod sse;
+
+pub use error::GaiseError;
 
 #[async_trait]
 pub trait GaiseClient : Send + Sync {
@@ -14,14 +18,14 @@ pub trait GaiseClient : Send + Sync {
         std::pin::Pin<
             Box<
                 dyn futures_util::Stream<
-                        Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>,
+                        Item = Result<GaiseInstructStreamResponse, GaiseError>,
                     > + Send,
             >,
         >,
-        Box<dyn std::error::Error + Send + Sync>,
+        GaiseError,
     >;
 
-    async fn instruct(&self, request:&GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>>;
-    async fn embeddings(&self, request:&GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>>;
+    async fn instruct(&self, request:&GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError>;
+    async fn embeddings(&self, request:&GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError>;
 }
 
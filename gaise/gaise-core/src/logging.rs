@@ -1,5 +1,7 @@
+use crate::GaiseError;
 use serde_json::Value;
 use std::fmt::Debug;
+use std::time::Duration;
 pub trait IGaiseLogger: Send + Sync + Debug {
     fn log_request(
         &self,
@@ -23,6 +25,29 @@ pub trait IGaiseLogger: Send + Sync + Debug {
         model: &str,
         chunk_json: Value,
     );
+    /// Called when attempt number `attempt` (1-based) failed with `error` and the request
+    /// is sent again after `delay`. Does nothing unless overridden.
+    fn log_retry(
+        &self,
+        _correlation_id: Option<&str>,
+        _request_type: &str,
+        _model: &str,
+        _attempt: u32,
+        _delay: Duration,
+        _error: &GaiseError,
+    ) {
+    }
+    /// Called when `model` failed with `error` and the request moves on to `next_model`,
+    /// the next model of its fallback chain. Does nothing unless overridden.
+    fn log_fallback(
+        &self,
+        _correlation_id: Option<&str>,
+        _request_type: &str,
+        _model: &str,
+        _next_model: &str,
+        _error: &GaiseError,
+    ) {
+    }
 }
 #[derive(Debug, Default)]
 pub struct ConsoleGaiseLogger;
@@ -68,4 +93,33 @@ impl IGaiseLogger for ConsoleGaiseLogger {
             cid, request_type, model, chunk_json
         );
     }
+    fn log_retry(
+        &self,
+        correlation_id: Option<&str>,
+        request_type: &str,
+        model: &str,
+        attempt: u32,
+        delay: Duration,
+        error: &GaiseError,
+    ) {
+        let cid = correlation_id.unwrap_or("none");
+        println!(
+            "[GAISE RETRY] CID: {} | Type: {} | Model: {} | Attempt: {} | Delay: {:?} | Error: {}",
+            cid, request_type, model, attempt, delay, error
+        );
+    }
+    fn log_fallback(
+        &self,
+        correlation_id: Option<&str>,
+        request_type: &str,
+        model: &str,
+        next_model: &str,
+        error: &GaiseError,
+    ) {
+        let cid = correlation_id.unwrap_or("none");
+        println!(
+            "[GAISE FALLBACK] CID: {} | Type: {} | Model: {} | Next: {} | Error: {}",
+            cid, request_type, model, next_model, error
+        );
+    }
 }
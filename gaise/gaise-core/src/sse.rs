@@ -0,0 +1,81 @@
+use crate::GaiseError;
+use futures_util::{Stream, StreamExt};
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+struct LineState<S> {
+    inner: S,
+    buffer: Vec<u8>,
+    pending: VecDeque<String>,
+    done: bool,
+}
+
+impl<S> LineState<S> {
+    fn drain_lines(&mut self) {
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if !line.is_empty() {
+                self.pending.push_back(line.to_string());
+            }
+        }
+    }
+}
+
+/// Splits a byte stream into its non-empty lines. Network chunks do not line up with lines:
+/// a chunk can end mid-line or carry several lines, so bytes are buffered until a line is complete.
+/// Used for newline-delimited JSON (Ollama) and as the base of [`data_events`].
+/// A transport error ends the stream with a [`GaiseError::Stream`].
+pub fn lines<S, B, E>(stream: S) -> impl Stream<Item = Result<String, GaiseError>> + Send
+where
+    S: Stream<Item = Result<B, E>> + Send + Unpin,
+    B: AsRef<[u8]> + Send,
+    E: Display + Send,
+{
+    let state = LineState { inner: stream, buffer: Vec::new(), pending: VecDeque::new(), done: false };
+
+    futures_util::stream::unfold(state, |mut state| async move {
+        loop {
+            if let Some(line) = state.pending.pop_front() {
+                return Some((Ok(line), state));
+            }
+            if state.done {
+                return None;
+            }
+            match state.inner.next().await {
+                Some(Ok(bytes)) => {
+                    state.buffer.extend_from_slice(bytes.as_ref());
+                    state.drain_lines();
+                }
+                Some(Err(e)) => {
+                    state.done = true;
+                    return Some((Err(GaiseError::Stream(format!("Stream interrupted: {}", e))), state));
+                }
+                None => {
+                    state.done = true;
+                    state.buffer.push(b'\n');
+                    state.drain_lines();
+                }
+            }
+        }
+    })
+}
+
+/// Yields the payload of every `data:` line of a server-sent events stream, e.g. `{"id":...}`
+/// or `[DONE]`. `event:`, `id:` and comment lines are skipped.
+pub fn data_events<S, B, E>(stream: S) -> impl Stream<Item = Result<String, GaiseError>> + Send
+where
+    S: Stream<Item = Result<B, E>> + Send + Unpin,
+    B: AsRef<[u8]> + Send,
+    E: Display + Send,
+{
+    lines(stream).filter_map(|line| {
+        futures_util::future::ready(match line {
+            Ok(line) => line
+                .strip_prefix("data:")
+                .map(|data| Ok(data.strip_prefix(' ').unwrap_or(data).to_string())),
+            Err(e) => Some(Err(e)),
+        })
+    })
+}
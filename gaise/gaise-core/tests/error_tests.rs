@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod tests {
+    use gaise_core::GaiseError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_status_classifies_by_status() {
+        assert!(matches!(GaiseError::from_status("OpenAI", 401, "bad key", None), GaiseError::Unauthorized(_)));
+        assert!(matches!(GaiseError::from_status("OpenAI", 403, "forbidden", None), GaiseError::Unauthorized(_)));
+        assert!(matches!(GaiseError::from_status("OpenAI", 404, "no such model", None), GaiseError::InvalidRequest(_)));
+        assert!(matches!(GaiseError::from_status("Anthropic", 529, "overloaded", None), GaiseError::ProviderUnavailable(_)));
+        assert!(matches!(GaiseError::from_status("Vertex AI", 504, "deadline exceeded", None), GaiseError::Timeout(_)));
+    }
+
+    #[test]
+    fn test_from_status_keeps_retry_after() {
+        let err = GaiseError::from_status("OpenAI", 429, "slow down", Some(Duration::from_secs(20)));
+        assert_eq!(
+            err,
+            GaiseError::RateLimited {
+                retry_after: Some(Duration::from_secs(20)),
+                message: "OpenAI API error (429): slow down".to_string(),
+            }
+        );
+        assert_eq!(err.kind(), "rate_limited");
+    }
+
+    #[test]
+    fn test_from_status_detects_context_length_in_body() {
+        let openai = r#"{"error":{"message":"This model's maximum context length is 128000 tokens.","code":"context_length_exceeded"}}"#;
+        let anthropic = r#"{"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 210000 tokens > 200000 maximum"}}"#;
+        let vertex = r#"{"error":{"code":400,"message":"The input token count (1200000) exceeds the maximum number of tokens allowed (1048576)."}}"#;
+
+        for (provider, body) in [("OpenAI", openai), ("Anthropic", anthropic), ("Vertex AI", vertex)] {
+            let err = GaiseError::from_status(provider, 400, body, None);
+            assert!(matches!(err, GaiseError::ContextLengthExceeded(_)), "{}: {:?}", provider, err);
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(GaiseError::parse_retry_after("20"), Some(Duration::from_secs(20)));
+        assert_eq!(GaiseError::parse_retry_after(" 1.5 "), Some(Duration::from_millis(1500)));
+        assert_eq!(GaiseError::parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        assert_eq!(GaiseError::parse_retry_after("-3"), None);
+    }
+
+    #[test]
+    fn test_display_is_message() {
+        let err = GaiseError::Unsupported("Anthropic does not support embeddings API".to_string());
+        assert_eq!(err.to_string(), "Anthropic does not support embeddings API");
+    }
+}
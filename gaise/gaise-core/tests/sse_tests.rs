@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use futures_util::StreamExt;
+    use gaise_core::sse::{data_events, lines};
+
+    type Chunk = Result<&'static [u8], std::io::Error>;
+
+    #[tokio::test]
+    async fn test_lines_joins_chunks_split_mid_line() {
+        let chunks: Vec<Chunk> = vec![Ok(b"{\"a\":1}\n{\"b\""), Ok(b":2}\n\n{\"c\":3}")];
+        let lines: Vec<String> = lines(futures_util::stream::iter(chunks))
+            .map(|l| l.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(lines, vec!["{\"a\":1}", "{\"b\":2}", "{\"c\":3}"]);
+    }
+
+    #[tokio::test]
+    async fn test_data_events_skips_event_and_comment_lines() {
+        let chunks: Vec<Chunk> = vec![
+            Ok(b": keep-alive\r\nevent: message_start\r\ndata: {\"x\":1}\r\n\r\n"),
+            Ok(b"data:[DONE]\n"),
+        ];
+        let events: Vec<String> = data_events(futures_util::stream::iter(chunks))
+            .map(|e| e.unwrap())
+            .collect()
+            .await;
+
+        assert_eq!(events, vec!["{\"x\":1}", "[DONE]"]);
+    }
+
+    #[tokio::test]
+    async fn test_lines_forwards_stream_errors() {
+        let chunks: Vec<Chunk> = vec![Ok(b"partial"), Err(std::io::Error::other("reset"))];
+        let results: Vec<_> = lines(futures_util::stream::iter(chunks)).collect().await;
+
+        assert_eq!(results.len(), 1);
+        let err = results[0].as_ref().unwrap_err();
+        assert_eq!(err.kind(), "stream");
+        assert!(err.to_string().contains("reset"));
+    }
+}
@@ -10,10 +10,12 @@ mod tests {
         acc.push(&GaiseInstructStreamResponse {
             chunk: GaiseStreamChunk::Text("Hello ".to_string()),
             external_id: Some("ext-1".to_string()),
+            model: None,
         });
         acc.push(&GaiseInstructStreamResponse {
             chunk: GaiseStreamChunk::Text("world!".to_string()),
             external_id: Some("ext-1".to_string()),
+            model: None,
         });
 
         let msg = acc.finish();
@@ -38,6 +40,7 @@ mod tests {
                 arguments: Some("{\"loc".to_string()),
             },
             external_id: None,
+            model: None,
         });
 
         acc.push(&GaiseInstructStreamResponse {
@@ -48,6 +51,7 @@ mod tests {
                 arguments: Some("ation\": \"London\"}".to_string()),
             },
             external_id: None,
+            model: None,
         });
 
         let msg = acc.finish();
@@ -69,8 +73,10 @@ mod tests {
             chunk: GaiseStreamChunk::Usage(GaiseUsage {
                 input: Some(input_usage),
                 output: None,
+                ..Default::default()
             }),
             external_id: None,
+            model: None,
         });
 
         let mut output_usage = HashMap::new();
@@ -80,8 +86,10 @@ mod tests {
             chunk: GaiseStreamChunk::Usage(GaiseUsage {
                 input: None,
                 output: Some(output_usage),
+                ..Default::default()
             }),
             external_id: None,
+            model: None,
         });
 
         assert!(acc.usage.is_some());
@@ -90,16 +98,63 @@ mod tests {
         assert_eq!(usage.output.as_ref().unwrap().get("completion"), Some(&5));
     }
 
+    #[test]
+    fn test_accumulation_usage_sums_canonical_fields() {
+        let mut acc = GaiseStreamAccumulator::new();
+
+        let prompt = GaiseUsage {
+            input_tokens: Some(120),
+            cache_read_tokens: Some(100),
+            total_tokens: Some(120),
+            ..Default::default()
+        };
+        let completion = GaiseUsage {
+            output_tokens: Some(30),
+            reasoning_tokens: Some(10),
+            total_tokens: Some(30),
+            ..Default::default()
+        };
+        for usage in [prompt, completion] {
+            acc.push(&GaiseInstructStreamResponse {
+                chunk: GaiseStreamChunk::Usage(usage),
+                external_id: None,
+                model: None,
+            });
+        }
+
+        let usage = acc.usage.as_ref().unwrap();
+        assert_eq!(usage.input_tokens, Some(120));
+        assert_eq!(usage.output_tokens, Some(30));
+        assert_eq!(usage.cache_read_tokens, Some(100));
+        assert_eq!(usage.cache_write_tokens, None);
+        assert_eq!(usage.reasoning_tokens, Some(10));
+        assert_eq!(usage.total_tokens, Some(150));
+    }
+
+    #[test]
+    fn test_usage_new_sets_total_and_raw_keys() {
+        let usage = GaiseUsage::new(10, 5).with_raw_input("prompt_tokens", 10);
+        assert_eq!(usage.total_tokens, Some(15));
+        assert_eq!(usage.input.as_ref().unwrap().get("prompt_tokens"), Some(&10));
+        assert!(usage.output.is_none());
+
+        let json = serde_json::to_value(&usage).unwrap();
+        assert_eq!(json["input_tokens"], 10);
+        assert!(json.get("cache_read_tokens").is_none());
+    }
+
     #[tokio::test]
     async fn test_collect_stream() {
         let chunks: Vec<Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> = vec![
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text("Hello ".to_string()),
                 external_id: None,
+                model: None,
             }),
             Ok(GaiseInstructStreamResponse {
                 chunk: GaiseStreamChunk::Text("world!".to_string()),
                 external_id: None,
+                model: None,
             }),
         ];
         let stream = futures_util::stream::iter(chunks);
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
 use gaise_core::contracts::{
     GaiseContent, GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest,
     GaiseInstructResponse, GaiseInstructStreamResponse, GaiseMessage, GaiseStreamChunk,
@@ -7,7 +7,6 @@ use gaise_core::contracts::{
 };
 use crate::contracts::*;
 use futures_util::{Stream, StreamExt};
-use std::collections::HashMap;
 use std::pin::Pin;
 use base64::Engine;
 
@@ -61,13 +60,8 @@ impl From<&GaiseInstructRequest> for AnthropicRequest {
         for m in messages {
             // Extract system message
             if m.role == "system" {
-                if let Some(content) = &m.content {
-                    match content {
-                        OneOrMany::One(GaiseContent::Text { text }) => {
-                            system_prompt = Some(text.clone());
-                        }
-                        _ => {}
-                    }
+                if let Some(OneOrMany::One(GaiseContent::Text { text })) = &m.content {
+                    system_prompt = Some(text.clone());
                 }
                 continue;
             }
@@ -174,6 +168,135 @@ impl From<&GaiseInstructRequest> for AnthropicRequest {
     }
 }
 
+impl From<&AnthropicUsage> for GaiseUsage {
+    fn from(u: &AnthropicUsage) -> Self {
+        // Anthropic's `input_tokens` excludes the cached part of the prompt.
+        let cache_read = u.cache_read_input_tokens.unwrap_or(0);
+        let cache_write = u.cache_creation_input_tokens.unwrap_or(0);
+        let input = u.input_tokens + cache_read + cache_write;
+
+        let mut usage = GaiseUsage::new(input, u.output_tokens)
+            .with_raw_input("input_tokens", u.input_tokens)
+            .with_raw_output("output_tokens", u.output_tokens);
+        if let Some(cache_read) = u.cache_read_input_tokens {
+            usage.cache_read_tokens = Some(cache_read);
+            usage = usage.with_raw_input("cache_read_input_tokens", cache_read);
+        }
+        if let Some(cache_write) = u.cache_creation_input_tokens {
+            usage.cache_write_tokens = Some(cache_write);
+            usage = usage.with_raw_input("cache_creation_input_tokens", cache_write);
+        }
+        usage
+    }
+}
+
+/// Classifies a failure to send the request or read the response.
+fn request_error(e: reqwest::Error) -> GaiseError {
+    if e.is_timeout() {
+        GaiseError::Timeout(format!("Anthropic request timed out: {}", e))
+    } else if e.is_decode() {
+        GaiseError::Other(format!("Invalid Anthropic response: {}", e))
+    } else {
+        GaiseError::ProviderUnavailable(format!("Anthropic request failed: {}", e))
+    }
+}
+
+/// Classifies a non-success response by its status, `Retry-After` header and body.
+async fn response_error(response: reqwest::Response) -> GaiseError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(GaiseError::parse_retry_after);
+    let body = response.text().await.unwrap_or_default();
+    GaiseError::from_status("Anthropic", status, &body, retry_after)
+}
+
+/// Keeps only the prompt side of a usage report.
+fn input_usage(usage: GaiseUsage) -> GaiseUsage {
+    GaiseUsage {
+        output: None,
+        output_tokens: None,
+        reasoning_tokens: None,
+        total_tokens: usage.input_tokens,
+        ..usage
+    }
+}
+
+/// Keeps only the generated side of a usage report.
+fn output_usage(usage: GaiseUsage) -> GaiseUsage {
+    GaiseUsage {
+        input: None,
+        input_tokens: None,
+        cache_read_tokens: None,
+        cache_write_tokens: None,
+        total_tokens: usage.output_tokens,
+        ..usage
+    }
+}
+
+/// Maps one streamed event to a Gaise chunk. Usage arrives in two parts: the prompt side with
+/// `message_start` and the cumulative output count with the final `message_delta`.
+pub fn map_stream_event(
+    data: &str,
+    message_id: &mut Option<String>,
+) -> Result<Option<GaiseInstructStreamResponse>, GaiseError> {
+    let event: AnthropicStreamResponse = serde_json::from_str(data)
+        .map_err(|e| GaiseError::Stream(format!("Invalid Anthropic stream event: {}", e)))?;
+
+    let chunk = match event.r#type.as_str() {
+        "message_start" => event.message.map(|message| {
+            *message_id = Some(message.id.clone());
+            GaiseStreamChunk::Usage(input_usage(GaiseUsage::from(&message.usage)))
+        }),
+        "message_delta" => event.usage.map(|u| GaiseStreamChunk::Usage(output_usage(GaiseUsage::from(&u)))),
+        "content_block_delta" => event.delta.and_then(|delta| {
+            if let Some(text) = delta.text {
+                Some(GaiseStreamChunk::Text(text))
+            } else {
+                delta.partial_json.map(|partial_json| GaiseStreamChunk::ToolCall {
+                    index: event.index.unwrap_or(0),
+                    id: None,
+                    name: None,
+                    arguments: Some(partial_json),
+                })
+            }
+        }),
+        "content_block_start" => match event.content_block {
+            Some(AnthropicContentBlock::ToolUse { id, name, .. }) => Some(GaiseStreamChunk::ToolCall {
+                index: event.index.unwrap_or(0),
+                id: Some(id),
+                name: Some(name),
+                arguments: None,
+            }),
+            _ => None,
+        },
+        "error" => {
+            let error = event.error.unwrap_or_default();
+            // Errors after the stream started come without a status; derive it from the error type.
+            let status = match error.get("type").and_then(|t| t.as_str()) {
+                Some("invalid_request_error") => 400,
+                Some("authentication_error") => 401,
+                Some("permission_error") => 403,
+                Some("request_too_large") => 413,
+                Some("rate_limit_error") => 429,
+                Some("api_error") => 500,
+                Some("overloaded_error") => 529,
+                _ => return Err(GaiseError::Stream(format!("Anthropic API error: {}", error))),
+            };
+            return Err(GaiseError::from_status("Anthropic", status, &error.to_string(), None));
+        }
+        _ => None,
+    };
+
+    Ok(chunk.map(|chunk| GaiseInstructStreamResponse {
+        chunk,
+        external_id: message_id.clone(),
+        model: None,
+    }))
+}
+
 impl GaiseClientAnthropic {
     pub fn new(api_url: String, api_key: String) -> Self {
         Self {
@@ -238,8 +361,8 @@ impl GaiseClient for GaiseClientAnthropic {
         &self,
         request: &GaiseInstructRequest,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>,
-        Box<dyn std::error::Error + Send + Sync>,
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>,
+        GaiseError,
     > {
         let url = format!("{}/messages", self.api_url);
 
@@ -252,81 +375,23 @@ impl GaiseClient for GaiseClientAnthropic {
             .header("content-type", "application/json")
             .json(&anthropic_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format!("Anthropic API error: {}", err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let stream = response.bytes_stream();
-
-        let mapped_stream = stream.map(|res| {
-            res.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>).and_then(|bytes| {
-                let line = std::str::from_utf8(&bytes)?;
-
-                // Anthropic uses SSE format: "data: {...}"
-                if !line.starts_with("data: ") {
-                    return Err("Unexpected stream format".into());
-                }
-
-                let json_str = &line[6..];
-                let chunk: AnthropicStreamResponse = serde_json::from_str(json_str)?;
-
-                match chunk.r#type.as_str() {
-                    "content_block_delta" => {
-                        if let Some(delta) = chunk.delta {
-                            if let Some(text) = delta.text {
-                                return Ok(GaiseInstructStreamResponse {
-                                    chunk: GaiseStreamChunk::Text(text),
-                                    external_id: chunk.message.as_ref().map(|m| m.id.clone()),
-                                });
-                            }
-                            if let Some(partial_json) = delta.partial_json {
-                                return Ok(GaiseInstructStreamResponse {
-                                    chunk: GaiseStreamChunk::ToolCall {
-                                        index: chunk.index.unwrap_or(0),
-                                        id: None,
-                                        name: None,
-                                        arguments: Some(partial_json),
-                                    },
-                                    external_id: chunk.message.as_ref().map(|m| m.id.clone()),
-                                });
-                            }
-                        }
-                    }
-                    "content_block_start" => {
-                        if let Some(content_block) = chunk.content_block {
-                            if let AnthropicContentBlock::ToolUse { id, name, .. } = content_block {
-                                return Ok(GaiseInstructStreamResponse {
-                                    chunk: GaiseStreamChunk::ToolCall {
-                                        index: chunk.index.unwrap_or(0),
-                                        id: Some(id),
-                                        name: Some(name),
-                                        arguments: None,
-                                    },
-                                    external_id: chunk.message.as_ref().map(|m| m.id.clone()),
-                                });
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-
-                Err("Empty chunk".into())
-            })
-        })
-        .filter(|res| {
-            match res {
-                Err(e) if e.to_string() == "Empty chunk" => futures_util::future::ready(false),
-                _ => futures_util::future::ready(true),
-            }
-        });
+        let mut message_id = None;
+        let mapped_stream = gaise_core::sse::data_events(response.bytes_stream())
+            .filter_map(move |res| {
+                futures_util::future::ready(res.and_then(|data| map_stream_event(&data, &mut message_id)).transpose())
+            });
 
         Ok(Box::pin(mapped_stream))
     }
 
-    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
         let url = format!("{}/messages", self.api_url);
 
         let anthropic_request = AnthropicRequest::from(request);
@@ -337,14 +402,14 @@ impl GaiseClient for GaiseClientAnthropic {
             .header("content-type", "application/json")
             .json(&anthropic_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format!("Anthropic API error: {}", err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let anthropic_response: AnthropicResponse = response.json().await?;
+        let anthropic_response: AnthropicResponse = response.json().await.map_err(request_error)?;
 
         let (content, tool_calls) = self.map_from_anthropic_content(anthropic_response.content);
 
@@ -355,22 +420,15 @@ impl GaiseClient for GaiseClientAnthropic {
             tool_call_id: None,
         };
 
-        let mut input = HashMap::new();
-        input.insert("input_tokens".to_string(), anthropic_response.usage.input_tokens);
-        let mut output = HashMap::new();
-        output.insert("output_tokens".to_string(), anthropic_response.usage.output_tokens);
-
         Ok(GaiseInstructResponse {
             output: OneOrMany::One(message),
             external_id: Some(anthropic_response.id),
-            usage: Some(GaiseUsage {
-                input: Some(input),
-                output: Some(output),
-            }),
+            usage: Some(GaiseUsage::from(&anthropic_response.usage)),
+            model: None,
         })
     }
 
-    async fn embeddings(&self, _request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
-        Err("Anthropic does not support embeddings API".into())
+    async fn embeddings(&self, _request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
+        Err(GaiseError::Unsupported("Anthropic does not support embeddings API".to_string()))
     }
 }
@@ -103,8 +103,14 @@ pub struct AnthropicResponse {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AnthropicUsage {
+    #[serde(default)]
     pub input_tokens: usize,
+    #[serde(default)]
     pub output_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_creation_input_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read_input_tokens: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -118,6 +124,10 @@ pub struct AnthropicStreamResponse {
     pub content_block: Option<AnthropicContentBlock>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<AnthropicStreamMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<AnthropicUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -3,6 +3,9 @@ use gaise_core::contracts::{
     GaiseMessage, GaiseTool, GaiseToolParameter, OneOrMany,
 };
 use gaise_provider_anthropic::contracts::models::{AnthropicRequest, AnthropicContent};
+use gaise_core::GaiseError;
+use gaise_core::contracts::{GaiseStreamChunk, GaiseUsage};
+use gaise_provider_anthropic::anthropic_client::map_stream_event;
 use std::collections::HashMap;
 
 #[test]
@@ -179,3 +182,35 @@ fn test_mapping_multimodal_request() {
         panic!("Expected blocks content");
     }
 }
+
+#[test]
+fn test_mapping_stream_usage_includes_cache() {
+    let mut message_id = None;
+    let start = r#"{"type":"message_start","message":{"id":"msg_1","type":"message","role":"assistant","model":"claude-sonnet-4","usage":{"input_tokens":100,"output_tokens":1,"cache_read_input_tokens":800,"cache_creation_input_tokens":50}}}"#;
+    let delta = r#"{"type":"message_delta","delta":{"stop_reason":"end_turn"},"usage":{"output_tokens":300}}"#;
+
+    let mut total = GaiseUsage::default();
+    for data in [start, delta] {
+        let chunk = map_stream_event(data, &mut message_id).unwrap().expect("Missing chunk");
+        assert_eq!(chunk.external_id.as_deref(), Some("msg_1"));
+        match chunk.chunk {
+            GaiseStreamChunk::Usage(usage) => total.add(&usage),
+            other => panic!("Expected usage, got {:?}", other),
+        }
+    }
+
+    assert_eq!(total.input_tokens, Some(950));
+    assert_eq!(total.output_tokens, Some(300));
+    assert_eq!(total.cache_read_tokens, Some(800));
+    assert_eq!(total.cache_write_tokens, Some(50));
+    assert_eq!(total.total_tokens, Some(1250));
+    assert_eq!(total.input.unwrap().get("input_tokens"), Some(&100));
+}
+
+#[test]
+fn test_mapping_stream_error_event() {
+    let data = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+    let err = map_stream_event(data, &mut None).unwrap_err();
+    assert!(matches!(err, GaiseError::ProviderUnavailable(_)));
+    assert!(err.to_string().contains("Overloaded"));
+}
@@ -1,14 +1,41 @@
 use async_trait::async_trait;
 use aws_sdk_bedrockruntime::Client as BedrockClient;
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
+use aws_sdk_bedrockruntime::error::{DisplayErrorContext, ProvideErrorMetadata, SdkError};
 use gaise_core::contracts::{
     GaiseInstructRequest, GaiseInstructResponse, GaiseInstructStreamResponse,
     GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseMessage, GaiseContent,
-    OneOrMany, GaiseToolCall, GaiseFunctionCall, GaiseStreamChunk,
+    OneOrMany, GaiseToolCall, GaiseFunctionCall, GaiseStreamChunk, GaiseUsage,
 };
 use std::pin::Pin;
 use futures_util::{Stream};
-use std::error::Error;
+
+/// Classifies an SDK error. Service errors carry the HTTP status and an error code such as
+/// `ThrottlingException` or `ValidationException`.
+fn sdk_error<E>(e: SdkError<E>) -> GaiseError
+where
+    E: ProvideErrorMetadata + std::error::Error + 'static,
+{
+    match &e {
+        SdkError::TimeoutError(_) => GaiseError::Timeout(format!("Bedrock request timed out: {}", DisplayErrorContext(&e))),
+        SdkError::DispatchFailure(failure) if failure.is_timeout() => {
+            GaiseError::Timeout(format!("Bedrock request timed out: {}", DisplayErrorContext(&e)))
+        }
+        SdkError::DispatchFailure(_) => {
+            GaiseError::ProviderUnavailable(format!("Bedrock request failed: {}", DisplayErrorContext(&e)))
+        }
+        SdkError::ServiceError(_) | SdkError::ResponseError(_) => {
+            let response = e.raw_response();
+            let status = response.map(|r| r.status().as_u16()).unwrap_or(500);
+            let retry_after = response
+                .and_then(|r| r.headers().get("retry-after"))
+                .and_then(GaiseError::parse_retry_after);
+            let body = format!("{}: {}", e.code().unwrap_or("UnknownError"), e.message().unwrap_or_default());
+            GaiseError::from_status("Bedrock", status, &body, retry_after)
+        }
+        _ => GaiseError::Other(format!("Bedrock error: {}", DisplayErrorContext(&e))),
+    }
+}
 
 pub struct GaiseClientBedrock {
     client: BedrockClient,
@@ -21,10 +48,38 @@ impl GaiseClientBedrock {
         Self { client }
     }
 
+    /// Like `new`, but for `region` instead of the region configured in the environment.
+    pub async fn with_region(region: String) -> Self {
+        let config = aws_config::from_env().region(aws_config::Region::new(region)).load().await;
+        let client = BedrockClient::new(&config);
+        Self { client }
+    }
+
     pub fn with_client(client: BedrockClient) -> Self {
         Self { client }
     }
 
+    /// Bedrock's `input_tokens` excludes the tokens read from or written to the prompt cache.
+    pub fn map_usage(usage: &aws_sdk_bedrockruntime::types::TokenUsage) -> GaiseUsage {
+        let count = |v: i32| v.max(0) as usize;
+        let cache_read = usage.cache_read_input_tokens.map(count);
+        let cache_write = usage.cache_write_input_tokens.map(count);
+        let input = count(usage.input_tokens) + cache_read.unwrap_or(0) + cache_write.unwrap_or(0);
+
+        let mut gaise_usage = GaiseUsage::new(input, count(usage.output_tokens))
+            .with_raw_input("inputTokens", count(usage.input_tokens))
+            .with_raw_output("outputTokens", count(usage.output_tokens));
+        if let Some(cache_read) = cache_read {
+            gaise_usage.cache_read_tokens = Some(cache_read);
+            gaise_usage = gaise_usage.with_raw_input("cacheReadInputTokens", cache_read);
+        }
+        if let Some(cache_write) = cache_write {
+            gaise_usage.cache_write_tokens = Some(cache_write);
+            gaise_usage = gaise_usage.with_raw_input("cacheWriteInputTokens", cache_write);
+        }
+        gaise_usage
+    }
+
     fn to_document(value: &serde_json::Value) -> aws_smithy_types::Document {
         match value {
             serde_json::Value::Null => aws_smithy_types::Document::Null,
@@ -68,7 +123,7 @@ impl GaiseClientBedrock {
                 )]
             }
             GaiseContent::Parts { parts } => {
-                parts.iter().flat_map(|p| Self::map_gaise_content_to_bedrock(p)).collect()
+                parts.iter().flat_map(Self::map_gaise_content_to_bedrock).collect()
             }
             GaiseContent::File { data, name } => {
                 let format = if let Some(n) = name {
@@ -129,7 +184,7 @@ impl GaiseClientBedrock {
 
 #[async_trait]
 impl GaiseClient for GaiseClientBedrock {
-    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn Error + Send + Sync>> {
+    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
         let mut messages = Vec::new();
         let mut system_messages = Vec::new();
 
@@ -191,12 +246,13 @@ impl GaiseClient for GaiseClientBedrock {
             builder = builder.tool_config(aws_sdk_bedrockruntime::types::ToolConfiguration::builder().set_tools(Some(tool_list)).build().expect("Failed to build ToolConfiguration"));
         }
 
-        let response = builder.send().await?;
+        let response = builder.send().await.map_err(sdk_error)?;
 
-        let output = response.output.ok_or("No output from Bedrock")?;
+        let usage = response.usage.as_ref().map(Self::map_usage);
+        let output = response.output.ok_or_else(|| GaiseError::Other("No output from Bedrock".to_string()))?;
         let message = match output {
             aws_sdk_bedrockruntime::types::ConverseOutput::Message(m) => m,
-            _ => return Err("Unexpected output type from Bedrock".into()),
+            _ => return Err(GaiseError::Other("Unexpected output type from Bedrock".to_string())),
         };
 
         let mut gaise_content = Vec::new();
@@ -227,11 +283,12 @@ impl GaiseClient for GaiseClientBedrock {
                 tool_call_id: None,
             }),
             external_id: None,
-            usage: None,
+            usage,
+            model: None,
         })
     }
 
-    async fn instruct_stream(&self, request: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn Error + Send + Sync>>> + Send>>, Box<dyn Error + Send + Sync>> {
+    async fn instruct_stream(&self, request: &GaiseInstructRequest) -> Result<Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>, GaiseError> {
         let mut messages = Vec::new();
         let mut system_messages = Vec::new();
 
@@ -293,23 +350,36 @@ impl GaiseClient for GaiseClientBedrock {
             builder = builder.tool_config(aws_sdk_bedrockruntime::types::ToolConfiguration::builder().set_tools(Some(tool_list)).build().expect("Failed to build ToolConfiguration"));
         }
 
-        let response = builder.send().await?;
+        let response = builder.send().await.map_err(sdk_error)?;
         let mut stream = response.stream;
 
         let gaise_stream = async_stream::stream! {
-            while let Ok(Some(event)) = stream.recv().await {
+            loop {
+                let event = match stream.recv().await {
+                    Ok(Some(event)) => event,
+                    Ok(None) => break,
+                    Err(e) => {
+                        yield Err(GaiseError::Stream(format!("Bedrock stream error: {}", DisplayErrorContext(&e))));
+                        break;
+                    }
+                };
                 match event {
                     aws_sdk_bedrockruntime::types::ConverseStreamOutput::ContentBlockDelta(delta) => {
-                        if let Some(d) = delta.delta {
-                            match d {
-                                aws_sdk_bedrockruntime::types::ContentBlockDelta::Text(t) => {
-                                    yield Ok(GaiseInstructStreamResponse {
-                                        chunk: GaiseStreamChunk::Text(t),
-                                        external_id: None,
-                                    });
-                                }
-                                _ => {}
-                            }
+                        if let Some(aws_sdk_bedrockruntime::types::ContentBlockDelta::Text(t)) = delta.delta {
+                            yield Ok(GaiseInstructStreamResponse {
+                                chunk: GaiseStreamChunk::Text(t),
+                                external_id: None,
+                                model: None,
+                            });
+                        }
+                    }
+                    aws_sdk_bedrockruntime::types::ConverseStreamOutput::Metadata(metadata) => {
+                        if let Some(usage) = &metadata.usage {
+                            yield Ok(GaiseInstructStreamResponse {
+                                chunk: GaiseStreamChunk::Usage(Self::map_usage(usage)),
+                                external_id: None,
+                                model: None,
+                            });
                         }
                     }
                     _ => {}
@@ -320,7 +390,7 @@ impl GaiseClient for GaiseClientBedrock {
         Ok(Box::pin(gaise_stream))
     }
 
-    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn Error + Send + Sync>> {
+    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
         let inputs = match &request.input {
             OneOrMany::One(s) => vec![s.clone()],
             OneOrMany::Many(v) => v.clone(),
@@ -339,7 +409,7 @@ impl GaiseClient for GaiseClientBedrock {
                     "input_type": "search_document"
                 })
             } else {
-                return Err(format!("Unsupported embedding model: {}", request.model).into());
+                return Err(GaiseError::Unsupported(format!("Unsupported embedding model: {}", request.model)));
             };
 
             let response = self.client.invoke_model()
@@ -347,7 +417,8 @@ impl GaiseClient for GaiseClientBedrock {
                 .content_type("application/json")
                 .body(aws_smithy_types::Blob::new(serde_json::to_vec(&body)?))
                 .send()
-                .await?;
+                .await
+                .map_err(sdk_error)?;
 
             let response_body: serde_json::Value = serde_json::from_slice(response.body.as_ref())?;
             
@@ -356,15 +427,13 @@ impl GaiseClient for GaiseClientBedrock {
                     let vec: Vec<f32> = embedding.iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
                     embeddings.push(vec);
                 }
-            } else if request.model.contains("cohere") {
-                if let Some(embeddings_arr) = response_body["embeddings"].as_array() {
-                    if let Some(first) = embeddings_arr.first() {
-                        if let Some(embedding) = first.as_array() {
-                            let vec: Vec<f32> = embedding.iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
-                            embeddings.push(vec);
-                        }
-                    }
-                }
+            } else if request.model.contains("cohere")
+                && let Some(embeddings_arr) = response_body["embeddings"].as_array()
+                && let Some(first) = embeddings_arr.first()
+                && let Some(embedding) = first.as_array()
+            {
+                let vec: Vec<f32> = embedding.iter().map(|v| v.as_f64().unwrap_or(0.0) as f32).collect();
+                embeddings.push(vec);
             }
         }
 
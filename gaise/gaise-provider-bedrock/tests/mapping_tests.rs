@@ -1,6 +1,5 @@
 use gaise_provider_bedrock::GaiseClientBedrock;
 use gaise_core::contracts::{GaiseInstructRequest, GaiseMessage, OneOrMany, GaiseContent};
-use gaise_core::GaiseClient;
 
 #[tokio::test]
 async fn test_mapping_to_bedrock() {
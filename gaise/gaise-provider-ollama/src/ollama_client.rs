@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
 use gaise_core::contracts::{
     GaiseContent, GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest,
     GaiseInstructResponse, GaiseInstructStreamResponse, GaiseMessage, GaiseStreamChunk,
@@ -129,8 +129,12 @@ impl From<&GaiseInstructRequest> for OllamaChatRequest {
     }
 }
 
+fn is_tool_call_parse_error(err_text: &str) -> bool {
+    err_text.contains("error parsing tool call")
+}
+
 fn format_ollama_error(err_text: &str) -> String {
-    if err_text.contains("error parsing tool call") {
+    if is_tool_call_parse_error(err_text) {
         format!(
             "Ollama failed to parse the model's tool call output. \
             This usually means the model does not support tool calling. \
@@ -143,6 +147,94 @@ fn format_ollama_error(err_text: &str) -> String {
     }
 }
 
+/// Classifies a failure to send the request or read the response.
+fn request_error(e: reqwest::Error) -> GaiseError {
+    if e.is_timeout() {
+        GaiseError::Timeout(format!("Ollama request timed out: {}", e))
+    } else if e.is_decode() {
+        GaiseError::Other(format!("Invalid Ollama response: {}", e))
+    } else {
+        GaiseError::ProviderUnavailable(format!("Ollama request failed: {}", e))
+    }
+}
+
+/// Classifies a non-success response by its status, `Retry-After` header and body.
+async fn response_error(response: reqwest::Response) -> GaiseError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(GaiseError::parse_retry_after);
+    let body = response.text().await.unwrap_or_default();
+    if is_tool_call_parse_error(&body) {
+        return GaiseError::Unsupported(format_ollama_error(&body));
+    }
+    GaiseError::from_status("Ollama", status, &body, retry_after)
+}
+
+/// Ollama reports `prompt_eval_count` and `eval_count` and has no prompt cache or reasoning counters.
+pub fn ollama_usage(prompt_eval_count: Option<usize>, eval_count: Option<usize>) -> GaiseUsage {
+    let input = prompt_eval_count.unwrap_or(0);
+    let output = eval_count.unwrap_or(0);
+    let usage = GaiseUsage::new(input, output).with_raw_input("prompt_eval_count", input);
+    match eval_count {
+        Some(_) => usage.with_raw_output("eval_count", output),
+        None => GaiseUsage { output_tokens: None, ..usage },
+    }
+}
+
+/// Maps one line of the chat stream to Gaise chunks. The final line (`done: true`) carries the usage.
+pub fn map_stream_line(line: &str) -> Result<Vec<GaiseInstructStreamResponse>, GaiseError> {
+    let value: serde_json::Value = serde_json::from_str(line)
+        .map_err(|e| GaiseError::Stream(format!("Invalid Ollama stream chunk: {}", e)))?;
+    if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+        return Err(if is_tool_call_parse_error(error) {
+            GaiseError::Unsupported(format_ollama_error(error))
+        } else {
+            GaiseError::Stream(format_ollama_error(error))
+        });
+    }
+    let chunk: OllamaChatResponse = serde_json::from_value(value)
+        .map_err(|e| GaiseError::Stream(format!("Invalid Ollama stream chunk: {}", e)))?;
+    let mut responses = Vec::new();
+
+    if let Some(tool_calls) = chunk.message.tool_calls {
+        for (index, tc) in tool_calls.into_iter().enumerate() {
+            responses.push(GaiseInstructStreamResponse {
+                chunk: GaiseStreamChunk::ToolCall {
+                    index,
+                    id: None,
+                    name: Some(tc.function.name),
+                    arguments: Some(serde_json::to_string(&tc.function.arguments).unwrap_or_default()),
+                },
+                external_id: None,
+                model: None,
+            });
+        }
+    }
+
+    if let Some(content) = chunk.message.content
+        && !content.is_empty()
+    {
+        responses.push(GaiseInstructStreamResponse {
+            chunk: GaiseStreamChunk::Text(content),
+            external_id: None,
+            model: None,
+        });
+    }
+
+    if chunk.done {
+        responses.push(GaiseInstructStreamResponse {
+            chunk: GaiseStreamChunk::Usage(ollama_usage(chunk.prompt_eval_count, chunk.eval_count)),
+            external_id: None,
+            model: None,
+        });
+    }
+
+    Ok(responses)
+}
+
 impl GaiseClientOllama {
     pub fn new(api_url: String) -> Self {
         Self {
@@ -180,8 +272,8 @@ impl GaiseClient for GaiseClientOllama {
         &self,
         request: &GaiseInstructRequest,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>,
-        Box<dyn std::error::Error + Send + Sync>,
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>,
+        GaiseError,
     > {
         let url = format!("{}/api/chat", self.api_url);
         
@@ -191,61 +283,27 @@ impl GaiseClient for GaiseClientOllama {
         let response = self.client.post(url)
             .json(&ollama_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format_ollama_error(&err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let stream = response.bytes_stream();
-        
-        let mapped_stream = stream.map(|res| {
-            res.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>).and_then(|bytes| {
-                let chunk: OllamaChatResponse = serde_json::from_slice(&bytes)?;
-                
-                // Ollama stream chunks usually contain one message piece or tool call
-                if let Some(tool_calls) = chunk.message.tool_calls {
-                    if let Some((index, tc)) = tool_calls.into_iter().enumerate().next() {
-                        return Ok(GaiseInstructStreamResponse {
-                            chunk: GaiseStreamChunk::ToolCall {
-                                index,
-                                id: None,
-                                name: Some(tc.function.name),
-                                arguments: Some(serde_json::to_string(&tc.function.arguments).unwrap_or_default()),
-                            },
-                            external_id: None,
-                        });
-                    }
-                }
-
-                if chunk.done {
-                    // Could emit usage here
-                    let mut input_usage = HashMap::new();
-                    input_usage.insert("prompt_tokens".to_string(), chunk.prompt_eval_count.unwrap_or(0));
-                    let mut output_usage = HashMap::new();
-                    output_usage.insert("completion_tokens".to_string(), chunk.eval_count.unwrap_or(0));
-                    
-                    return Ok(GaiseInstructStreamResponse {
-                        chunk: GaiseStreamChunk::Usage(GaiseUsage {
-                            input: Some(input_usage),
-                            output: Some(output_usage),
-                        }),
-                        external_id: None,
-                    });
-                }
-
-                Ok(GaiseInstructStreamResponse {
-                    chunk: GaiseStreamChunk::Text(chunk.message.content.unwrap_or_default()),
-                    external_id: None,
-                })
-            })
-        });
+        // Ollama streams newline-delimited JSON, one chunk per line.
+        let mapped_stream = gaise_core::sse::lines(response.bytes_stream())
+            .flat_map(|res| {
+                let items = match res.and_then(|line| map_stream_line(&line)) {
+                    Ok(chunks) => chunks.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures_util::stream::iter(items)
+            });
 
         Ok(Box::pin(mapped_stream))
     }
 
-    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
         let url = format!("{}/api/chat", self.api_url);
         
         let ollama_request = OllamaChatRequest::from(request);
@@ -253,31 +311,24 @@ impl GaiseClient for GaiseClientOllama {
         let response = self.client.post(url)
             .json(&ollama_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format_ollama_error(&err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let ollama_response: OllamaChatResponse = response.json().await?;
-
-        let mut input_usage = HashMap::new();
-        input_usage.insert("prompt_tokens".to_string(), ollama_response.prompt_eval_count.unwrap_or(0));
-        let mut output_usage = HashMap::new();
-        output_usage.insert("completion_tokens".to_string(), ollama_response.eval_count.unwrap_or(0));
+        let ollama_response: OllamaChatResponse = response.json().await.map_err(request_error)?;
 
         Ok(GaiseInstructResponse {
             output: OneOrMany::One(self.map_from_ollama_message(ollama_response.message)),
             external_id: None,
-            usage: Some(GaiseUsage {
-                input: Some(input_usage),
-                output: Some(output_usage),
-            }),
+            usage: Some(ollama_usage(ollama_response.prompt_eval_count, ollama_response.eval_count)),
+            model: None,
         })
     }
 
-    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
         let url = format!("{}/api/embed", self.api_url);
         
         let inputs = match &request.input {
@@ -294,25 +345,19 @@ impl GaiseClient for GaiseClientOllama {
         let response = self.client.post(url)
             .json(&ollama_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format_ollama_error(&err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let ollama_response: OllamaEmbedResponse = response.json().await?;
-
-        let mut input_usage = HashMap::new();
-        input_usage.insert("prompt_tokens".to_string(), ollama_response.prompt_eval_count.unwrap_or(0));
+        let ollama_response: OllamaEmbedResponse = response.json().await.map_err(request_error)?;
 
         Ok(GaiseEmbeddingsResponse {
             external_id: None,
             output: ollama_response.embeddings,
-            usage: Some(GaiseUsage {
-                input: Some(input_usage),
-                output: None,
-            }),
+            usage: Some(ollama_usage(ollama_response.prompt_eval_count, None)),
         })
     }
 }
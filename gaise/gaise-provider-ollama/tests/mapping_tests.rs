@@ -4,6 +4,8 @@ use gaise_core::contracts::{
     GaiseToolCall, GaiseFunctionCall
 };
 use gaise_provider_ollama::contracts::models::OllamaChatRequest;
+use gaise_core::contracts::GaiseStreamChunk;
+use gaise_provider_ollama::ollama_client::map_stream_line;
 use std::collections::HashMap;
 
 #[test]
@@ -185,3 +187,20 @@ fn test_mapping_tool_response_request() {
     assert_eq!(tool_calls[0].function.name, "get_weather");
     assert_eq!(tool_calls[0].function.arguments.get("location").unwrap(), "London");
 }
+
+#[test]
+fn test_mapping_stream_done_line_reports_usage() {
+    let line = r#"{"model":"llama3","created_at":"2024-01-01T00:00:00Z","message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":26,"eval_count":290}"#;
+
+    let chunks = map_stream_line(line).unwrap();
+    assert_eq!(chunks.len(), 1);
+    match &chunks[0].chunk {
+        GaiseStreamChunk::Usage(usage) => {
+            assert_eq!(usage.input_tokens, Some(26));
+            assert_eq!(usage.output_tokens, Some(290));
+            assert_eq!(usage.total_tokens, Some(316));
+            assert_eq!(usage.output.as_ref().unwrap().get("eval_count"), Some(&290));
+        }
+        other => panic!("Expected usage, got {:?}", other),
+    }
+}
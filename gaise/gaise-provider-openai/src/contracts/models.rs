@@ -16,6 +16,13 @@ pub struct OpenAIChatRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
     pub stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<OpenAIStreamOptions>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct OpenAIStreamOptions {
+    pub include_usage: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -120,8 +127,23 @@ pub struct OpenAIChoice {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OpenAIUsage {
     pub prompt_tokens: usize,
+    #[serde(default)]
     pub completion_tokens: usize,
     pub total_tokens: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt_tokens_details: Option<OpenAIPromptTokensDetails>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completion_tokens_details: Option<OpenAICompletionTokensDetails>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenAIPromptTokensDetails {
+    pub cached_tokens: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenAICompletionTokensDetails {
+    pub reasoning_tokens: Option<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -131,6 +153,8 @@ pub struct OpenAIChatStreamResponse {
     pub created: u64,
     pub model: String,
     pub choices: Vec<OpenAIStreamChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<OpenAIUsage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
 use gaise_core::contracts::{
     GaiseContent, GaiseEmbeddingsRequest, GaiseEmbeddingsResponse, GaiseInstructRequest,
     GaiseInstructResponse, GaiseInstructStreamResponse, GaiseMessage, GaiseStreamChunk,
@@ -7,14 +7,17 @@ use gaise_core::contracts::{
 };
 use crate::contracts::*;
 use futures_util::{Stream, StreamExt};
-use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use base64::Engine;
 
 pub struct GaiseClientOpenAI {
     api_url: String,
     api_key: String,
     client: reqwest::Client,
+    /// Set once the endpoint rejected `stream_options`, as some OpenAI-compatible servers
+    /// do, so later streams are sent without it and report no usage.
+    stream_usage_unsupported: AtomicBool,
 }
 
 impl From<GaiseTool> for OpenAITool {
@@ -93,10 +96,10 @@ impl From<&GaiseInstructRequest> for OpenAIChatRequest {
                     }
                 }).collect();
 
-                if parts.len() == 1 && matches!(parts.first(), Some(OpenAIContentPart::Text { .. })) {
-                    if let Some(OpenAIContentPart::Text { text }) = parts.first() {
-                        return OpenAIContent::Text(text.clone());
-                    }
+                if parts.len() == 1
+                    && let Some(OpenAIContentPart::Text { text }) = parts.first()
+                {
+                    return OpenAIContent::Text(text.clone());
                 }
                 OpenAIContent::Parts(parts)
             });
@@ -131,8 +134,101 @@ impl From<&GaiseInstructRequest> for OpenAIChatRequest {
             max_tokens: request.generation_config.as_ref().and_then(|c| c.max_tokens),
             prompt_cache_key: request.generation_config.as_ref().and_then(|c| c.cache_key.clone()),
             tools: request.tools.as_ref().map(|ts| ts.iter().map(|t| OpenAITool::from(t.clone())).collect()),
+            stream_options: None,
+        }
+    }
+}
+
+impl From<&OpenAIUsage> for GaiseUsage {
+    fn from(u: &OpenAIUsage) -> Self {
+        let cached = u.prompt_tokens_details.as_ref().and_then(|d| d.cached_tokens);
+        let reasoning = u.completion_tokens_details.as_ref().and_then(|d| d.reasoning_tokens);
+
+        let mut usage = GaiseUsage::new(u.prompt_tokens, u.completion_tokens)
+            .with_raw_input("prompt_tokens", u.prompt_tokens)
+            .with_raw_output("completion_tokens", u.completion_tokens);
+        usage.total_tokens = Some(u.total_tokens);
+        if let Some(cached) = cached {
+            usage.cache_read_tokens = Some(cached);
+            usage = usage.with_raw_input("cached_tokens", cached);
+        }
+        if let Some(reasoning) = reasoning {
+            usage.reasoning_tokens = Some(reasoning);
+            usage = usage.with_raw_output("reasoning_tokens", reasoning);
+        }
+        usage
+    }
+}
+
+/// Classifies a failure to send the request or read the response.
+fn request_error(e: reqwest::Error) -> GaiseError {
+    if e.is_timeout() {
+        GaiseError::Timeout(format!("OpenAI request timed out: {}", e))
+    } else if e.is_decode() {
+        GaiseError::Other(format!("Invalid OpenAI response: {}", e))
+    } else {
+        GaiseError::ProviderUnavailable(format!("OpenAI request failed: {}", e))
+    }
+}
+
+/// Classifies a non-success response by its status, `Retry-After` header and body.
+async fn response_error(response: reqwest::Response) -> GaiseError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(GaiseError::parse_retry_after);
+    let body = response.text().await.unwrap_or_default();
+    GaiseError::from_status("OpenAI", status, &body, retry_after)
+}
+
+/// Maps one streamed `chat.completion.chunk` to Gaise chunks. The last chunk carries no
+/// choices, only the usage requested through `stream_options.include_usage`.
+pub fn map_stream_event(data: &str) -> Result<Vec<GaiseInstructStreamResponse>, GaiseError> {
+    let value: serde_json::Value = serde_json::from_str(data)
+        .map_err(|e| GaiseError::Stream(format!("Invalid OpenAI stream chunk: {}", e)))?;
+    if let Some(error) = value.get("error") {
+        return Err(GaiseError::Stream(format!("OpenAI API error: {}", error)));
+    }
+    let chunk: OpenAIChatStreamResponse = serde_json::from_value(value)
+        .map_err(|e| GaiseError::Stream(format!("Invalid OpenAI stream chunk: {}", e)))?;
+    let mut responses = Vec::new();
+
+    if let Some(choice) = chunk.choices.first() {
+        if let Some(content) = &choice.delta.content
+            && !content.is_empty()
+        {
+            responses.push(GaiseInstructStreamResponse {
+                chunk: GaiseStreamChunk::Text(content.clone()),
+                external_id: Some(chunk.id.clone()),
+                model: None,
+            });
+        }
+
+        for tc in choice.delta.tool_calls.iter().flatten() {
+            responses.push(GaiseInstructStreamResponse {
+                chunk: GaiseStreamChunk::ToolCall {
+                    index: tc.index,
+                    id: tc.id.clone(),
+                    name: tc.function.as_ref().and_then(|f| f.name.clone()),
+                    arguments: tc.function.as_ref().and_then(|f| f.arguments.clone()),
+                },
+                external_id: Some(chunk.id.clone()),
+                model: None,
+            });
         }
     }
+
+    if let Some(usage) = &chunk.usage {
+        responses.push(GaiseInstructStreamResponse {
+            chunk: GaiseStreamChunk::Usage(GaiseUsage::from(usage)),
+            external_id: Some(chunk.id.clone()),
+            model: None,
+        });
+    }
+
+    Ok(responses)
 }
 
 impl GaiseClientOpenAI {
@@ -141,9 +237,19 @@ impl GaiseClientOpenAI {
             api_url,
             api_key,
             client: reqwest::Client::new(),
+            stream_usage_unsupported: AtomicBool::new(false),
         }
     }
 
+    async fn post_chat(&self, request: &OpenAIChatRequest) -> Result<reqwest::Response, GaiseError> {
+        self.client.post(format!("{}/chat/completions", self.api_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(request)
+            .send()
+            .await
+            .map_err(request_error)
+    }
+
     fn map_from_openai_message(&self, msg: OpenAIMessage) -> GaiseMessage {
         let content = msg.content.map(|c| match c {
             OpenAIContent::Text(text) => OneOrMany::One(GaiseContent::Text { text }),
@@ -186,118 +292,66 @@ impl GaiseClient for GaiseClientOpenAI {
         &self,
         request: &GaiseInstructRequest,
     ) -> Result<
-        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>> + Send>>,
-        Box<dyn std::error::Error + Send + Sync>,
+        Pin<Box<dyn Stream<Item = Result<GaiseInstructStreamResponse, GaiseError>> + Send>>,
+        GaiseError,
     > {
-        let url = format!("{}/chat/completions", self.api_url);
-        
         let mut openai_request = OpenAIChatRequest::from(request);
         openai_request.stream = true;
-
-        let response = self.client.post(url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&openai_request)
-            .send()
-            .await?;
-
-        if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format!("OpenAI API error: {}", err_text).into());
+        if !self.stream_usage_unsupported.load(Ordering::Relaxed) {
+            openai_request.stream_options = Some(OpenAIStreamOptions { include_usage: true });
         }
 
-        let stream = response.bytes_stream();
-        
-        let mapped_stream = stream.map(|res| {
-            res.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>).and_then(|bytes| {
-                let line = std::str::from_utf8(&bytes)?;
-                if line.trim() == "data: [DONE]" {
-                    // End of stream
-                    return Err("EOF".into()); 
-                }
-                
-                if !line.starts_with("data: ") {
-                     return Err("Unexpected stream format".into());
-                }
+        let mut response = self.post_chat(&openai_request).await?;
 
-                let json_str = &line[6..];
-                let chunk: OpenAIChatStreamResponse = serde_json::from_str(json_str)?;
-                
-                if let Some(choice) = chunk.choices.first() {
-                    if let Some(tool_calls) = &choice.delta.tool_calls {
-                        if let Some(tc) = tool_calls.first() {
-                            return Ok(GaiseInstructStreamResponse {
-                                chunk: GaiseStreamChunk::ToolCall {
-                                    index: tc.index,
-                                    id: tc.id.clone(),
-                                    name: tc.function.as_ref().and_then(|f| f.name.clone()),
-                                    arguments: tc.function.as_ref().and_then(|f| f.arguments.clone()),
-                                },
-                                external_id: Some(chunk.id.clone()),
-                            });
-                        }
-                    }
+        // Servers that do not know `stream_options` answer 400; ask once more without it.
+        if response.status() == reqwest::StatusCode::BAD_REQUEST && openai_request.stream_options.is_some() {
+            openai_request.stream_options = None;
+            let retried = self.post_chat(&openai_request).await?;
+            if retried.status().is_success() {
+                self.stream_usage_unsupported.store(true, Ordering::Relaxed);
+            }
+            response = retried;
+        }
 
-                    if let Some(content) = &choice.delta.content {
-                        return Ok(GaiseInstructStreamResponse {
-                            chunk: GaiseStreamChunk::Text(content.clone()),
-                            external_id: Some(chunk.id.clone()),
-                        });
-                    }
-                }
+        if !response.status().is_success() {
+            return Err(response_error(response).await);
+        }
 
-                // If no content or tool calls, it might be an empty chunk or just metadata
-                // Returning a dummy or filtering it out would be better.
-                Err("Empty chunk".into())
-            })
-        })
-        .filter(|res| {
-            // Filter out EOF and Empty chunks
-            match res {
-                Err(e) if e.to_string() == "EOF" || e.to_string() == "Empty chunk" => futures_util::future::ready(false),
-                _ => futures_util::future::ready(true),
-            }
-        });
+        let mapped_stream = gaise_core::sse::data_events(response.bytes_stream())
+            .take_while(|res| futures_util::future::ready(!matches!(res, Ok(data) if data.trim() == "[DONE]")))
+            .flat_map(|res| {
+                let items = match res.and_then(|data| map_stream_event(&data)) {
+                    Ok(chunks) => chunks.into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures_util::stream::iter(items)
+            });
 
         Ok(Box::pin(mapped_stream))
     }
 
-    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
-        let url = format!("{}/chat/completions", self.api_url);
-        
+    async fn instruct(&self, request: &GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
         let openai_request = OpenAIChatRequest::from(request);
 
-        let response = self.client.post(url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&openai_request)
-            .send()
-            .await?;
+        let response = self.post_chat(&openai_request).await?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format!("OpenAI API error: {}", err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let openai_response: OpenAIChatResponse = response.json().await?;
+        let openai_response: OpenAIChatResponse = response.json().await.map_err(request_error)?;
 
-        let usage = openai_response.usage.map(|u| {
-            let mut input = HashMap::new();
-            input.insert("prompt_tokens".to_string(), u.prompt_tokens);
-            let mut output = HashMap::new();
-            output.insert("completion_tokens".to_string(), u.completion_tokens);
-            GaiseUsage {
-                input: Some(input),
-                output: Some(output),
-            }
-        });
+        let usage = openai_response.usage.as_ref().map(GaiseUsage::from);
 
         Ok(GaiseInstructResponse {
             output: OneOrMany::Many(openai_response.choices.into_iter().map(|c| self.map_from_openai_message(c.message)).collect()),
             external_id: Some(openai_response.id),
             usage,
+            model: None,
         })
     }
 
-    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn embeddings(&self, request: &GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
         let url = format!("{}/embeddings", self.api_url);
         
         let input = match &request.input {
@@ -314,25 +368,19 @@ impl GaiseClient for GaiseClientOpenAI {
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&openai_request)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !response.status().is_success() {
-            let err_text = response.text().await?;
-            return Err(format!("OpenAI API error: {}", err_text).into());
+            return Err(response_error(response).await);
         }
 
-        let openai_response: OpenAIEmbedResponse = response.json().await?;
-
-        let mut input_usage = HashMap::new();
-        input_usage.insert("prompt_tokens".to_string(), openai_response.usage.prompt_tokens);
+        let openai_response: OpenAIEmbedResponse = response.json().await.map_err(request_error)?;
 
         Ok(GaiseEmbeddingsResponse {
             external_id: Some(openai_response.object),
             output: openai_response.data.into_iter().map(|d| d.embedding).collect(),
-            usage: Some(GaiseUsage {
-                input: Some(input_usage),
-                output: None,
-            }),
+            usage: Some(GaiseUsage::from(&openai_response.usage)),
         })
     }
 }
@@ -4,6 +4,9 @@ use gaise_core::contracts::{
     GaiseToolCall, GaiseFunctionCall
 };
 use gaise_provider_openai::contracts::models::{OpenAIChatRequest, OpenAIContent, OpenAIContentPart};
+use gaise_core::contracts::{GaiseStreamChunk, GaiseUsage};
+use gaise_provider_openai::contracts::models::OpenAIUsage;
+use gaise_provider_openai::openai_client::map_stream_event;
 use std::collections::HashMap;
 
 #[test]
@@ -232,3 +235,34 @@ fn test_mapping_tool_response_request() {
     assert_eq!(openai_request.messages[2].role, "tool");
     assert_eq!(openai_request.messages[2].tool_call_id, Some("call_123".to_string()));
 }
+
+#[test]
+fn test_mapping_usage() {
+    let usage: OpenAIUsage = serde_json::from_value(serde_json::json!({
+        "prompt_tokens": 1000,
+        "completion_tokens": 200,
+        "total_tokens": 1200,
+        "prompt_tokens_details": { "cached_tokens": 800 },
+        "completion_tokens_details": { "reasoning_tokens": 50 }
+    })).unwrap();
+
+    let usage = GaiseUsage::from(&usage);
+    assert_eq!(usage.input_tokens, Some(1000));
+    assert_eq!(usage.output_tokens, Some(200));
+    assert_eq!(usage.cache_read_tokens, Some(800));
+    assert_eq!(usage.reasoning_tokens, Some(50));
+    assert_eq!(usage.total_tokens, Some(1200));
+    assert_eq!(usage.input.unwrap().get("prompt_tokens"), Some(&1000));
+}
+
+#[test]
+fn test_mapping_stream_usage_chunk() {
+    let data = r#"{"id":"chatcmpl-1","object":"chat.completion.chunk","created":1700000000,"model":"gpt-4o","choices":[],"usage":{"prompt_tokens":12,"completion_tokens":3,"total_tokens":15}}"#;
+
+    let chunks = map_stream_event(data).unwrap();
+    assert_eq!(chunks.len(), 1);
+    match &chunks[0].chunk {
+        GaiseStreamChunk::Usage(usage) => assert_eq!(usage.total_tokens, Some(15)),
+        other => panic!("Expected usage, got {:?}", other),
+    }
+}
@@ -0,0 +1,98 @@
+use futures_util::StreamExt;
+use gaise_core::GaiseClient;
+use gaise_core::contracts::{GaiseContent, GaiseInstructRequest, GaiseMessage, GaiseStreamChunk, OneOrMany};
+use gaise_provider_openai::openai_client::GaiseClientOpenAI;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const STREAM_BODY: &str = "data: {\"id\":\"c1\",\"object\":\"chat.completion.chunk\",\"created\":1700000000,\"model\":\"local-model\",\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hi\"}}]}\n\ndata: [DONE]\n\n";
+
+/// Reads one HTTP request and returns its body.
+async fn read_request(socket: &mut TcpStream) -> String {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = socket.read(&mut chunk).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                return String::from_utf8_lossy(&buf[end + 4..end + 4 + length]).to_string();
+            }
+        }
+    }
+    String::new()
+}
+
+/// Serves chat completions, answering 400 to any request that sets `stream_options`, and
+/// records the request bodies.
+async fn serve_without_stream_options(listener: TcpListener, bodies: Arc<Mutex<Vec<String>>>) {
+    loop {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let body = read_request(&mut socket).await;
+        let response = if body.contains("stream_options") {
+            let error = "{\"error\":{\"message\":\"Unrecognized request argument supplied: stream_options\"}}";
+            format!("HTTP/1.1 400 Bad Request\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", error.len(), error)
+        } else {
+            format!("HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}", STREAM_BODY.len(), STREAM_BODY)
+        };
+        bodies.lock().unwrap().push(body);
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.ok();
+    }
+}
+
+fn request() -> GaiseInstructRequest {
+    GaiseInstructRequest {
+        model: "local-model".to_string(),
+        input: OneOrMany::One(GaiseMessage {
+            role: "user".to_string(),
+            content: Some(OneOrMany::One(GaiseContent::Text { text: "hello".to_string() })),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+async fn stream_text(client: &GaiseClientOpenAI) -> String {
+    let mut stream = client.instruct_stream(&request()).await.unwrap();
+    let mut text = String::new();
+    while let Some(chunk) = stream.next().await {
+        if let GaiseStreamChunk::Text(t) = chunk.unwrap().chunk {
+            text.push_str(&t);
+        }
+    }
+    text
+}
+
+#[tokio::test]
+async fn test_stream_retries_without_stream_options_on_bad_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    tokio::spawn(serve_without_stream_options(listener, bodies.clone()));
+
+    let client = GaiseClientOpenAI::new(url, "key".to_string());
+
+    assert_eq!(stream_text(&client).await, "hi");
+    {
+        let bodies = bodies.lock().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[0].contains("\"include_usage\":true"));
+        assert!(!bodies[1].contains("stream_options"));
+    }
+
+    // The client remembers that the endpoint rejects the option.
+    assert_eq!(stream_text(&client).await, "hi");
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 3);
+    assert!(!bodies[2].contains("stream_options"));
+}
@@ -547,27 +547,13 @@ impl GoogleChatCompletionResponse {
     pub fn to_stream_view(&self) -> Vec<GaiseInstructStreamResponse> {
         let mut responses = Vec::new();
 
-        let usage = &self.usage_metadata;
-        let mut input = std::collections::HashMap::new();
-        if let Some(v) = usage.prompt_token_count {
-            input.insert("prompt_tokens".to_string(), v);
-        }
-
-        let mut output = std::collections::HashMap::new();
-        if let Some(v) = usage.candidates_token_count {
-            output.insert("candidates_tokens".to_string(), v);
-        }
-        if let Some(v) = usage.total_token_count {
-            output.insert("total_tokens".to_string(), v);
-        }
-
-        if !input.is_empty() || !output.is_empty() {
+        // Every chunk repeats the usage so far; only the final one is reported so that
+        // summing the stream's usage chunks gives the real total.
+        if self.candidates.iter().any(|c| c.finish_reason.is_some()) {
             responses.push(GaiseInstructStreamResponse {
-                chunk: GaiseStreamChunk::Usage(GaiseUsage {
-                    input: if input.is_empty() { None } else { Some(input) },
-                    output: if output.is_empty() { None } else { Some(output) },
-                }),
+                chunk: GaiseStreamChunk::Usage(GaiseUsage::from(&self.usage_metadata)),
                 external_id: None,
+                model: None,
             });
         }
 
@@ -577,6 +563,7 @@ impl GoogleChatCompletionResponse {
                     responses.push(GaiseInstructStreamResponse {
                         chunk: GaiseStreamChunk::Text(text.clone()),
                         external_id: None,
+                        model: None,
                     });
                 }
                 if let Some(tool_call) = &part.tool_call {
@@ -588,6 +575,7 @@ impl GoogleChatCompletionResponse {
                             arguments: Some(tool_call.args.to_string()),
                         },
                         external_id: None,
+                        model: None,
                     });
                 }
             }
@@ -640,7 +628,8 @@ impl GoogleChatCompletionResponse {
         GaiseInstructResponse {
             output: OneOrMany::Many(outputs),
             external_id: None,
-            usage: None,
+            usage: Some(GaiseUsage::from(&self.usage_metadata)),
+            model: None,
         }
     }
 }
@@ -656,7 +645,14 @@ impl GoogleEmbeddingsResponse {
         GaiseEmbeddingsResponse {
             output: self.predictions.clone().into_iter().map(|x| x.embeddings.unwrap().values ).collect(),
             external_id: None,
-            usage: None,
+            usage: self.metadata.as_ref().and_then(|m| m.total_tokens).map(|total| {
+                GaiseUsage {
+                    input_tokens: Some(total),
+                    total_tokens: Some(total),
+                    ..Default::default()
+                }
+                .with_raw_input("totalTokens", total)
+            }),
         }
     }
 }
@@ -738,10 +734,41 @@ pub struct GoogleUsageMetadata {
     pub total_token_count: Option<usize>,
     #[serde(rename="thoughtsTokenCount")]
     pub thoughts_token_count: Option<usize>,
+    #[serde(rename="cachedContentTokenCount")]
+    pub cached_content_token_count: Option<usize>,
     #[serde(rename="trafficType")]
     pub traffic_type: Option<String>,
 }
 
+impl From<&GoogleUsageMetadata> for GaiseUsage {
+    fn from(u: &GoogleUsageMetadata) -> Self {
+        // `candidatesTokenCount` excludes the thinking tokens, which are billed as output.
+        let input = u.prompt_token_count.unwrap_or(0);
+        let thoughts = u.thoughts_token_count.unwrap_or(0);
+        let output = u.candidates_token_count.unwrap_or(0) + thoughts;
+
+        let mut usage = GaiseUsage::new(input, output);
+        let raw_input = [("promptTokenCount", u.prompt_token_count), ("cachedContentTokenCount", u.cached_content_token_count)];
+        for (key, value) in raw_input {
+            if let Some(value) = value {
+                usage = usage.with_raw_input(key, value);
+            }
+        }
+        let raw_output = [("candidatesTokenCount", u.candidates_token_count), ("thoughtsTokenCount", u.thoughts_token_count)];
+        for (key, value) in raw_output {
+            if let Some(value) = value {
+                usage = usage.with_raw_output(key, value);
+            }
+        }
+        usage.cache_read_tokens = u.cached_content_token_count;
+        usage.reasoning_tokens = u.thoughts_token_count;
+        if let Some(total) = u.total_token_count {
+            usage.total_tokens = Some(total);
+        }
+        usage
+    }
+}
+
 /*
 
     "usageMetadata": {
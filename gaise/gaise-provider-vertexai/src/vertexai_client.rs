@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 use futures_util::StreamExt;
 use gaise_core::{
     GaiseClient,
+    GaiseError,
     contracts::{
         GaiseEmbeddingsRequest,
         GaiseEmbeddingsResponse,
@@ -117,6 +118,29 @@ impl GaiseClientVertexAI {
     }
 }
 
+/// Classifies a failure to send the request or read the response.
+fn request_error(e: reqwest::Error) -> GaiseError {
+    if e.is_timeout() {
+        GaiseError::Timeout(format!("Vertex AI request timed out: {}", e))
+    } else if e.is_decode() {
+        GaiseError::Other(format!("Invalid Vertex AI response: {}", e))
+    } else {
+        GaiseError::ProviderUnavailable(format!("Vertex AI request failed: {}", e))
+    }
+}
+
+/// Classifies a non-success response by its status, `Retry-After` header and body.
+async fn response_error(response: reqwest::Response) -> GaiseError {
+    let status = response.status().as_u16();
+    let retry_after = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(GaiseError::parse_retry_after);
+    let body = response.text().await.unwrap_or_default();
+    GaiseError::from_status("Vertex AI", status, &body, retry_after)
+}
+
 #[async_trait]
 impl GaiseClient for GaiseClientVertexAI {
     async fn instruct_stream(
@@ -126,11 +150,11 @@ impl GaiseClient for GaiseClientVertexAI {
         std::pin::Pin<
             Box<
                 dyn futures_util::Stream<
-                        Item = Result<GaiseInstructStreamResponse, Box<dyn std::error::Error + Send + Sync>>,
+                        Item = Result<GaiseInstructStreamResponse, GaiseError>,
                     > + Send,
             >,
         >,
-        Box<dyn std::error::Error + Send + Sync>,
+        GaiseError,
     > {
         let url = self.api_url.replace("{{MODEL}}", &request.model) + ":streamGenerateContent?alt=sse";
         let json = serde_json::to_string(&GoogleInstructRequest::from(request))?;
@@ -138,55 +162,41 @@ impl GaiseClient for GaiseClientVertexAI {
         let token = self
             .get_token()
             .await
-            .map_err(|e| format!("no google access token: {e}"))?;
+            .map_err(|e| GaiseError::Unauthorized(format!("no google access token: {e}")))?;
 
         let client = reqwest::Client::builder()
             .danger_accept_invalid_certs(true)
-            .build()?;
+            .build()
+            .map_err(request_error)?;
         let res = client
             .post(&url)
             .header("Authorization", "Bearer ".to_owned() + &token)
             .header("Content-type", "application/json")
             .body(json)
             .send()
-            .await?;
+            .await
+            .map_err(request_error)?;
 
         if !res.status().is_success() {
-            let err_text = res.text().await.unwrap_or_default();
-            return Err(format!("Vertex AI error: {}", err_text).into());
+            return Err(response_error(res).await);
         }
 
-        let stream = res.bytes_stream();
-        let event_stream = stream.map(|chunk_res| {
-            let chunk = chunk_res.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-            let text = String::from_utf8_lossy(&chunk);
-            
-            // Vertex AI SSE format: data: {...}
-            let mut results = Vec::new();
-            for line in text.lines() {
-                if let Some(json_str) = line.strip_prefix("data: ") {
-                    let response: GoogleChatCompletionResponse = serde_json::from_str(json_str)
-                        .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?;
-                    
-                    for r in response.to_stream_view() {
-                        results.push(Ok(r));
-                    }
-                }
-            }
-            Ok(futures_util::stream::iter(results))
-        });
-
-        let flattened_stream = event_stream
-            .map(|res| match res {
-                Ok(s) => s.left_stream(),
-                Err(e) => futures_util::stream::iter(vec![Err(e)]).right_stream(),
-            })
-            .flatten();
+        let flattened_stream = gaise_core::sse::data_events(res.bytes_stream())
+            .flat_map(|res| {
+                let items = match res.and_then(|data| {
+                    serde_json::from_str::<GoogleChatCompletionResponse>(&data)
+                        .map_err(|e| GaiseError::Stream(format!("Invalid Vertex AI stream chunk: {}", e)))
+                }) {
+                    Ok(response) => response.to_stream_view().into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                futures_util::stream::iter(items)
+            });
 
         Ok(Box::pin(flattened_stream))
     }
 
-    async fn instruct(&self, request:&GaiseInstructRequest) -> Result<GaiseInstructResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn instruct(&self, request:&GaiseInstructRequest) -> Result<GaiseInstructResponse, GaiseError> {
 
         let url = self.api_url.replace("{{MODEL}}", &request.model) + ":generateContent";
         let json = serde_json::to_string(&GoogleInstructRequest::from(request))?;     
@@ -194,20 +204,25 @@ impl GaiseClient for GaiseClientVertexAI {
        let token = self
             .get_token()
             .await
-            .map_err(|e| format!("no google access token: {e}"))?;
+            .map_err(|e| GaiseError::Unauthorized(format!("no google access token: {e}")))?;
 
         let client = reqwest::Client::builder()
             .danger_accept_invalid_certs(true)
-            .build()?;
+            .build()
+            .map_err(request_error)?;
         let res = client.post(&url)
             .header("Authorization", "Bearer ".to_owned() + &token)
             .header("Content-type", "application/json")
             .body(json)
             .send()
             .await
-            .expect("failed to get response");
+            .map_err(request_error)?;
+
+        if !res.status().is_success() {
+            return Err(response_error(res).await);
+        }
 
-        let res_json = res.text().await.expect("failed to get payload");
+        let res_json = res.text().await.map_err(request_error)?;
 
         println!("{}", res_json);
         let response:GoogleChatCompletionResponse = serde_json::from_str(&res_json)?;
@@ -216,7 +231,7 @@ impl GaiseClient for GaiseClientVertexAI {
         Ok(response_view)
     }
 
-    async fn embeddings(&self, request:&GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, Box<dyn std::error::Error + Send + Sync>> {
+    async fn embeddings(&self, request:&GaiseEmbeddingsRequest) -> Result<GaiseEmbeddingsResponse, GaiseError> {
         
         let url = self.api_url.replace("{{MODEL}}", &request.model) + ":predict";
         let json = serde_json::to_string(&GoogleEmbeddingsRequest::from(request))?;
@@ -224,23 +239,28 @@ impl GaiseClient for GaiseClientVertexAI {
         let token = self
             .get_token()
             .await
-            .map_err(|e| format!("no google access token: {e}"))?;
+            .map_err(|e| GaiseError::Unauthorized(format!("no google access token: {e}")))?;
 
         println!("{}\n{}", url, json);
 
         let client = reqwest::Client::builder()
             .danger_accept_invalid_certs(true)
-            .build()?;
+            .build()
+            .map_err(request_error)?;
         let res = client.post(&url)
             .header("Authorization", "Bearer ".to_owned() + &token)
             .header("Content-type", "application/json")
             .body(json)
             .send()
             .await
-            .expect("failed to get response");
+            .map_err(request_error)?;
+
+        if !res.status().is_success() {
+            return Err(response_error(res).await);
+        }
 
         let res_wrapper = res.text();
-        let res_json = res_wrapper.await.expect("failed to get payload");
+        let res_json = res_wrapper.await.map_err(request_error)?;
         let response:GoogleEmbeddingsResponse = serde_json::from_str(&res_json)?;
         let response_view = response.to_view();
 
@@ -3,6 +3,8 @@ use gaise_core::contracts::{
     GaiseMessage, GaiseTool, GaiseToolParameter, OneOrMany,
 };
 use gaise_provider_vertexai::contracts::models::GoogleInstructRequest;
+use gaise_core::contracts::GaiseStreamChunk;
+use gaise_provider_vertexai::contracts::models::GoogleChatCompletionResponse;
 use std::collections::HashMap;
 
 #[test]
@@ -239,3 +241,37 @@ fn test_mapping_system_instruction() {
     assert_eq!(google_request.contents[0].role, "user");
     assert_eq!(google_request.contents[0].parts[0].text, Some("Hi".to_string()));
 }
+
+fn completion(finish_reason: Option<&str>) -> GoogleChatCompletionResponse {
+    serde_json::from_value(serde_json::json!({
+        "candidates": [{
+            "content": { "role": "model", "parts": [{ "text": "Hi" }] },
+            "finishReason": finish_reason
+        }],
+        "usageMetadata": {
+            "promptTokenCount": 34,
+            "candidatesTokenCount": 176,
+            "thoughtsTokenCount": 185,
+            "cachedContentTokenCount": 20,
+            "totalTokenCount": 395
+        }
+    })).unwrap()
+}
+
+#[test]
+fn test_mapping_usage_counts_thoughts_as_output() {
+    let usage = completion(Some("STOP")).to_view().usage.expect("Missing usage");
+    assert_eq!(usage.input_tokens, Some(34));
+    assert_eq!(usage.output_tokens, Some(361));
+    assert_eq!(usage.reasoning_tokens, Some(185));
+    assert_eq!(usage.cache_read_tokens, Some(20));
+    assert_eq!(usage.total_tokens, Some(395));
+}
+
+#[test]
+fn test_mapping_stream_usage_only_on_final_chunk() {
+    let is_usage = |c: &gaise_core::contracts::GaiseInstructStreamResponse| matches!(c.chunk, GaiseStreamChunk::Usage(_));
+
+    assert!(!completion(None).to_stream_view().iter().any(is_usage));
+    assert_eq!(completion(Some("STOP")).to_stream_view().iter().filter(|c| is_usage(c)).count(), 1);
+}
@@ -0,0 +1,201 @@
+use crate::compaction::{self, CompactionSummary, content_text};
+use crate::context::{self, TokenCalibration};
+use crate::tools::{SpawnAgentArgs, get_tools};
+use anyhow::{Result, anyhow};
+use gaise_core::contracts::{GaiseContent, GaiseGenerationConfig, GaiseInstructRequest, GaiseMessage, GaiseTool, GaiseToolCall, OneOrMany};
+
+/// Name of the tool that starts a sub-agent.
+pub const SPAWN_AGENT: &str = "spawn_agent";
+/// The tools a sub-agent may use. They need no confirmation and change nothing, so several
+/// sub-agents can run at once without asking the user anything.
+pub const READ_ONLY_TOOLS: &[&str] = &["read_file", "grep", "glob", "list_directory"];
+/// Requests a sub-agent may make before it is stopped without an answer.
+pub const MAX_REQUESTS: usize = 40;
+/// Messages at the start of a sub-agent's history that every request keeps: the system
+/// prompt and the task.
+const PREFIX_LEN: usize = 2;
+
+/// A nested agent with a history of its own, started by the `spawn_agent` tool. Only the
+/// text of its final answer goes back to the agent that started it.
+#[derive(Debug, Clone)]
+pub struct SubAgent {
+    /// Counts the sub-agents of the session, to tell their output apart.
+    pub number: usize,
+    pub task: String,
+    pub model: String,
+    pub tools: Vec<&'static str>,
+    pub history: Vec<GaiseMessage>,
+    pub requests: usize,
+    /// Stands in for the older part of the history once it has been compacted.
+    pub compaction: Option<CompactionSummary>,
+}
+
+impl SubAgent {
+    /// A sub-agent for `args` that starts from `system_prompt`, using `default_model` unless
+    /// `args` names one. Fails if `args` asks for a tool that is not read-only.
+    pub fn new(number: usize, args: SpawnAgentArgs, system_prompt: &str, default_model: &str) -> Result<Self> {
+        if args.task.trim().is_empty() {
+            return Err(anyhow!("the task is empty"));
+        }
+        let tools = match &args.tools {
+            None => READ_ONLY_TOOLS.to_vec(),
+            Some(requested) => {
+                let mut tools = Vec::new();
+                for name in requested {
+                    let tool = READ_ONLY_TOOLS
+                        .iter()
+                        .find(|tool| *tool == name)
+                        .ok_or_else(|| anyhow!("sub-agents cannot use '{}', only {}", name, READ_ONLY_TOOLS.join(", ")))?;
+                    if !tools.contains(tool) {
+                        tools.push(*tool);
+                    }
+                }
+                tools
+            }
+        };
+
+        let system_prompt = format!(
+            "{}\n\n# Sub-agent\nYou are running as a sub-agent of another iKode agent, which gave you the task in the next message. You can only use these tools: {}. Nobody will answer questions, so work through the task on your own. When you are done, reply without calling a tool. That reply is all the other agent will see, so make it complete and self-contained, with the file paths, line numbers and facts it needs.",
+            system_prompt,
+            if tools.is_empty() { "none".to_string() } else { tools.join(", ") }
+        );
+        let history = vec![text_message("system", system_prompt), text_message("user", args.task.clone())];
+        Ok(Self {
+            number,
+            task: args.task,
+            model: args.model.unwrap_or_else(|| default_model.to_string()),
+            tools,
+            history,
+            requests: 0,
+            compaction: None,
+        })
+    }
+
+    pub fn allows(&self, tool: &str) -> bool {
+        self.tools.contains(&tool)
+    }
+
+    pub fn tool_definitions(&self) -> Vec<GaiseTool> {
+        get_tools().into_iter().filter(|tool| self.allows(&tool.name)).collect()
+    }
+
+    /// The next request, with the history that fits in `budget` tokens (see `request_history`);
+    /// `None` once the sub-agent has used up its requests.
+    pub fn next_request(
+        &mut self,
+        generation_config: Option<GaiseGenerationConfig>,
+        budget: usize,
+        calibration: &TokenCalibration,
+    ) -> Option<GaiseInstructRequest> {
+        if self.requests >= MAX_REQUESTS {
+            return None;
+        }
+        self.requests += 1;
+        Some(GaiseInstructRequest {
+            input: OneOrMany::Many(self.request_history(budget, calibration)),
+            model: self.model.clone(),
+            tools: (!self.tools.is_empty()).then(|| self.tool_definitions()),
+            generation_config,
+            ..Default::default()
+        })
+    }
+
+    /// The system prompt and the task, the compaction summary if there is one, and as many of
+    /// the later messages as fit in `budget` tokens (0 means unlimited), with a note in place
+    /// of those left out. The latest assistant message and its tool results are always kept.
+    pub fn request_history(&self, budget: usize, calibration: &TokenCalibration) -> Vec<GaiseMessage> {
+        let mut result = self.history[..PREFIX_LEN].to_vec();
+        let mut tail_start = PREFIX_LEN;
+        if let Some(summary) = &self.compaction {
+            result.push(summary.to_message());
+            tail_start = summary.covered_until;
+        }
+
+        let mut kept_start = tail_start;
+        if budget > 0 {
+            let tail = &self.history[tail_start..];
+            let tail_tokens: Vec<usize> = tail.iter()
+                .map(|m| calibration.apply(compaction::estimate_message_tokens(m)))
+                .collect();
+            let fixed_tokens = calibration.apply(compaction::estimate_tokens(&result))
+                + calibration.apply(compaction::estimate_message_tokens(&compaction::truncation_note(self.history.len())));
+            let limit = tail.iter().rposition(|m| m.role == "assistant").unwrap_or(0);
+            let drop = context::fit_to_budget(&tail_tokens, fixed_tokens, context::prompt_budget(budget), limit);
+            if drop > 0 {
+                kept_start = compaction::skip_tool_results(&self.history, tail_start + drop);
+            }
+        }
+
+        if kept_start > tail_start {
+            result.push(compaction::truncation_note(kept_start - tail_start));
+        }
+        result.extend_from_slice(&self.history[kept_start..]);
+        result
+    }
+
+    /// The request asking `model` to summarise the history between the task (or the previous
+    /// summary) and the last `keep_recent` messages, with the index the summary will cover up
+    /// to; `None` when there is nothing to compact.
+    pub fn compaction_request(&self, model: &str, keep_recent: usize) -> Option<(GaiseInstructRequest, usize)> {
+        let start = self.compaction.as_ref().map_or(PREFIX_LEN, |c| c.covered_until);
+        let end = compaction::tool_safe_start(&self.history, self.history.len().saturating_sub(keep_recent));
+        if end <= start {
+            return None;
+        }
+        let previous_summary = self.compaction.as_ref().map(|c| c.summary.as_str());
+        let request = compaction::build_compaction_request(model, previous_summary, &self.history[start..end], "");
+        Some((request, end))
+    }
+
+    /// Sends `summary` in place of the history up to `covered_until` from now on.
+    pub fn compact(&mut self, covered_until: usize, summary: String) {
+        let start = self.compaction.as_ref().map_or(PREFIX_LEN, |c| c.covered_until);
+        let previously_compacted = self.compaction.as_ref().map_or(0, |c| c.compacted_messages);
+        self.compaction = Some(CompactionSummary {
+            covered_until,
+            compacted_messages: previously_compacted + covered_until.saturating_sub(start),
+            summary,
+        });
+    }
+
+    pub fn push_tool_result(&mut self, tool_call_id: &str, result: String) {
+        self.history.push(GaiseMessage {
+            role: "tool".to_string(),
+            content: Some(OneOrMany::One(GaiseContent::Text { text: result })),
+            tool_calls: None,
+            tool_call_id: Some(tool_call_id.to_string()),
+        });
+    }
+
+    /// The text of the assistant messages since the last tool result.
+    pub fn final_answer(&self) -> String {
+        let start = self.history.iter().rposition(|message| message.role != "assistant").map_or(0, |index| index + 1);
+        let answer = self.history[start..]
+            .iter()
+            .filter_map(|message| message.content.as_ref().map(content_text))
+            .collect::<Vec<_>>()
+            .join("\n");
+        if answer.trim().is_empty() {
+            "(The sub-agent finished without an answer.)".to_string()
+        } else {
+            answer.trim().to_string()
+        }
+    }
+}
+
+/// Splits `tool_calls` into runs of consecutive `spawn_agent` calls, which can run at once,
+/// and runs of other calls, keeping their order.
+pub fn group_agent_calls(tool_calls: &[GaiseToolCall]) -> Vec<&[GaiseToolCall]> {
+    tool_calls
+        .chunk_by(|a, b| (a.function.name == SPAWN_AGENT) == (b.function.name == SPAWN_AGENT))
+        .collect()
+}
+
+fn text_message(role: &str, text: String) -> GaiseMessage {
+    GaiseMessage {
+        role: role.to_string(),
+        content: Some(OneOrMany::One(GaiseContent::Text { text })),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
@@ -0,0 +1,250 @@
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A file as it was before the first change of a turn.
+#[derive(Debug, Clone)]
+pub struct FileSnapshot {
+    pub path: PathBuf,
+    /// `None` if the file did not exist, so restoring it deletes the file.
+    pub content: Option<Vec<u8>>,
+}
+
+/// A directory as it was before the first change of a turn.
+#[derive(Debug, Clone)]
+pub struct DirectorySnapshot {
+    pub path: PathBuf,
+    /// Whether the directory existed, so restoring recreates it, or removes it if the turn
+    /// created it.
+    pub existed: bool,
+}
+
+/// The files and directories changed during one user turn, as they were before the turn.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    pub prompt: String,
+    pub created_at: DateTime<Utc>,
+    /// Length of the history before the turn's prompt, for rewinding the conversation;
+    /// `None` once the history has been replaced, e.g. by `/clear`.
+    pub history_len: Option<usize>,
+    pub files: Vec<FileSnapshot>,
+    pub directories: Vec<DirectorySnapshot>,
+}
+
+/// What `restore` put back.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Restored {
+    /// Files written back, or deleted if they were created after the checkpoint.
+    pub files: Vec<PathBuf>,
+    /// Directories recreated, or removed if they were created after the checkpoint.
+    pub directories: Vec<PathBuf>,
+    /// Where the history stood before the checkpoint's turn, if still known.
+    pub history_len: Option<usize>,
+}
+
+/// Snapshots of files changed by tools, grouped by user turn. A turn gets a checkpoint when
+/// it changes its first file; checkpoints are numbered from 1 and live for the session.
+#[derive(Debug, Default)]
+pub struct Checkpoints {
+    checkpoints: Vec<Checkpoint>,
+    /// The turn in progress, until it changes a file.
+    pending: Option<(String, usize)>,
+    /// Whether the last checkpoint belongs to the turn in progress.
+    current_turn_has_checkpoint: bool,
+}
+
+impl Checkpoints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn list(&self) -> &[Checkpoint] {
+        &self.checkpoints
+    }
+
+    /// Starts a new turn for `prompt`, with `history_len` messages before it.
+    pub fn begin_turn(&mut self, prompt: &str, history_len: usize) {
+        self.pending = Some((prompt.to_string(), history_len));
+        self.current_turn_has_checkpoint = false;
+    }
+
+    /// Records `path` as it is now, unless this turn already did. Call before every change.
+    pub fn snapshot(&mut self, path: &Path) -> Result<()> {
+        let checkpoint = self.current();
+        if checkpoint.files.iter().any(|file| file.path == path) {
+            return Ok(());
+        }
+        let content = match fs::read(path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(anyhow!("Could not snapshot {}: {}", path.display(), e)),
+        };
+        checkpoint.files.push(FileSnapshot { path: path.to_path_buf(), content });
+        Ok(())
+    }
+
+    /// Records whether the directory `path` exists, along with its missing parents, unless
+    /// this turn already did. Call before creating or removing a directory.
+    pub fn snapshot_directory(&mut self, path: &Path) {
+        let checkpoint = self.current();
+        let mut next = Some(path);
+        while let Some(path) = next {
+            if checkpoint.directories.iter().any(|dir| dir.path == path) {
+                break;
+            }
+            let existed = path.is_dir();
+            checkpoint.directories.push(DirectorySnapshot { path: path.to_path_buf(), existed });
+            if existed {
+                break;
+            }
+            next = path.parent();
+        }
+    }
+
+    /// Records everything moving `source` to `destination` changes: the files and directories
+    /// that disappear from the source and those that appear at the destination, including
+    /// the destination's missing parents.
+    pub fn snapshot_move(&mut self, source: &Path, destination: &Path) -> Result<()> {
+        let moved = |path: &Path| match path.strip_prefix(source) {
+            Ok(inner) if !inner.as_os_str().is_empty() => destination.join(inner),
+            _ => destination.to_path_buf(),
+        };
+        if let Some(parent) = destination.parent().filter(|parent| !parent.exists()) {
+            self.snapshot_directory(parent);
+        }
+        for dir in directories_under(source)? {
+            self.snapshot_directory(&dir);
+            self.snapshot_directory(&moved(&dir));
+        }
+        for file in files_under(source)? {
+            self.snapshot(&file)?;
+            self.snapshot(&moved(&file))?;
+        }
+        Ok(())
+    }
+
+    /// The checkpoint of the turn in progress, created on its first change.
+    fn current(&mut self) -> &mut Checkpoint {
+        if !self.current_turn_has_checkpoint {
+            let (prompt, history_len) = match self.pending.take() {
+                Some((prompt, history_len)) => (prompt, Some(history_len)),
+                None => (String::new(), None),
+            };
+            self.checkpoints.push(Checkpoint {
+                prompt,
+                created_at: Utc::now(),
+                history_len,
+                files: Vec::new(),
+                directories: Vec::new(),
+            });
+            self.current_turn_has_checkpoint = true;
+        }
+        self.checkpoints.last_mut().expect("a checkpoint was just ensured")
+    }
+
+    /// Rolls every file and directory back to how it was before checkpoint `number` and drops that
+    /// checkpoint and all later ones.
+    pub fn restore(&mut self, number: usize) -> Result<Restored> {
+        if number == 0 || number > self.checkpoints.len() {
+            return Err(anyhow!("No checkpoint {} (there are {})", number, self.checkpoints.len()));
+        }
+
+        let mut files: Vec<PathBuf> = Vec::new();
+        let mut directories: Vec<PathBuf> = Vec::new();
+        // Newest first, so a file changed in several turns ends up as the oldest snapshot.
+        for checkpoint in self.checkpoints[number - 1..].iter().rev() {
+            for snapshot in checkpoint.directories.iter().filter(|dir| dir.existed && !dir.path.is_dir()) {
+                fs::create_dir_all(&snapshot.path).map_err(|e| anyhow!("Could not restore {}: {}", snapshot.path.display(), e))?;
+                directories.push(snapshot.path.clone());
+            }
+            for snapshot in &checkpoint.files {
+                match &snapshot.content {
+                    Some(content) => {
+                        if let Some(parent) = snapshot.path.parent() {
+                            fs::create_dir_all(parent)?;
+                        }
+                        fs::write(&snapshot.path, content)
+                    }
+                    None if snapshot.path.exists() => fs::remove_file(&snapshot.path),
+                    None => Ok(()),
+                }
+                .map_err(|e| anyhow!("Could not restore {}: {}", snapshot.path.display(), e))?;
+                if !files.contains(&snapshot.path) {
+                    files.push(snapshot.path.clone());
+                }
+            }
+            // Deepest first, so created parents are empty by the time they are removed. A
+            // created directory that still holds files the turns did not create is kept.
+            let mut created: Vec<&DirectorySnapshot> = checkpoint.directories.iter().filter(|dir| !dir.existed).collect();
+            created.sort_by_key(|dir| std::cmp::Reverse(dir.path.components().count()));
+            for snapshot in created {
+                let is_empty = fs::read_dir(&snapshot.path).is_ok_and(|mut entries| entries.next().is_none());
+                if is_empty {
+                    fs::remove_dir(&snapshot.path).map_err(|e| anyhow!("Could not restore {}: {}", snapshot.path.display(), e))?;
+                    directories.push(snapshot.path.clone());
+                }
+            }
+        }
+
+        let history_len = self.checkpoints[number - 1].history_len;
+        self.checkpoints.truncate(number - 1);
+        self.current_turn_has_checkpoint = false;
+        files.sort();
+        directories.sort();
+        directories.dedup();
+        Ok(Restored { files, directories, history_len })
+    }
+
+    /// Rolls back the most recent checkpoint, `None` if there is none.
+    pub fn undo(&mut self) -> Result<Option<Restored>> {
+        match self.checkpoints.len() {
+            0 => Ok(None),
+            last => self.restore(last).map(Some),
+        }
+    }
+
+    /// Keeps the file snapshots but stops offering to rewind the history, after the history
+    /// they point into was replaced.
+    pub fn forget_history(&mut self) {
+        for checkpoint in &mut self.checkpoints {
+            checkpoint.history_len = None;
+        }
+        self.pending = None;
+    }
+}
+
+/// Every file under `path`, or `path` itself if it is not a directory, for snapshotting the
+/// files a directory move touches.
+pub fn files_under(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            files.extend(files_under(&entry.path())?);
+        } else {
+            files.push(entry.path());
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// `path` and every directory under it, or nothing if it is not a directory.
+fn directories_under(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut directories = vec![path.to_path_buf()];
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            directories.extend(directories_under(&entry.path())?);
+        }
+    }
+    Ok(directories)
+}
@@ -0,0 +1,322 @@
+use crate::permissions::wildcard_match;
+use anyhow::{Result, anyhow};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Placeholder in a custom command for the text typed after its name.
+pub const ARGUMENTS_PLACEHOLDER: &str = "$ARGUMENTS";
+
+/// A slash command handled by ikode itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Builtin {
+    Help,
+    Model,
+    History,
+    MaxHistory,
+    PrefixKeep,
+    Compact,
+    Cost,
+    Config,
+    Permissions,
+    Jobs,
+    Mcp,
+    Hooks,
+    Checkpoints,
+    Undo,
+    Restore,
+    MaxCost,
+    Sessions,
+    Clear,
+    ClearScreen,
+    Exit,
+}
+
+/// A built-in command with the names it answers to and its lines in `/help`.
+pub struct BuiltinCommand {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub builtin: Builtin,
+    /// The arguments and description of each form of the command.
+    pub usage: &'static [(&'static str, &'static str)],
+}
+
+/// The built-in commands, in the order `/help` lists them.
+pub const BUILTIN_COMMANDS: &[BuiltinCommand] = &[
+    BuiltinCommand { name: "help", aliases: &[], builtin: Builtin::Help, usage: &[("", "Display this help message")] },
+    BuiltinCommand {
+        name: "model",
+        aliases: &[],
+        builtin: Builtin::Model,
+        usage: &[("", "Display the current model"), ("{model}", "Switch to a different model")],
+    },
+    BuiltinCommand { name: "history", aliases: &[], builtin: Builtin::History, usage: &[("", "Show history settings and stats")] },
+    BuiltinCommand {
+        name: "max-history",
+        aliases: &[],
+        builtin: Builtin::MaxHistory,
+        usage: &[("{n}", "Set max history messages (0 = unlimited)")],
+    },
+    BuiltinCommand {
+        name: "prefix-keep",
+        aliases: &[],
+        builtin: Builtin::PrefixKeep,
+        usage: &[("{n}", "Set number of prefix messages to always keep")],
+    },
+    BuiltinCommand { name: "compact", aliases: &[], builtin: Builtin::Compact, usage: &[("", "Summarise older messages to free up context")] },
+    BuiltinCommand {
+        name: "cost",
+        aliases: &[],
+        builtin: Builtin::Cost,
+        usage: &[("", "Show token usage and cost for the last turn and the session")],
+    },
+    BuiltinCommand {
+        name: "config",
+        aliases: &[],
+        builtin: Builtin::Config,
+        usage: &[("", "Show the effective settings and where each one came from")],
+    },
+    BuiltinCommand { name: "permissions", aliases: &[], builtin: Builtin::Permissions, usage: &[("", "Show the permission rules for tool calls")] },
+    BuiltinCommand {
+        name: "jobs",
+        aliases: &[],
+        builtin: Builtin::Jobs,
+        usage: &[("", "List background jobs started by the agent"), ("kill {id}", "Stop a background job")],
+    },
+    BuiltinCommand { name: "mcp", aliases: &[], builtin: Builtin::Mcp, usage: &[("", "Show the MCP servers and the tools they provide")] },
+    BuiltinCommand { name: "hooks", aliases: &[], builtin: Builtin::Hooks, usage: &[("", "Show the hooks and the events they run on")] },
+    BuiltinCommand {
+        name: "checkpoints",
+        aliases: &[],
+        builtin: Builtin::Checkpoints,
+        usage: &[("", "List the checkpoints of files changed this session")],
+    },
+    BuiltinCommand {
+        name: "undo",
+        aliases: &[],
+        builtin: Builtin::Undo,
+        usage: &[("[--history]", "Revert the files changed by the last turn (and rewind the conversation)")],
+    },
+    BuiltinCommand {
+        name: "restore",
+        aliases: &[],
+        builtin: Builtin::Restore,
+        usage: &[("{n} [--history]", "Revert the files to how they were before checkpoint n")],
+    },
+    BuiltinCommand {
+        name: "max-cost",
+        aliases: &[],
+        builtin: Builtin::MaxCost,
+        usage: &[("{usd}", "Stop the agent once the session costs more (0 = no limit)")],
+    },
+    BuiltinCommand {
+        name: "sessions",
+        aliases: &[],
+        builtin: Builtin::Sessions,
+        usage: &[("", "List saved sessions for this directory"), ("{n|id}", "Load a saved session")],
+    },
+    BuiltinCommand {
+        name: "clear",
+        aliases: &[],
+        builtin: Builtin::Clear,
+        usage: &[("", "Reset the conversation history and start a new session")],
+    },
+    BuiltinCommand { name: "cls", aliases: &["clear_screen"], builtin: Builtin::ClearScreen, usage: &[("", "Clear the terminal screen")] },
+    BuiltinCommand { name: "exit", aliases: &[], builtin: Builtin::Exit, usage: &[("", "Quit the interactive session")] },
+];
+
+/// A prompt template from `.ikode/commands/<name>.md` or `~/.config/ikode/commands/<name>.md`,
+/// run as `/<name> [arguments]`:
+///
+/// ```markdown
+/// ---
+/// description: Review a file for bugs
+/// argument-hint: {file}
+/// model: anthropic::claude-sonnet-4-5
+/// allowed-tools: read_file, grep, glob
+/// ---
+/// Review $ARGUMENTS for bugs and explain each one you find.
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomCommand {
+    pub name: String,
+    pub description: Option<String>,
+    /// Shown after the name in `/help`, e.g. `{file}`.
+    pub argument_hint: Option<String>,
+    /// Model for the turn the command starts, instead of the current one.
+    pub model: Option<String>,
+    /// Tool name patterns the model may use during the turn, where `*` matches anything;
+    /// every tool if unset.
+    pub allowed_tools: Option<Vec<String>>,
+    pub template: String,
+    /// The file the command was loaded from.
+    pub source: PathBuf,
+}
+
+impl CustomCommand {
+    /// The prompt for `arguments`: the template with `$ARGUMENTS` replaced, or followed by
+    /// the arguments if it has no placeholder.
+    pub fn render(&self, arguments: &str) -> String {
+        if self.template.contains(ARGUMENTS_PLACEHOLDER) {
+            self.template.replace(ARGUMENTS_PLACEHOLDER, arguments)
+        } else if arguments.is_empty() {
+            self.template.clone()
+        } else {
+            format!("{}\n\n{}", self.template, arguments)
+        }
+    }
+
+    pub fn allows_tool(&self, tool: &str) -> bool {
+        self.allowed_tools.as_ref().is_none_or(|patterns| patterns.iter().any(|pattern| wildcard_match(pattern, tool)))
+    }
+
+    /// The description from the frontmatter, or else the first line of the template.
+    pub fn summary(&self) -> String {
+        if let Some(description) = &self.description {
+            return description.clone();
+        }
+        let line = self.template.lines().map(str::trim).find(|line| !line.is_empty()).unwrap_or_default();
+        match line.char_indices().nth(80) {
+            Some((end, _)) => format!("{}...", &line[..end]),
+            None => line.to_string(),
+        }
+    }
+}
+
+/// What a line typed at the prompt asks for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlashCommand<'a> {
+    Builtin(Builtin),
+    Custom(&'a CustomCommand),
+}
+
+/// The built-in commands together with the custom ones.
+#[derive(Debug, Clone, Default)]
+pub struct SlashCommands {
+    custom: Vec<CustomCommand>,
+}
+
+impl SlashCommands {
+    pub fn new(custom: Vec<CustomCommand>) -> Self {
+        Self { custom }
+    }
+
+    pub fn custom(&self) -> &[CustomCommand] {
+        &self.custom
+    }
+
+    /// The command `input` names and the arguments after it, or `None` if it is not a
+    /// known command and should go to the model as it is.
+    pub fn parse<'a, 'b>(&'a self, input: &'b str) -> Option<(SlashCommand<'a>, &'b str)> {
+        let rest = input.strip_prefix('/')?;
+        let (name, arguments) = rest.split_once(char::is_whitespace).map_or((rest, ""), |(name, arguments)| (name, arguments.trim()));
+        if let Some(command) = BUILTIN_COMMANDS.iter().find(|command| command.name == name || command.aliases.contains(&name)) {
+            return Some((SlashCommand::Builtin(command.builtin), arguments));
+        }
+        self.custom.iter().find(|command| command.name == name).map(|command| (SlashCommand::Custom(command), arguments))
+    }
+}
+
+/// The project's command directory, `.ikode/commands` in the working directory.
+pub fn project_commands_dir(working_directory: &Path) -> PathBuf {
+    working_directory.join(".ikode").join("commands")
+}
+
+/// The user's command directory, next to the global config file.
+pub fn global_commands_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ikode").join("commands"))
+}
+
+/// Loads every `*.md` file in `dirs`, where a command in a later directory replaces one of
+/// the same name in an earlier one. Files that cannot be used are skipped and described in
+/// the returned errors. The commands are sorted by name.
+pub fn load_custom_commands(dirs: &[PathBuf]) -> (Vec<CustomCommand>, Vec<String>) {
+    let mut commands: Vec<CustomCommand> = Vec::new();
+    let mut errors = Vec::new();
+    for dir in dirs {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "md"))
+                .collect(),
+            Err(_) => continue,
+        };
+        paths.sort();
+
+        for path in paths {
+            match load_file(&path) {
+                Ok(command) => {
+                    commands.retain(|other| other.name != command.name);
+                    commands.push(command);
+                }
+                Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+    }
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    (commands, errors)
+}
+
+fn load_file(path: &Path) -> Result<CustomCommand> {
+    let name = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+    let valid_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_name {
+        return Err(anyhow!("invalid command name '{}': use letters, digits, '_' and '-'", name));
+    }
+    if BUILTIN_COMMANDS.iter().any(|command| command.name == name || command.aliases.contains(&name.as_str())) {
+        return Err(anyhow!("/{} is a built-in command", name));
+    }
+
+    let content = fs::read_to_string(path)?;
+    let mut command = CustomCommand {
+        name,
+        description: None,
+        argument_hint: None,
+        model: None,
+        allowed_tools: None,
+        template: String::new(),
+        source: path.to_path_buf(),
+    };
+    let (frontmatter, template) = split_frontmatter(&content)?;
+    for line in frontmatter.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+        let (key, value) = line.split_once(':').ok_or_else(|| anyhow!("expected `key: value` in the frontmatter, found `{}`", line))?;
+        let value = unquote(value.trim());
+        match key.trim() {
+            "description" => command.description = Some(value.to_string()),
+            "argument-hint" => command.argument_hint = Some(value.to_string()),
+            "model" => command.model = Some(value.to_string()),
+            "allowed-tools" => {
+                let list = value.strip_prefix('[').and_then(|value| value.strip_suffix(']')).unwrap_or(value);
+                command.allowed_tools = Some(list.split(',').map(|tool| unquote(tool.trim()).to_string()).filter(|tool| !tool.is_empty()).collect());
+            }
+            key => return Err(anyhow!("unknown frontmatter key `{}`", key)),
+        }
+    }
+    command.template = template.trim().to_string();
+    if command.template.is_empty() {
+        return Err(anyhow!("the command has no prompt"));
+    }
+    Ok(command)
+}
+
+/// Splits `content` into the frontmatter between two `---` lines at its start, if any, and
+/// the rest.
+fn split_frontmatter(content: &str) -> Result<(&str, &str)> {
+    let Some(rest) = content.strip_prefix("---\n").or_else(|| content.strip_prefix("---\r\n")) else {
+        return Ok(("", content));
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            return Ok((&rest[..offset], &rest[offset + line.len()..]));
+        }
+        offset += line.len();
+    }
+    Err(anyhow!("the frontmatter is not closed with `---`"))
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote).and_then(|value| value.strip_suffix(*quote)))
+        .unwrap_or(value)
+}
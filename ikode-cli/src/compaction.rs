@@ -0,0 +1,218 @@
+use anyhow::{Result, anyhow};
+use gaise_core::contracts::{GaiseContent, GaiseInstructRequest, GaiseMessage, OneOrMany};
+use serde::{Deserialize, Serialize};
+
+/// Number of most recent messages that `/compact` leaves untouched.
+pub const KEEP_RECENT_MESSAGES: usize = 10;
+
+/// Tool results longer than this are shortened in the transcript sent for summarisation.
+const MAX_TOOL_RESULT_CHARS: usize = 2000;
+
+const COMPACTION_PROMPT: &str = "You are compacting the history of a coding session between a user and iKode, a CLI coding agent. \
+The transcript below will be removed from the agent's context and replaced by your summary, so the summary must \
+contain everything the agent needs to continue the work without re-reading the transcript.
+
+Respond with a summary using exactly these sections:
+
+## Goal
+What the user is trying to achieve.
+
+## Files touched
+Every file read, created or edited, with one line on what was done to it.
+
+## Decisions made
+Design decisions, constraints and user preferences that must be respected.
+
+## Commands run
+Relevant commands and their outcome (tests passing or failing, build errors).
+
+## Open todos
+Work that is still pending, including items from the todo list.
+
+## Current state
+Where the work stands right now and the immediate next step.
+
+Be specific (paths, function names, error messages) and concise. Do not add anything that is not in the transcript.";
+
+/// A cached LLM summary that stands in for `history[..covered_until]` (after the kept prefix).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompactionSummary {
+    /// Index into the full history of the first message that is *not* covered by the summary.
+    pub covered_until: usize,
+    /// Number of messages folded into the summary so far.
+    pub compacted_messages: usize,
+    pub summary: String,
+}
+
+impl CompactionSummary {
+    pub fn to_message(&self) -> GaiseMessage {
+        GaiseMessage {
+            role: "system".to_string(),
+            content: Some(OneOrMany::One(GaiseContent::Text {
+                text: format!(
+                    "[Summary of {} earlier messages that were compacted to save context. The conversation continues below.]\n\n{}",
+                    self.compacted_messages, self.summary
+                ),
+            })),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+}
+
+/// Stands in for `dropped` messages that were left out of a request to fit the context budget.
+pub fn truncation_note(dropped: usize) -> GaiseMessage {
+    GaiseMessage {
+        role: "system".to_string(),
+        content: Some(OneOrMany::One(GaiseContent::Text {
+            text: format!(
+                "[Note: {} earlier messages were truncated to save context. The conversation continues below.]",
+                dropped
+            ),
+        })),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+/// The text of a summarisation response, or an error if it is empty.
+pub fn summary_text(output: OneOrMany<GaiseMessage>) -> Result<String> {
+    let messages = match output {
+        OneOrMany::One(m) => vec![m],
+        OneOrMany::Many(ms) => ms,
+    };
+    let summary = messages.iter()
+        .filter_map(|m| m.content.as_ref().map(content_text))
+        .collect::<Vec<_>>()
+        .join("\n");
+    if summary.trim().is_empty() {
+        return Err(anyhow!("The compaction model returned an empty summary"));
+    }
+    Ok(summary.trim().to_string())
+}
+
+/// Moves `index` backwards so that `history[index..]` never starts with a `tool` result
+/// whose assistant tool call would be left behind.
+pub fn tool_safe_start(history: &[GaiseMessage], mut index: usize) -> usize {
+    while index > 0 && index < history.len() && history[index].role == "tool" {
+        index -= 1;
+    }
+    index
+}
+
+/// Moves `index` forwards past any `tool` results, so that a range starting at `index`
+/// does not take tool results away from an assistant message that precedes it.
+pub fn skip_tool_results(history: &[GaiseMessage], mut index: usize) -> usize {
+    while index < history.len() && history[index].role == "tool" {
+        index += 1;
+    }
+    index
+}
+
+/// Rough token estimate (about four characters per token) used before provider usage data is available.
+pub fn estimate_tokens(messages: &[GaiseMessage]) -> usize {
+    messages.iter().map(estimate_message_tokens).sum()
+}
+
+pub fn estimate_message_tokens(message: &GaiseMessage) -> usize {
+    let mut chars = message.role.len();
+    if let Some(content) = &message.content {
+        chars += content_text(content).len();
+    }
+    if let Some(tool_calls) = &message.tool_calls {
+        for tc in tool_calls {
+            chars += tc.function.name.len() + tc.function.arguments.as_deref().map_or(0, str::len);
+        }
+    }
+    chars / 4 + 4
+}
+
+pub fn content_text(content: &OneOrMany<GaiseContent>) -> String {
+    fn part_text(part: &GaiseContent, out: &mut String) {
+        match part {
+            GaiseContent::Text { text } => out.push_str(text),
+            GaiseContent::Parts { parts } => parts.iter().for_each(|p| part_text(p, out)),
+            _ => {}
+        }
+    }
+
+    let mut out = String::new();
+    match content {
+        OneOrMany::One(part) => part_text(part, &mut out),
+        OneOrMany::Many(parts) => parts.iter().for_each(|p| part_text(p, &mut out)),
+    }
+    out
+}
+
+/// Renders messages as a plain-text transcript for the summarisation model.
+pub fn render_transcript(messages: &[GaiseMessage]) -> String {
+    let mut transcript = String::new();
+    for message in messages {
+        let text = message.content.as_ref().map(content_text).unwrap_or_default();
+        match message.role.as_str() {
+            "tool" => {
+                let text = if text.chars().count() > MAX_TOOL_RESULT_CHARS {
+                    let head: String = text.chars().take(MAX_TOOL_RESULT_CHARS).collect();
+                    format!("{}\n... (tool result shortened)", head)
+                } else {
+                    text
+                };
+                transcript.push_str(&format!("[tool result]\n{}\n\n", text));
+            }
+            role => {
+                if !text.is_empty() {
+                    transcript.push_str(&format!("[{}]\n{}\n\n", role, text));
+                }
+                if let Some(tool_calls) = &message.tool_calls {
+                    for tc in tool_calls {
+                        transcript.push_str(&format!(
+                            "[tool call] {}({})\n\n",
+                            tc.function.name,
+                            tc.function.arguments.as_deref().unwrap_or("")
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    transcript
+}
+
+/// Builds the request asking `model` to summarise `messages`. A previous summary, if any, is
+/// folded into the new one so repeated compaction keeps the whole session's context.
+pub fn build_compaction_request(
+    model: &str,
+    previous_summary: Option<&str>,
+    messages: &[GaiseMessage],
+    todos: &str,
+) -> GaiseInstructRequest {
+    let mut input = String::new();
+    if let Some(previous) = previous_summary {
+        input.push_str("Summary of the session before this transcript:\n\n");
+        input.push_str(previous);
+        input.push_str("\n\n");
+    }
+    input.push_str("Transcript to compact:\n\n");
+    input.push_str(&render_transcript(messages));
+    input.push_str("Current todo list:\n");
+    input.push_str(if todos.is_empty() { "No tasks." } else { todos });
+
+    GaiseInstructRequest {
+        model: model.to_string(),
+        input: OneOrMany::Many(vec![
+            GaiseMessage {
+                role: "system".to_string(),
+                content: Some(OneOrMany::One(GaiseContent::Text { text: COMPACTION_PROMPT.to_string() })),
+                tool_calls: None,
+                tool_call_id: None,
+            },
+            GaiseMessage {
+                role: "user".to_string(),
+                content: Some(OneOrMany::One(GaiseContent::Text { text: input })),
+                tool_calls: None,
+                tool_call_id: None,
+            },
+        ]),
+        ..Default::default()
+    }
+}
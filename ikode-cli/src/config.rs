@@ -0,0 +1,336 @@
+use crate::exec::{self, SandboxMode};
+use crate::hooks::HookConfig;
+use crate::mcp::McpServerConfig;
+use crate::paths;
+use crate::permissions::{Decision, PermissionRule};
+use anyhow::{Result, anyhow};
+use gaise_core::contracts::GaiseGenerationConfig;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Top-level keys of an ikode config file that configure gaise-client (providers, endpoints,
+/// aliases, ...) rather than ikode itself. They use the layout of a gaise config file.
+pub const GAISE_KEYS: &[&str] = &["providers", "endpoints", "aliases", "fallback_chains", "retry"];
+
+/// Gaise sections a project file may only set once the user trusts it: they decide where
+/// requests, and the API keys from the user's environment, are sent.
+pub const TRUSTED_GAISE_KEYS: &[&str] = &["providers", "endpoints"];
+
+/// The ikode settings of one config file. Anything left out falls through to the
+/// next layer: global config, then project config, then command line flags.
+///
+/// ```toml
+/// model = "anthropic::claude-sonnet-4-5"
+/// max_history = 120
+/// guides = ["docs/STYLE.md"]
+///
+/// [models."openai::gpt-5"]
+/// thinking_effort = "low"
+///
+/// [[permissions]]
+/// tool = "execute_command"
+/// command = "cargo test*"
+/// action = "allow"
+///
+/// [mcp_servers.github]
+/// command = "npx"
+/// args = ["-y", "@modelcontextprotocol/server-github"]
+///
+/// [providers.anthropic]
+/// api_key = "${ANTHROPIC_API_KEY}"
+/// ```
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub model: Option<String>,
+    pub fallback_models: Option<Vec<String>>,
+    pub compact_model: Option<String>,
+    /// Model for sub-agents that do not name one.
+    pub agent_model: Option<String>,
+    pub brave: Option<bool>,
+    pub max_history: Option<usize>,
+    pub prefix_keep: Option<usize>,
+    pub max_context_tokens: Option<usize>,
+    pub auto_compact_tokens: Option<usize>,
+    pub max_cost: Option<f64>,
+    pub status_line: Option<bool>,
+    pub stream: Option<bool>,
+    /// Seconds before `execute_command` kills a command the model gave no timeout for.
+    pub command_timeout: Option<u64>,
+    /// Bytes of stdout and of stderr kept from each command.
+    pub command_output_limit: Option<usize>,
+    pub sandbox: Option<SandboxMode>,
+    /// Extra guideline files appended to the system prompt, relative to the working directory.
+    #[serde(default)]
+    pub guides: Vec<String>,
+    /// Generation settings per model, e.g. `[models."openai::gpt-5"]`.
+    #[serde(default)]
+    pub models: HashMap<String, GaiseGenerationConfig>,
+    /// Allow/ask/deny rules for tool calls, see `PermissionRule`.
+    #[serde(default)]
+    pub permissions: Vec<PermissionRule>,
+    /// MCP servers to launch, by name, see `McpServerConfig`.
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, McpServerConfig>,
+    /// Commands run on tool calls, prompts and session end, see `HookConfig`.
+    #[serde(default)]
+    pub hooks: Vec<HookConfig>,
+}
+
+/// Where the effective value of a setting came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    File(PathBuf),
+    Flag,
+    /// Changed during the session with a slash command or by resuming a saved session.
+    Command,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::File(path) => write!(f, "{}", path.display()),
+            Self::Flag => f.write_str("command line"),
+            Self::Command => f.write_str("this session"),
+        }
+    }
+}
+
+/// The config files found for a project, in the order they apply.
+#[derive(Debug, Clone, Default)]
+pub struct IkodeConfig {
+    pub layers: Vec<(PathBuf, ConfigFile)>,
+    /// The gaise sections of every file, in the same order.
+    pub gaise_layers: Vec<serde_json::Value>,
+    /// Position of the project file in `layers`, and its content for `TrustStore`.
+    pub project: Option<(usize, String)>,
+}
+
+impl IkodeConfig {
+    /// Loads `~/.config/ikode/config.toml`, then `.ikode/config.toml` in `working_directory`.
+    pub fn load(working_directory: &Path) -> Result<Self> {
+        let mut config = Self::load_files(&global_config_path().into_iter().collect::<Vec<_>>())?;
+        config.add_project_file(&project_config_path(working_directory))?;
+        Ok(config)
+    }
+
+    /// Adds the project config file at `path` as the last layer, if it exists. `${NAME}` in
+    /// it is kept as written rather than read from the environment, so a checked-out
+    /// repository cannot copy the user's secrets into its settings.
+    pub fn add_project_file(&mut self, path: &Path) -> Result<()> {
+        if !path.exists() {
+            return Ok(());
+        }
+        let content = read_file(path)?;
+        let (file, gaise) = parse_file(path, &content)?;
+        self.project = Some((self.layers.len(), content));
+        self.layers.push((path.to_path_buf(), file));
+        self.gaise_layers.push(gaise_client::escape_variables(gaise));
+        Ok(())
+    }
+
+    /// Loads the given files in order; files that do not exist are skipped.
+    pub fn load_files(paths: &[PathBuf]) -> Result<Self> {
+        let mut config = Self::default();
+        for path in paths.iter().filter(|p| p.exists()) {
+            let (file, gaise) = parse_file(path, &read_file(path)?)?;
+            config.layers.push((path.clone(), file));
+            config.gaise_layers.push(gaise);
+        }
+        Ok(config)
+    }
+
+    /// Settings of the project file that only apply once the user trusts it: provider
+    /// settings, MCP servers and hooks, which run commands on their own, anything that lets
+    /// tool calls through without asking, and limits it loosens (see `loosened_limits`).
+    /// Ask and deny rules, and limits it tightens, always apply.
+    pub fn settings_needing_trust(&self) -> Vec<&'static str> {
+        let Some((index, _)) = self.project else {
+            return Vec::new();
+        };
+        let (_, file) = &self.layers[index];
+        let mut settings: Vec<&'static str> = TRUSTED_GAISE_KEYS
+            .iter()
+            .copied()
+            .filter(|key| self.gaise_layers[index].get(key).is_some())
+            .collect();
+        if !file.mcp_servers.is_empty() {
+            settings.push("mcp_servers");
+        }
+        if !file.hooks.is_empty() {
+            settings.push("hooks");
+        }
+        if file.brave == Some(true) {
+            settings.push("brave");
+        }
+        if file.permissions.iter().any(|rule| rule.action == Decision::Allow) {
+            settings.push("allow rules");
+        }
+        settings.extend(self.loosened_limits(index));
+        settings
+    }
+
+    /// The limits the layer at `index` loosens compared to the layers before it, or the
+    /// defaults: a weaker `sandbox`, a longer `command_timeout`, or a higher or no `max_cost`.
+    fn loosened_limits(&self, index: usize) -> Vec<&'static str> {
+        fn before<T>(layers: &[(PathBuf, ConfigFile)], pick: impl Fn(&ConfigFile) -> Option<T>) -> Option<T> {
+            layers.iter().rev().find_map(|(_, file)| pick(file))
+        }
+
+        let (earlier, file) = (&self.layers[..index], &self.layers[index].1);
+        let mut limits = Vec::new();
+        if file.sandbox.is_some_and(|sandbox| sandbox < before(earlier, |f| f.sandbox).unwrap_or_default()) {
+            limits.push("sandbox");
+        }
+        let timeout = before(earlier, |f| f.command_timeout).unwrap_or(exec::DEFAULT_TIMEOUT.as_secs());
+        if file.command_timeout.is_some_and(|seconds| seconds > timeout) {
+            limits.push("command_timeout");
+        }
+        // A limit of 0 means none.
+        if let (Some(limit), Some(earlier_limit)) = (file.max_cost, before(earlier, |f| f.max_cost).filter(|limit| *limit > 0.0)) {
+            if limit <= 0.0 || limit > earlier_limit {
+                limits.push("max_cost");
+            }
+        }
+        limits
+    }
+
+    /// Removes the settings `settings_needing_trust` lists from the project layer.
+    pub fn drop_untrusted_settings(&mut self) {
+        let Some((index, _)) = self.project else {
+            return;
+        };
+        if let Some(gaise) = self.gaise_layers[index].as_object_mut() {
+            for key in TRUSTED_GAISE_KEYS {
+                gaise.remove(*key);
+            }
+        }
+        let loosened = self.loosened_limits(index);
+        let (_, file) = &mut self.layers[index];
+        if loosened.contains(&"sandbox") {
+            file.sandbox = None;
+        }
+        if loosened.contains(&"command_timeout") {
+            file.command_timeout = None;
+        }
+        if loosened.contains(&"max_cost") {
+            file.max_cost = None;
+        }
+        file.mcp_servers.clear();
+        file.hooks.clear();
+        if file.brave == Some(true) {
+            file.brave = None;
+        }
+        file.permissions.retain(|rule| rule.action != Decision::Allow);
+    }
+
+    /// Resolves a setting: the command line `flag` if given, otherwise the value of the last
+    /// file that sets it, otherwise `default`.
+    pub fn resolve<T>(&self, flag: Option<T>, pick: impl Fn(&ConfigFile) -> Option<T>, default: T) -> (T, ConfigSource) {
+        if let Some(value) = flag {
+            return (value, ConfigSource::Flag);
+        }
+        self.layers
+            .iter()
+            .rev()
+            .find_map(|(path, file)| pick(file).map(|value| (value, ConfigSource::File(path.clone()))))
+            .unwrap_or((default, ConfigSource::Default))
+    }
+
+    /// Like `resolve`, for a setting that is unset by default.
+    pub fn resolve_option<T>(&self, flag: Option<T>, pick: impl Fn(&ConfigFile) -> Option<T>) -> (Option<T>, ConfigSource) {
+        self.resolve(flag.map(Some), |file| pick(file).map(Some), None)
+    }
+
+    /// Guideline files from every layer, with the file that lists them.
+    pub fn guides(&self) -> Vec<(String, ConfigSource)> {
+        self.layers
+            .iter()
+            .flat_map(|(path, file)| file.guides.iter().map(|guide| (guide.clone(), ConfigSource::File(path.clone()))))
+            .collect()
+    }
+
+    /// Where to read `guide`, listed in `source`. The project file comes with the repository,
+    /// so its guides must lie inside `working_directory` (canonical); otherwise a cloned
+    /// repository could put e.g. `~/.ssh/id_rsa` into the system prompt sent to the provider.
+    pub fn guide_path(&self, guide: &str, source: &ConfigSource, working_directory: &Path) -> Result<PathBuf> {
+        let from_project = match (source, &self.project) {
+            (ConfigSource::File(path), Some((index, _))) => *path == self.layers[*index].0,
+            _ => false,
+        };
+        if from_project {
+            paths::validate_path(working_directory, guide)
+        } else {
+            Ok(PathBuf::from(guide))
+        }
+    }
+
+    /// Permission rules from every layer, global ones first.
+    pub fn permission_rules(&self) -> Vec<PermissionRule> {
+        self.layers.iter().flat_map(|(_, file)| file.permissions.iter().cloned()).collect()
+    }
+
+    /// Hooks from every layer, global ones first.
+    pub fn hooks(&self) -> Vec<HookConfig> {
+        self.layers.iter().flat_map(|(_, file)| file.hooks.iter().cloned()).collect()
+    }
+
+    /// MCP servers from every layer; a later file replaces a server of the same name.
+    pub fn mcp_servers(&self) -> BTreeMap<String, McpServerConfig> {
+        self.layers.iter().flat_map(|(_, file)| file.mcp_servers.clone()).collect()
+    }
+
+    /// Generation settings for `model`; a later file overrides single settings of an earlier one.
+    pub fn generation_config(&self, model: &str) -> Option<GaiseGenerationConfig> {
+        self.layers.iter().filter_map(|(_, file)| file.models.get(model)).fold(None, |merged, layer| {
+            Some(match merged {
+                Some(earlier) => layer.clone().with_defaults(&earlier),
+                None => layer.clone(),
+            })
+        })
+    }
+
+    /// Models with generation settings in any layer.
+    pub fn configured_models(&self) -> Vec<String> {
+        let mut models: Vec<String> = self.layers.iter().flat_map(|(_, file)| file.models.keys().cloned()).collect();
+        models.sort();
+        models.dedup();
+        models
+    }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| anyhow!("Could not read config file {}: {}", path.display(), e))
+}
+
+/// Splits a config file into its ikode settings and its gaise sections.
+fn parse_file(path: &Path, content: &str) -> Result<(ConfigFile, serde_json::Value)> {
+    let mut table: toml::Table = toml::from_str(content)
+        .map_err(|e| anyhow!("Could not parse config file {}: {}", path.display(), e))?;
+
+    let mut gaise = serde_json::Map::new();
+    for key in GAISE_KEYS {
+        if let Some(value) = table.remove(*key) {
+            gaise.insert(key.to_string(), serde_json::to_value(value)?);
+        }
+    }
+    let file: ConfigFile = toml::Value::Table(table)
+        .try_into()
+        .map_err(|e| anyhow!("Invalid config file {}: {}", path.display(), e))?;
+    Ok((file, serde_json::Value::Object(gaise)))
+}
+
+/// The user's config file, e.g. `~/.config/ikode/config.toml`.
+pub fn global_config_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ikode").join("config.toml"))
+}
+
+/// The project's config file, `.ikode/config.toml` in the working directory.
+pub fn project_config_path(working_directory: &Path) -> PathBuf {
+    working_directory.join(".ikode").join("config.toml")
+}
@@ -0,0 +1,92 @@
+/// Context window assumed for models missing from `CONTEXT_WINDOWS`.
+pub const DEFAULT_CONTEXT_WINDOW: usize = 32_768;
+
+/// Known context windows in tokens, matched against the model part of `provider::model`.
+/// The longest matching key wins, so `gpt-4o` is not mistaken for `gpt-4`.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    // OpenAI
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4-mini", 200_000),
+    // Anthropic (direct and Bedrock ids such as `anthropic.claude-3-5-sonnet-...`)
+    ("claude", 200_000),
+    // Google
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini", 1_048_576),
+    // Bedrock
+    ("amazon.nova", 300_000),
+    ("amazon.nova-micro", 128_000),
+    ("meta.llama3-1", 128_000),
+    ("mistral.mistral-large", 128_000),
+    // Ollama
+    ("llama3.1", 131_072),
+    ("llama3.2", 131_072),
+    ("llama3.3", 131_072),
+    ("llama3", 8_192),
+    ("qwen2.5", 32_768),
+    ("qwen3", 40_960),
+    ("mistral", 32_768),
+    ("deepseek-r1", 131_072),
+    ("gpt-oss", 131_072),
+];
+
+/// Returns the context window of `model` (`provider::model` or a bare model name), if known.
+pub fn context_window(model: &str) -> Option<usize> {
+    let name = model.split_once("::").map_or(model, |(_, name)| name).to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .filter(|(key, _)| name.contains(key))
+        .max_by_key(|(key, _)| key.len())
+        .map(|(_, window)| *window)
+}
+
+/// Scales the character based estimate so it tracks the prompt sizes the provider actually
+/// reports, including overhead the estimate cannot see such as tool definitions.
+#[derive(Debug, Clone)]
+pub struct TokenCalibration {
+    ratio: f64,
+}
+
+impl Default for TokenCalibration {
+    fn default() -> Self {
+        Self { ratio: 1.0 }
+    }
+}
+
+impl TokenCalibration {
+    /// Records that a request estimated at `estimated` tokens was billed as `actual` prompt tokens.
+    pub fn observe(&mut self, estimated: usize, actual: usize) {
+        if estimated == 0 || actual == 0 {
+            return;
+        }
+        self.ratio = (actual as f64 / estimated as f64).clamp(0.5, 4.0);
+    }
+
+    pub fn apply(&self, estimated: usize) -> usize {
+        (estimated as f64 * self.ratio).ceil() as usize
+    }
+}
+
+/// Tokens available for the prompt when `budget` is the whole context: a tenth of it is left
+/// free for the model's response.
+pub fn prompt_budget(budget: usize) -> usize {
+    budget - budget / 10
+}
+
+/// Returns the first index of `message_tokens` to keep so that the kept messages, plus
+/// `fixed_tokens` for everything sent before them, fit in `budget`. Never goes past `limit`.
+pub fn fit_to_budget(message_tokens: &[usize], fixed_tokens: usize, budget: usize, limit: usize) -> usize {
+    let mut total: usize = fixed_tokens + message_tokens.iter().sum::<usize>();
+    let mut start = 0;
+    while total > budget && start < limit.min(message_tokens.len()) {
+        total -= message_tokens[start];
+        start += 1;
+    }
+    start
+}
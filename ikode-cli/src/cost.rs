@@ -0,0 +1,165 @@
+use anyhow::{Result, anyhow};
+use gaise_core::contracts::GaiseUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Token counts for one or more requests. `input` includes cached prompt tokens and
+/// `output` includes reasoning tokens; `cache_read`, `cache_write` and `reasoning` break them down.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct TokenUsage {
+    pub input: usize,
+    pub output: usize,
+    pub cache_read: usize,
+    pub cache_write: usize,
+    pub reasoning: usize,
+}
+
+impl TokenUsage {
+    pub fn from_usage(usage: &GaiseUsage) -> Self {
+        Self {
+            input: usage.input_tokens.unwrap_or(0),
+            output: usage.output_tokens.unwrap_or(0),
+            cache_read: usage.cache_read_tokens.unwrap_or(0),
+            cache_write: usage.cache_write_tokens.unwrap_or(0),
+            reasoning: usage.reasoning_tokens.unwrap_or(0),
+        }
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input += other.input;
+        self.output += other.output;
+        self.cache_read += other.cache_read;
+        self.cache_write += other.cache_write;
+        self.reasoning += other.reasoning;
+    }
+}
+
+/// Prices in USD per million tokens.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Price {
+    pub input: f64,
+    pub output: f64,
+    /// Price of prompt tokens served from the cache; defaults to `input`.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+    /// Price of prompt tokens written to the cache; defaults to `input`.
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl Price {
+    const fn new(input: f64, output: f64, cached_input: Option<f64>, cache_write: Option<f64>) -> Self {
+        Self { input, output, cached_input, cache_write }
+    }
+
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let uncached = usage.input.saturating_sub(usage.cache_read + usage.cache_write);
+        (uncached as f64 * self.input
+            + usage.cache_read as f64 * self.cached_input.unwrap_or(self.input)
+            + usage.cache_write as f64 * self.cache_write.unwrap_or(self.input)
+            + usage.output as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// List prices at the time of writing. They go stale; `prices.toml` overrides them.
+const DEFAULT_PRICES: &[(&str, Price)] = &[
+    ("openai::gpt-5", Price::new(1.25, 10.0, Some(0.125), None)),
+    ("openai::gpt-5-mini", Price::new(0.25, 2.0, Some(0.025), None)),
+    ("openai::gpt-5-nano", Price::new(0.05, 0.40, Some(0.005), None)),
+    ("openai::gpt-4.1", Price::new(2.0, 8.0, Some(0.50), None)),
+    ("openai::gpt-4.1-mini", Price::new(0.40, 1.60, Some(0.10), None)),
+    ("openai::gpt-4.1-nano", Price::new(0.10, 0.40, Some(0.025), None)),
+    ("openai::gpt-4o", Price::new(2.50, 10.0, Some(1.25), None)),
+    ("openai::gpt-4o-mini", Price::new(0.15, 0.60, Some(0.075), None)),
+    ("openai::o3", Price::new(2.0, 8.0, Some(0.50), None)),
+    ("openai::o4-mini", Price::new(1.10, 4.40, Some(0.275), None)),
+    ("anthropic::claude-opus-4", Price::new(15.0, 75.0, Some(1.50), Some(18.75))),
+    ("anthropic::claude-sonnet-4", Price::new(3.0, 15.0, Some(0.30), Some(3.75))),
+    ("anthropic::claude-3-7-sonnet", Price::new(3.0, 15.0, Some(0.30), Some(3.75))),
+    ("anthropic::claude-3-5-sonnet", Price::new(3.0, 15.0, Some(0.30), Some(3.75))),
+    ("anthropic::claude-3-5-haiku", Price::new(0.80, 4.0, Some(0.08), Some(1.0))),
+    ("vertexai::gemini-2.5-pro", Price::new(1.25, 10.0, Some(0.31), None)),
+    ("vertexai::gemini-2.5-flash", Price::new(0.30, 2.50, Some(0.075), None)),
+    ("vertexai::gemini-2.0-flash", Price::new(0.10, 0.40, Some(0.025), None)),
+    ("ollama::", Price::new(0.0, 0.0, None, None)),
+];
+
+/// Prices keyed by `provider::model`. A key also matches models it is a prefix of
+/// (`openai::gpt-4o` prices `openai::gpt-4o-2024-08-06`); the longest matching key wins.
+#[derive(Debug, Clone)]
+pub struct PriceTable {
+    prices: HashMap<String, Price>,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self { prices: DEFAULT_PRICES.iter().map(|(k, p)| (k.to_string(), *p)).collect() }
+    }
+}
+
+impl PriceTable {
+    /// Built-in prices overridden by the entries of a TOML file such as:
+    ///
+    /// ```toml
+    /// ["openai::gpt-4o"]
+    /// input = 2.5
+    /// output = 10.0
+    /// cached_input = 1.25
+    /// ```
+    pub fn with_overrides(path: &Path) -> Result<Self> {
+        let mut table = Self::default();
+        if !path.exists() {
+            return Ok(table);
+        }
+        let content = fs::read_to_string(path)?;
+        let overrides: HashMap<String, Price> = toml::from_str(&content)
+            .map_err(|e| anyhow!("Could not parse price table {}: {}", path.display(), e))?;
+        table.prices.extend(overrides);
+        Ok(table)
+    }
+
+    pub fn price(&self, model: &str) -> Option<Price> {
+        self.prices
+            .iter()
+            .filter(|(key, _)| model.starts_with(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, price)| *price)
+    }
+}
+
+/// Totals over a number of requests. Requests to models without a price still count
+/// their tokens but are reported as unpriced.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct UsageTotals {
+    pub requests: usize,
+    pub tokens: TokenUsage,
+    pub cost: f64,
+    pub unpriced_requests: usize,
+}
+
+impl UsageTotals {
+    pub fn record(&mut self, tokens: &TokenUsage, cost: Option<f64>) {
+        self.requests += 1;
+        self.tokens.add(tokens);
+        match cost {
+            Some(cost) => self.cost += cost,
+            None => self.unpriced_requests += 1,
+        }
+    }
+}
+
+/// Formats a token count compactly, e.g. `950`, `12.3k`, `1.2M`.
+pub fn format_tokens(tokens: usize) -> String {
+    match tokens {
+        0..=999 => tokens.to_string(),
+        1_000..=999_999 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+pub fn format_cost(cost: f64) -> String {
+    if cost < 0.01 { format!("${:.4}", cost) } else { format!("${:.2}", cost) }
+}
@@ -0,0 +1,167 @@
+use crate::exec::{self, CommandOutput, ExecOptions};
+use crate::tools::{get_tools, parameter_from_schema};
+use anyhow::{Result, anyhow};
+use gaise_core::contracts::GaiseTool;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Environment variable holding all arguments as one JSON object.
+pub const ARGS_ENV: &str = "IKODE_ARGS";
+/// Prefix of the environment variable holding each argument, e.g. `IKODE_ARG_TARGET`.
+pub const ARG_ENV_PREFIX: &str = "IKODE_ARG_";
+
+/// How a custom tool receives its arguments. Either way they never become part of the
+/// command line, so the model cannot inject shell syntax through them.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ArgumentInput {
+    /// `IKODE_ARG_<NAME>` for each argument, plus `IKODE_ARGS` with all of them as JSON.
+    #[default]
+    Env,
+    /// All arguments as one JSON object on stdin.
+    Stdin,
+}
+
+/// A project-specific tool backed by a shell command, declared in `.ikode/tools/<file>.toml`:
+///
+/// ```toml
+/// name = "run_migrations"
+/// description = "Runs the database migrations up or down to a version."
+/// command = "./scripts/migrate.sh \"$IKODE_ARG_DIRECTION\" \"$IKODE_ARG_VERSION\""
+/// timeout = 300
+///
+/// [parameters]
+/// type = "object"
+/// required = ["direction"]
+/// properties.direction = { type = "string", enum = ["up", "down"] }
+/// properties.version = { type = "integer", description = "Target version" }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CustomTool {
+    pub name: String,
+    pub description: String,
+    /// Run by the shell exactly as written, in the working directory.
+    pub command: String,
+    /// JSON Schema of the arguments, an object schema.
+    #[serde(default = "no_parameters")]
+    pub parameters: Value,
+    #[serde(default)]
+    pub input: ArgumentInput,
+    /// Seconds before the command is killed, instead of the `execute_command` default.
+    pub timeout: Option<u64>,
+    /// The file the tool was declared in.
+    #[serde(skip)]
+    pub source: PathBuf,
+    /// The content of that file, for `TrustStore`.
+    #[serde(skip)]
+    pub content: String,
+}
+
+fn no_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+impl CustomTool {
+    pub fn definition(&self) -> GaiseTool {
+        GaiseTool {
+            name: self.name.clone(),
+            description: Some(self.description.clone()),
+            parameters: Some(parameter_from_schema(&self.parameters)),
+        }
+    }
+
+    /// Runs the command with `arguments` under the limits of `options`.
+    pub async fn run(&self, arguments: &Value, options: &ExecOptions) -> Result<CommandOutput> {
+        let mut options = options.clone();
+        if let Some(seconds) = self.timeout {
+            options.timeout = Duration::from_secs(seconds.max(1)).min(exec::MAX_TIMEOUT);
+        }
+        match self.input {
+            ArgumentInput::Env => {
+                options.env.extend(argument_env(arguments));
+                exec::run_command(&self.command, &options).await
+            }
+            ArgumentInput::Stdin => {
+                let input = serde_json::to_vec(arguments)?;
+                exec::run_command_with_input(&self.command, &options, Some(input)).await
+            }
+        }
+    }
+}
+
+/// The environment variables for `arguments`: one per argument, named after it in upper
+/// case, with strings as they are and other values as JSON, then all of them as JSON.
+pub fn argument_env(arguments: &Value) -> Vec<(String, String)> {
+    let mut env = Vec::new();
+    for (name, value) in arguments.as_object().into_iter().flatten() {
+        let value = match value {
+            Value::Null => continue,
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' }).collect();
+        env.push((format!("{}{}", ARG_ENV_PREFIX, name), value));
+    }
+    env.push((ARGS_ENV.to_string(), arguments.to_string()));
+    env
+}
+
+/// The project's tool directory, `.ikode/tools` in the working directory.
+pub fn tools_dir(working_directory: &Path) -> PathBuf {
+    working_directory.join(".ikode").join("tools")
+}
+
+/// Loads every `*.toml` file in `dir`, in file name order. Files that cannot be used are
+/// skipped and described in the returned errors, so one broken file does not hide the rest.
+pub fn load_custom_tools(dir: &Path) -> (Vec<CustomTool>, Vec<String>) {
+    let mut paths: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect(),
+        Err(_) => return (Vec::new(), Vec::new()),
+    };
+    paths.sort();
+
+    let builtin: Vec<String> = get_tools().into_iter().map(|tool| tool.name).collect();
+    let mut tools: Vec<CustomTool> = Vec::new();
+    let mut errors = Vec::new();
+    for path in paths {
+        let tool = load_file(&path).and_then(|tool| {
+            if builtin.contains(&tool.name) || tools.iter().any(|other| other.name == tool.name) {
+                return Err(anyhow!("a tool named '{}' already exists", tool.name));
+            }
+            Ok(tool)
+        });
+        match tool {
+            Ok(tool) => tools.push(tool),
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    (tools, errors)
+}
+
+fn load_file(path: &Path) -> Result<CustomTool> {
+    let content = fs::read_to_string(path)?;
+    let mut tool: CustomTool = toml::from_str(&content)?;
+    tool.source = path.to_path_buf();
+    tool.content = content;
+
+    let valid_name = !tool.name.is_empty()
+        && tool.name.len() <= 64
+        && tool.name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid_name {
+        return Err(anyhow!("invalid tool name '{}': use up to 64 letters, digits, '_' and '-'", tool.name));
+    }
+    if tool.name.starts_with(crate::mcp::TOOL_PREFIX) {
+        return Err(anyhow!("tool names starting with '{}' are reserved for MCP servers", crate::mcp::TOOL_PREFIX));
+    }
+    if tool.parameters.get("type").and_then(Value::as_str) != Some("object") {
+        return Err(anyhow!("parameters must be a JSON Schema with type = \"object\""));
+    }
+    Ok(tool)
+}
@@ -0,0 +1,44 @@
+use colored::*;
+use similar::TextDiff;
+
+/// Lines of a preview shown before the rest is summarised.
+pub const MAX_PREVIEW_LINES: usize = 400;
+
+/// A unified diff from `old` to `new` for the file `path`, with `--- /dev/null` for a file
+/// that does not exist yet. Empty when nothing changes.
+pub fn unified_diff(path: &str, old: Option<&str>, new: &str) -> String {
+    let old_header = if old.is_some() { format!("a/{}", path) } else { "/dev/null".to_string() };
+    TextDiff::from_lines(old.unwrap_or_default(), new)
+        .unified_diff()
+        .context_radius(3)
+        .missing_newline_hint(true)
+        .header(&old_header, &format!("b/{}", path))
+        .to_string()
+}
+
+/// Colours a unified diff for the terminal: removals red, additions green, hunk headers
+/// cyan. Long diffs are cut after `MAX_PREVIEW_LINES` lines.
+pub fn colorize(diff: &str) -> String {
+    let lines: Vec<&str> = diff.lines().collect();
+    let mut output: Vec<String> = lines
+        .iter()
+        .take(MAX_PREVIEW_LINES)
+        .map(|line| {
+            if line.starts_with("+++") || line.starts_with("---") {
+                line.bold().to_string()
+            } else if line.starts_with("@@") {
+                line.cyan().to_string()
+            } else if line.starts_with('+') {
+                line.green().to_string()
+            } else if line.starts_with('-') {
+                line.red().to_string()
+            } else {
+                line.dimmed().to_string()
+            }
+        })
+        .collect();
+    if lines.len() > MAX_PREVIEW_LINES {
+        output.push(format!("... {} more lines", lines.len() - MAX_PREVIEW_LINES).dimmed().to_string());
+    }
+    output.join("\n")
+}
@@ -0,0 +1,400 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::{Child, Command};
+
+/// Timeout for a command when neither the model nor the config sets one.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Upper bound for the timeout the model may ask for.
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+/// Bytes kept of each of stdout and stderr, half from the start and half from the end.
+pub const DEFAULT_OUTPUT_LIMIT: usize = 32 * 1024;
+/// How long to wait for the output pipes to close once the command has been killed.
+const KILL_GRACE: Duration = Duration::from_secs(2);
+/// How long to keep reading output once the command has exited, before killing what it
+/// left running in the background with the pipes still open.
+const EXIT_GRACE: Duration = Duration::from_millis(300);
+
+/// Environment variables passed into the sandbox; everything else is cleared, including
+/// the provider API keys ikode itself runs with.
+pub const SANDBOX_ENV: &[&str] = &["PATH", "HOME", "USER", "LANG", "LC_ALL", "TERM", "TZ", "CARGO_HOME", "RUSTUP_HOME"];
+
+/// How commands are isolated from the rest of the machine, ordered from the least isolated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SandboxMode {
+    /// Run commands directly with ikode's environment.
+    #[default]
+    Off,
+    /// Run commands under `bwrap` (Linux): the filesystem is read-only except for the working
+    /// directory and a private `/tmp`, there is no network and the environment is cleared.
+    Bubblewrap,
+}
+
+impl fmt::Display for SandboxMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Off => "off",
+            Self::Bubblewrap => "bubblewrap",
+        })
+    }
+}
+
+impl FromStr for SandboxMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "bubblewrap" | "bwrap" => Ok(Self::Bubblewrap),
+            _ => Err(format!("unknown sandbox '{}', expected 'off' or 'bubblewrap'", s)),
+        }
+    }
+}
+
+/// Fails if `mode` cannot be used on this machine, so that a configured sandbox is never
+/// silently skipped.
+pub fn check_sandbox(mode: SandboxMode) -> Result<()> {
+    match mode {
+        SandboxMode::Off => Ok(()),
+        SandboxMode::Bubblewrap if !cfg!(target_os = "linux") => {
+            Err(anyhow!("The bubblewrap sandbox is only available on Linux"))
+        }
+        SandboxMode::Bubblewrap => find_in_path("bwrap")
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("sandbox = \"bubblewrap\" needs the bwrap executable on PATH")),
+    }
+}
+
+fn find_in_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).map(|dir| dir.join(program)).find(|candidate| candidate.is_file())
+}
+
+/// The `bwrap` arguments that confine a command to `working_directory`, up to the
+/// command itself.
+pub fn sandbox_args(working_directory: &Path) -> Vec<String> {
+    let dir = working_directory.display().to_string();
+    let mut args: Vec<String> = [
+        "--ro-bind", "/", "/",
+        "--dev", "/dev",
+        "--proc", "/proc",
+        "--tmpfs", "/tmp",
+        "--bind", &dir, &dir,
+        "--chdir", &dir,
+        "--unshare-net",
+        "--unshare-pid",
+        "--die-with-parent",
+        "--clearenv",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+    for name in SANDBOX_ENV {
+        if let Ok(value) = std::env::var(name) {
+            args.extend(["--setenv".to_string(), name.to_string(), value]);
+        }
+    }
+    args
+}
+
+/// Process groups of running commands, shared with the Ctrl-C handler so that it can kill
+/// them before ikode exits: they are not in ikode's process group, so the terminal's
+/// SIGINT never reaches them.
+pub type ProcessGroups = Arc<Mutex<HashSet<u32>>>;
+
+/// How to run a command.
+#[derive(Debug, Clone)]
+pub struct ExecOptions {
+    pub working_directory: PathBuf,
+    pub timeout: Duration,
+    pub output_limit: usize,
+    pub sandbox: SandboxMode,
+    /// Extra environment variables, set inside the sandbox too.
+    pub env: Vec<(String, String)>,
+    /// Where commands register their process group while they run; clones share it.
+    pub process_groups: ProcessGroups,
+}
+
+impl ExecOptions {
+    pub fn new(working_directory: PathBuf) -> Self {
+        Self {
+            working_directory,
+            timeout: DEFAULT_TIMEOUT,
+            output_limit: DEFAULT_OUTPUT_LIMIT,
+            sandbox: SandboxMode::Off,
+            env: Vec::new(),
+            process_groups: ProcessGroups::default(),
+        }
+    }
+}
+
+/// How a command ended.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandExit {
+    Code(i32),
+    /// Killed by a signal it did not get from ikode.
+    Signal,
+    /// Killed by ikode after running for the given time.
+    TimedOut(Duration),
+}
+
+impl fmt::Display for CommandExit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Code(code) => write!(f, "Exit code: {}", code),
+            Self::Signal => f.write_str("Terminated by a signal"),
+            Self::TimedOut(timeout) => write!(f, "Timed out after {}s and was killed", timeout.as_secs_f64()),
+        }
+    }
+}
+
+/// The result of a finished (or killed) command.
+#[derive(Debug, Clone)]
+pub struct CommandOutput {
+    pub exit: CommandExit,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+    /// The text handed back to the model.
+    pub fn to_tool_result(&self) -> String {
+        format!("{}\nSTDOUT:\n{}\nSTDERR:\n{}", self.exit, self.stdout, self.stderr)
+    }
+}
+
+/// Collects a stream up to `limit` bytes, keeping the first and last half of the limit and
+/// counting what was dropped in between.
+#[derive(Debug, Clone)]
+pub struct CappedOutput {
+    limit: usize,
+    head: Vec<u8>,
+    tail: VecDeque<u8>,
+    omitted: usize,
+}
+
+impl CappedOutput {
+    pub fn new(limit: usize) -> Self {
+        Self { limit, head: Vec::new(), tail: VecDeque::new(), omitted: 0 }
+    }
+
+    pub fn push(&mut self, bytes: &[u8]) {
+        let head_limit = self.limit / 2;
+        let split = head_limit.saturating_sub(self.head.len()).min(bytes.len());
+        self.head.extend_from_slice(&bytes[..split]);
+        self.tail.extend(&bytes[split..]);
+
+        let tail_limit = self.limit - head_limit;
+        if self.tail.len() > tail_limit {
+            let excess = self.tail.len() - tail_limit;
+            self.tail.drain(..excess);
+            self.omitted += excess;
+        }
+    }
+
+    /// Bytes pushed so far, including omitted ones.
+    pub fn received(&self) -> usize {
+        self.head.len() + self.tail.len() + self.omitted
+    }
+
+    pub fn into_string(self) -> String {
+        let head = String::from_utf8_lossy(&self.head).into_owned();
+        let tail = String::from_utf8_lossy(&Vec::from(self.tail)).into_owned();
+        if self.omitted == 0 {
+            head + &tail
+        } else {
+            format!("{}\n... [{} bytes omitted] ...\n{}", head, self.omitted, tail)
+        }
+    }
+}
+
+/// Builds the process for `command`: the shell (or the sandbox) in the working directory,
+/// with stdin closed so commands waiting for input fail instead of hanging, the output
+/// piped, and a process group of its own so that killing it also kills its children.
+pub fn shell_command(command: &str, options: &ExecOptions) -> Command {
+    let mut cmd = match options.sandbox {
+        SandboxMode::Off if cfg!(target_os = "windows") => {
+            let mut cmd = Command::new("cmd");
+            cmd.args(["/C", command]);
+            cmd
+        }
+        SandboxMode::Off => {
+            let mut cmd = Command::new("sh");
+            cmd.args(["-c", command]);
+            cmd
+        }
+        SandboxMode::Bubblewrap => {
+            let mut cmd = Command::new("bwrap");
+            cmd.args(sandbox_args(&options.working_directory));
+            for (name, value) in &options.env {
+                cmd.args(["--setenv", name, value]);
+            }
+            cmd.args(["sh", "-c", command]);
+            cmd
+        }
+    };
+    if options.sandbox == SandboxMode::Off {
+        cmd.envs(options.env.iter().map(|(name, value)| (name, value)));
+    }
+    cmd.current_dir(&options.working_directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    cmd.process_group(0);
+    cmd
+}
+
+/// Runs `command` (see `shell_command`), killing it and everything it started once
+/// `options.timeout` has passed.
+pub async fn run_command(command: &str, options: &ExecOptions) -> Result<CommandOutput> {
+    run_command_with_input(command, options, None).await
+}
+
+/// Like `run_command`, writing `input` to the command's stdin and then closing it.
+pub async fn run_command_with_input(command: &str, options: &ExecOptions, input: Option<Vec<u8>>) -> Result<CommandOutput> {
+    let mut cmd = shell_command(command, options);
+    if input.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    let mut child = cmd.spawn().map_err(|e| anyhow!("Could not start command: {}", e))?;
+    let pid = child.id();
+    let mut group = pid.map(|pid| RunningGroup::register(pid, &options.process_groups));
+    if let (Some(mut stdin), Some(input)) = (child.stdin.take(), input) {
+        // A command that exits without reading all of it only makes the write fail.
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&input).await;
+        });
+    }
+
+    let stdout = Arc::new(Mutex::new(CappedOutput::new(options.output_limit)));
+    let stderr = Arc::new(Mutex::new(CappedOutput::new(options.output_limit)));
+    let mut readers = Vec::new();
+    if let Some(pipe) = child.stdout.take() {
+        readers.push(tokio::spawn(collect(pipe, stdout.clone())));
+    }
+    if let Some(pipe) = child.stderr.take() {
+        readers.push(tokio::spawn(collect(pipe, stderr.clone())));
+    }
+
+    let deadline = tokio::time::Instant::now() + options.timeout;
+    let exit = match tokio::time::timeout_at(deadline, child.wait()).await {
+        Ok(status) => {
+            let status = status?;
+            // Children left running in the background may still hold the pipes open.
+            let grace = deadline.min(tokio::time::Instant::now() + EXIT_GRACE);
+            if tokio::time::timeout_at(grace, wait_for(&mut readers)).await.is_err() {
+                kill(&mut child, pid).await;
+                let _ = tokio::time::timeout(KILL_GRACE, wait_for(&mut readers)).await;
+            }
+            status.code().map_or(CommandExit::Signal, CommandExit::Code)
+        }
+        Err(_) => {
+            kill(&mut child, pid).await;
+            let _ = tokio::time::timeout(KILL_GRACE, wait_for(&mut readers)).await;
+            CommandExit::TimedOut(options.timeout)
+        }
+    };
+    for reader in &readers {
+        reader.abort();
+    }
+    if let Some(group) = &mut group {
+        group.finished = true;
+    }
+
+    let take = |output: &Arc<Mutex<CappedOutput>>| {
+        let mut output = output.lock().unwrap();
+        std::mem::replace(&mut *output, CappedOutput::new(0)).into_string()
+    };
+    Ok(CommandOutput { exit, stdout: take(&stdout), stderr: take(&stderr) })
+}
+
+/// A command's entry in `ProcessGroups` while it runs. If the command is abandoned before
+/// it finished, e.g. because the future running it was dropped, its group is killed too.
+struct RunningGroup {
+    pid: u32,
+    groups: ProcessGroups,
+    finished: bool,
+}
+
+impl RunningGroup {
+    fn register(pid: u32, groups: &ProcessGroups) -> Self {
+        groups.lock().unwrap().insert(pid);
+        Self { pid, groups: groups.clone(), finished: false }
+    }
+}
+
+impl Drop for RunningGroup {
+    fn drop(&mut self) {
+        self.groups.lock().unwrap().remove(&self.pid);
+        if !self.finished {
+            kill_process_group(self.pid);
+        }
+    }
+}
+
+/// Appends everything read from `pipe` to `output` until the pipe closes.
+pub async fn collect(mut pipe: impl AsyncRead + Unpin, output: Arc<Mutex<CappedOutput>>) {
+    let mut buffer = [0u8; 8192];
+    while let Ok(read) = pipe.read(&mut buffer).await {
+        if read == 0 {
+            break;
+        }
+        output.lock().unwrap().push(&buffer[..read]);
+    }
+}
+
+/// Waits for the readers to reach the end of their pipes, dropping each one that did so
+/// that a later call does not poll it again.
+async fn wait_for(readers: &mut Vec<tokio::task::JoinHandle<()>>) {
+    while let Some(reader) = readers.last_mut() {
+        let _ = reader.await;
+        readers.pop();
+    }
+}
+
+/// Kills the command's process group (on Unix) and the command itself, then reaps it.
+pub async fn kill(child: &mut Child, pid: Option<u32>) {
+    if let Some(pid) = pid {
+        kill_process_group(pid);
+    }
+    let _ = child.start_kill();
+    let _ = child.wait().await;
+}
+
+/// Kills the process group led by `pid`, as created by `shell_command`. Does nothing on
+/// platforms without process groups.
+pub fn kill_process_group(pid: u32) {
+    #[cfg(unix)]
+    // SAFETY: kill(2) has no memory-safety preconditions; a negative pid names the
+    // process group created for the command.
+    unsafe {
+        libc::kill(-(pid as i32), libc::SIGKILL);
+    }
+    #[cfg(not(unix))]
+    let _ = pid;
+}
+
+/// Whether the process group led by `pid` still has members, which may outlive the leader
+/// when it started them in the background. Always false on platforms without process groups.
+pub fn process_group_alive(pid: u32) -> bool {
+    #[cfg(unix)]
+    // SAFETY: as in `kill_process_group`; signal 0 only checks that the group exists.
+    unsafe {
+        libc::kill(-(pid as i32), 0) == 0 || std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = pid;
+        false
+    }
+}
@@ -0,0 +1,156 @@
+use crate::exec::{self, CommandExit, ExecOptions};
+use crate::permissions::wildcard_match;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How long a hook may run unless its config says otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Exit code with which a hook blocks the event, giving its stderr as the reason.
+pub const BLOCK_EXIT_CODE: i32 = 2;
+
+/// When a hook runs.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    /// Before a tool call; may block it or change its arguments.
+    PreToolUse,
+    /// After a tool call; may add to its result.
+    PostToolUse,
+    /// Before a prompt is sent; may block it or add context to it.
+    UserPromptSubmit,
+    /// When the session ends, on exit or `/clear`.
+    SessionEnd,
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// A hook from a config file:
+///
+/// ```toml
+/// [[hooks]]
+/// event = "PostToolUse"
+/// tool = "*edit*"
+/// command = "cargo fmt"
+/// ```
+///
+/// The command gets a JSON description of the event on stdin. Exit code 2 blocks the event
+/// with stderr as the reason; on exit code 0, JSON printed to stdout can block it too
+/// (`{"decision": "block", "reason": "..."}`), replace the tool arguments (`{"arguments": {...}}`)
+/// or add context (`{"context": "..."}`). Other failures only produce a warning.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HookConfig {
+    pub event: HookEvent,
+    /// Pattern over the tool name for the tool events, where `*` matches anything; all
+    /// tools if unset.
+    pub tool: Option<String>,
+    pub command: String,
+    /// Seconds before the hook is killed.
+    pub timeout: Option<u64>,
+}
+
+impl HookConfig {
+    fn matches(&self, event: HookEvent, tool: Option<&str>) -> bool {
+        self.event == event
+            && match (&self.tool, tool) {
+                (Some(pattern), Some(tool)) => wildcard_match(pattern, tool),
+                _ => true,
+            }
+    }
+}
+
+/// What the hooks for one event decided.
+#[derive(Debug, Default, PartialEq)]
+pub struct HookOutcome {
+    /// Why a hook blocked the event; the hooks after it did not run.
+    pub block: Option<String>,
+    /// Tool arguments as changed by `PreToolUse` hooks.
+    pub arguments: Option<Value>,
+    /// Text the hooks asked to add to the tool result or the prompt.
+    pub context: Vec<String>,
+    /// Hooks that failed without blocking anything.
+    pub errors: Vec<String>,
+}
+
+/// The configured hooks, run in the order they were configured.
+#[derive(Debug, Clone, Default)]
+pub struct Hooks {
+    hooks: Vec<HookConfig>,
+    working_directory: PathBuf,
+}
+
+impl Hooks {
+    pub fn new(hooks: Vec<HookConfig>, working_directory: PathBuf) -> Self {
+        Self { hooks, working_directory }
+    }
+
+    pub fn list(&self) -> &[HookConfig] {
+        &self.hooks
+    }
+
+    pub fn has(&self, event: HookEvent) -> bool {
+        self.hooks.iter().any(|hook| hook.event == event)
+    }
+
+    /// Runs the hooks for `event` (and `tool`, for tool events) with `input` on stdin, plus
+    /// an `event` field. A hook that changes the arguments passes them on to the next one
+    /// as `tool_input`.
+    pub async fn run(&self, event: HookEvent, tool: Option<&str>, mut input: Value) -> HookOutcome {
+        let mut outcome = HookOutcome::default();
+        input["event"] = Value::String(event.to_string());
+
+        for hook in self.hooks.iter().filter(|hook| hook.matches(event, tool)) {
+            let options = ExecOptions {
+                timeout: hook.timeout.map_or(DEFAULT_TIMEOUT, |seconds| Duration::from_secs(seconds.max(1))),
+                ..ExecOptions::new(self.working_directory.clone())
+            };
+            let output = match exec::run_command_with_input(&hook.command, &options, Some(input.to_string().into_bytes())).await {
+                Ok(output) => output,
+                Err(e) => {
+                    outcome.errors.push(format!("`{}`: {}", hook.command, e));
+                    continue;
+                }
+            };
+
+            match output.exit {
+                CommandExit::Code(0) => {}
+                CommandExit::Code(BLOCK_EXIT_CODE) => {
+                    let reason = output.stderr.trim();
+                    outcome.block = Some(if reason.is_empty() { format!("blocked by `{}`", hook.command) } else { reason.to_string() });
+                    return outcome;
+                }
+                exit => {
+                    let stderr = output.stderr.trim();
+                    outcome.errors.push(format!("`{}`: {}{}", hook.command, exit, if stderr.is_empty() { String::new() } else { format!(": {}", stderr) }));
+                    continue;
+                }
+            }
+
+            // Plain text output is only a log for the user; JSON output is a decision.
+            let Ok(Value::Object(response)) = serde_json::from_str::<Value>(output.stdout.trim()) else { continue };
+            if response.get("decision").and_then(Value::as_str) == Some("block") {
+                let reason = response.get("reason").and_then(Value::as_str).unwrap_or_default();
+                outcome.block = Some(if reason.is_empty() { format!("blocked by `{}`", hook.command) } else { reason.to_string() });
+                return outcome;
+            }
+            if let Some(context) = response.get("context").and_then(Value::as_str) {
+                outcome.context.push(context.to_string());
+            }
+            if let Some(arguments) = response.get("arguments").filter(|_| event == HookEvent::PreToolUse) {
+                if arguments.is_object() {
+                    input["tool_input"] = arguments.clone();
+                    outcome.arguments = Some(arguments.clone());
+                } else {
+                    outcome.errors.push(format!("`{}`: arguments must be a JSON object", hook.command));
+                }
+            }
+        }
+        outcome
+    }
+}
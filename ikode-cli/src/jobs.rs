@@ -0,0 +1,237 @@
+use crate::exec::{self, CappedOutput, ExecOptions, ProcessGroups};
+use anyhow::{Result, anyhow};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::Child;
+
+/// State of a background job.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Exited(i32),
+    /// Terminated by a signal it did not get from ikode.
+    Signalled,
+    /// Killed with `kill_background_command` or `/jobs`.
+    Killed,
+}
+
+impl fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Running => f.write_str("running"),
+            Self::Exited(code) => write!(f, "exited with code {}", code),
+            Self::Signalled => f.write_str("terminated by a signal"),
+            Self::Killed => f.write_str("killed"),
+        }
+    }
+}
+
+/// A job as shown by `/jobs`.
+#[derive(Debug, Clone)]
+pub struct JobSummary {
+    pub id: usize,
+    pub command: String,
+    pub status: JobStatus,
+    pub runtime: Duration,
+    /// Output bytes not yet returned by `read`.
+    pub unread: usize,
+}
+
+struct Job {
+    id: usize,
+    command: String,
+    started: Instant,
+    /// Set once the job is no longer running.
+    finished: Option<Instant>,
+    child: Child,
+    pid: Option<u32>,
+    status: JobStatus,
+    /// Stdout and stderr as they arrive, since the last read.
+    unread: Arc<Mutex<CappedOutput>>,
+    output_limit: usize,
+}
+
+impl Job {
+    fn runtime(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+
+    /// Notices that the job ended, and forgets its process group once it has no members left:
+    /// processes the job left behind (`nohup server &`) are still killed with it, but once the
+    /// group is gone its id may be reused by an unrelated one that must not be killed in its place.
+    fn refresh(&mut self, groups: &ProcessGroups) {
+        if self.status == JobStatus::Running {
+            if let Ok(Some(status)) = self.child.try_wait() {
+                self.status = status.code().map_or(JobStatus::Signalled, JobStatus::Exited);
+                self.finished = Some(Instant::now());
+            }
+        }
+        if self.status != JobStatus::Running {
+            if let Some(pid) = self.pid {
+                let mut groups = groups.lock().unwrap();
+                if groups.contains(&pid) && !exec::process_group_alive(pid) {
+                    groups.remove(&pid);
+                }
+            }
+        }
+    }
+
+    /// Whether the job ended but processes it started are still running in its group.
+    fn left_processes(&self, groups: &ProcessGroups) -> bool {
+        self.status != JobStatus::Running && self.pid.is_some_and(|pid| groups.lock().unwrap().contains(&pid))
+    }
+}
+
+/// Commands started with `start_background_command`, which keep running while the agent
+/// works. Their output is collected (up to the command output limit between two reads) and
+/// handed out incrementally. Dropping the registry kills every running job and its children,
+/// including those left running by jobs that already ended.
+#[derive(Default)]
+pub struct JobRegistry {
+    jobs: Vec<Job>,
+    groups: ProcessGroups,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn process_groups(&self) -> ProcessGroups {
+        self.groups.clone()
+    }
+
+    /// Starts `command` like `execute_command` would, but without waiting for it or applying
+    /// the timeout. Returns the job id.
+    pub fn start(&mut self, command: &str, options: &ExecOptions) -> Result<usize> {
+        let mut child = exec::shell_command(command, options)
+            .spawn()
+            .map_err(|e| anyhow!("Could not start command: {}", e))?;
+        let pid = child.id();
+
+        let unread = Arc::new(Mutex::new(CappedOutput::new(options.output_limit)));
+        if let Some(pipe) = child.stdout.take() {
+            tokio::spawn(exec::collect(pipe, unread.clone()));
+        }
+        if let Some(pipe) = child.stderr.take() {
+            tokio::spawn(exec::collect(pipe, unread.clone()));
+        }
+        if let Some(pid) = pid {
+            self.groups.lock().unwrap().insert(pid);
+        }
+
+        let id = self.jobs.len() + 1;
+        self.jobs.push(Job {
+            id,
+            command: command.to_string(),
+            started: Instant::now(),
+            finished: None,
+            child,
+            pid,
+            status: JobStatus::Running,
+            unread,
+            output_limit: options.output_limit,
+        });
+        Ok(id)
+    }
+
+    fn job(&mut self, id: usize) -> Result<&mut Job> {
+        let groups = self.groups.clone();
+        let job = self
+            .jobs
+            .iter_mut()
+            .find(|job| job.id == id)
+            .ok_or_else(|| anyhow!("No background job with id {}", id))?;
+        job.refresh(&groups);
+        Ok(job)
+    }
+
+    /// The job's status and the output it produced since the last read.
+    pub fn read(&mut self, id: usize) -> Result<String> {
+        let job = self.job(id)?;
+        let output = {
+            let mut unread = job.unread.lock().unwrap();
+            std::mem::replace(&mut *unread, CappedOutput::new(job.output_limit)).into_string()
+        };
+        let mut result = format!("Job {} (`{}`) is {} after {}s.\n", job.id, job.command, job.status, job.runtime().as_secs());
+        if output.is_empty() {
+            result.push_str("No new output.");
+        } else {
+            result.push_str("New output:\n");
+            result.push_str(&output);
+        }
+        Ok(result)
+    }
+
+    /// Kills the job and everything it started, even if the job itself already ended.
+    pub async fn kill(&mut self, id: usize) -> Result<String> {
+        let groups = self.groups.clone();
+        let job = self.job(id)?;
+        if job.left_processes(&groups) {
+            if let Some(pid) = job.pid {
+                exec::kill_process_group(pid);
+                groups.lock().unwrap().remove(&pid);
+            }
+            return Ok(format!("Job {} has already {}; killed the processes it left running.", job.id, job.status));
+        }
+        if job.status != JobStatus::Running {
+            return Ok(format!("Job {} has already {}.", job.id, job.status));
+        }
+        exec::kill(&mut job.child, job.pid).await;
+        if let Some(pid) = job.pid {
+            groups.lock().unwrap().remove(&pid);
+        }
+        job.status = JobStatus::Killed;
+        job.finished = Some(Instant::now());
+        Ok(format!("Job {} killed.", job.id))
+    }
+
+    /// Kills every running job, and what finished jobs left running, returning how many jobs
+    /// that concerned.
+    pub async fn kill_all(&mut self) -> usize {
+        let groups = self.groups.clone();
+        let mut alive = Vec::new();
+        for job in &mut self.jobs {
+            job.refresh(&groups);
+            if job.status == JobStatus::Running || job.left_processes(&groups) {
+                alive.push(job.id);
+            }
+        }
+        for id in &alive {
+            let _ = self.kill(*id).await;
+        }
+        alive.len()
+    }
+
+    pub fn jobs(&mut self) -> Vec<JobSummary> {
+        let groups = self.groups.clone();
+        self.jobs
+            .iter_mut()
+            .map(|job| {
+                job.refresh(&groups);
+                JobSummary {
+                    id: job.id,
+                    command: job.command.clone(),
+                    status: job.status.clone(),
+                    runtime: job.runtime(),
+                    unread: job.unread.lock().unwrap().received(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for JobRegistry {
+    fn drop(&mut self) {
+        let groups = self.groups.clone();
+        for job in &mut self.jobs {
+            job.refresh(&groups);
+        }
+        if let Ok(groups) = self.groups.lock() {
+            for pid in groups.iter() {
+                exec::kill_process_group(*pid);
+            }
+        }
+    }
+}
@@ -1,24 +1,67 @@
 use clap::{Parser, builder::styling};
 use gaise_core::contracts::{
-    GaiseContent, GaiseInstructRequest, GaiseMessage,
-    GaiseToolCall, OneOrMany, GaiseGenerationConfig
+    GaiseContent, GaiseInstructRequest, GaiseMessage, GaiseStreamAccumulator,
+    GaiseStreamChunk, GaiseTool, GaiseToolCall, GaiseUsage, OneOrMany, GaiseGenerationConfig
 };
-use gaise_core::GaiseClient;
+use gaise_core::{GaiseClient, GaiseError};
+use gaise_core::logging::IGaiseLogger;
 use gaise_client::{GaiseClientService, GaiseClientConfig};
-use gaise_client::ServiceAccount;
-use std::io::{self, Write};
+use std::io::{self, IsTerminal, Write};
 use std::process::Command;
 use std::fs;
 use std::path::{Path, PathBuf};
-use dialoguer::Confirm;
+use dialoguer::{Input, Select};
 use anyhow::{Result, anyhow};
 use colored::*;
+use futures_util::StreamExt;
+use futures_util::future::join_all;
 use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
+use serde_json::json;
 use uuid::Uuid;
 
+mod agents;
+mod checkpoints;
+mod commands;
+mod compaction;
+mod config;
+mod context;
+mod cost;
+mod custom_tools;
+mod diff;
+mod exec;
+mod hooks;
+mod jobs;
+mod mcp;
+mod patch;
+mod paths;
+mod permissions;
+mod search;
+mod session;
 mod tools;
+mod trust;
+use agents::SubAgent;
+use checkpoints::{Checkpoints, Restored};
+use commands::{BUILTIN_COMMANDS, Builtin, CustomCommand, SlashCommand, SlashCommands};
+use compaction::CompactionSummary;
+use config::{ConfigSource, IkodeConfig};
+use context::TokenCalibration;
+use cost::{PriceTable, TokenUsage, UsageTotals};
+use custom_tools::CustomTool;
+use exec::{ExecOptions, SandboxMode};
+use hooks::{HookEvent, HookOutcome, Hooks};
+use jobs::JobRegistry;
+use mcp::{McpServerState, McpServers};
+use permissions::{AuditEntry, AuditLog, Authorization, Decision, PermissionRequest, Permissions};
+use session::{Session, SessionStore, Todo, SESSION_FORMAT_VERSION};
 use tools::*;
+use trust::TrustStore;
+
+const DEFAULT_MODEL: &str = "openai::gpt-4o";
+const DEFAULT_MAX_HISTORY: usize = 80;
+const DEFAULT_PREFIX_KEEP: usize = 4;
 
 const STYLES: styling::Styles = styling::Styles::styled()
     .header(styling::AnsiColor::Green.on_default().bold())
@@ -38,79 +81,150 @@ struct Args {
     #[arg(short, long, help = "The prompt to process")]
     prompt: Option<String>,
 
-    #[arg(short, long, default_value = "openai::gpt-4o", help = "The model to use")]
-    model: String,
+    #[arg(short, long, help = "The model to use (default: openai::gpt-4o)")]
+    model: Option<String>,
 
-    #[arg(short, long, default_value_t = false, help = "Whether to use brave mode (no confirmation for commands)")]
+    #[arg(long = "fallback-model", value_name = "MODEL", help = "Model to fall back to when --model is rate-limited or unavailable (repeat for a chain)")]
+    fallback_models: Vec<String>,
+
+    #[arg(short, long, default_value_t = false, help = "Whether to use brave mode (no confirmation unless a permission rule asks for it)")]
     brave: bool,
 
     #[arg(short, long, help = "Path to a guide file")]
     guide: Option<String>,
 
-    #[arg(long, default_value_t = 80, help = "Maximum number of history messages sent per request")]
-    max_history: usize,
+    #[arg(long, help = "Maximum number of history messages sent per request (default: 80)")]
+    max_history: Option<usize>,
 
-    #[arg(long, default_value_t = 4, help = "Number of early messages to always keep for cache stability")]
-    prefix_keep: usize,
-}
+    #[arg(long, help = "Number of early messages to always keep for cache stability (default: 4)")]
+    prefix_keep: Option<usize>,
+
+    #[arg(long, default_value_t = false, help = "Wait for the full response instead of streaming it")]
+    no_stream: bool,
+
+    #[arg(long, value_name = "TOKENS", help = "Token budget for each request; older history is compacted or truncated to fit (default: the model's context window, 0 = unlimited)")]
+    max_context_tokens: Option<usize>,
+
+    #[arg(long, value_name = "TOKENS", help = "Summarise older history once a request is estimated to exceed this many tokens (default: 80% of the context budget, 0 = disabled)")]
+    auto_compact_tokens: Option<usize>,
+
+    #[arg(long, value_name = "MODEL", help = "Model used to summarise history when compacting (defaults to --model)")]
+    compact_model: Option<String>,
+
+    #[arg(long, value_name = "MODEL", help = "Model for sub-agents started with spawn_agent, unless the agent names one (defaults to --model)")]
+    agent_model: Option<String>,
+
+    #[arg(long, value_name = "USD", help = "Stop the agent once the session has cost more than this many US dollars")]
+    max_cost: Option<f64>,
+
+    #[arg(long, default_value_t = false, help = "Print a token and cost summary after every turn")]
+    status_line: bool,
+
+    #[arg(long, value_name = "SECONDS", help = "Kill commands that run longer than this unless the model asks for another timeout (default: 120)")]
+    command_timeout: Option<u64>,
+
+    #[arg(long, value_name = "MODE", help = "Run commands in a sandbox: off or bubblewrap (Linux, needs bwrap; writes only to the working directory, no network)")]
+    sandbox: Option<SandboxMode>,
+
+    #[arg(long, value_name = "ID", conflicts_with = "continue_session", help = "Resume a saved session by id (or unique id prefix)")]
+    resume: Option<String>,
 
-struct Todo {
-    id: usize,
-    task: String,
-    completed: bool,
+    #[arg(short = 'c', long = "continue", default_value_t = false, help = "Continue the most recent session for this directory")]
+    continue_session: bool,
 }
 
 struct App {
-    client: Box<dyn GaiseClient>,
+    client: GaiseClientService,
     history: Vec<GaiseMessage>,
     todos: Vec<Todo>,
     model: String,
     brave: bool,
     system_prompt: String,
+    session_id: String,
+    session_created_at: chrono::DateTime<chrono::Utc>,
     session_cache_key: String,
+    sessions: Option<SessionStore>,
     working_directory: PathBuf,
     max_history: usize,
     prefix_keep: usize,
+    stream: bool,
+    compaction: Option<CompactionSummary>,
+    fallback_models: Vec<String>,
+    compact_model: Option<String>,
+    agent_model: Option<String>,
+    auto_compact_tokens: Option<usize>,
+    max_context_tokens: Option<usize>,
+    calibration: TokenCalibration,
+    last_prompt_tokens: Option<usize>,
+    prices: PriceTable,
+    turn_usage: UsageTotals,
+    session_usage: UsageTotals,
+    max_cost: Option<f64>,
+    status_line: bool,
+    config: IkodeConfig,
+    /// Where each setting shown by `/config` came from.
+    config_sources: HashMap<&'static str, ConfigSource>,
+    guides: Vec<(String, ConfigSource)>,
+    permissions: Permissions,
+    audit: Option<AuditLog>,
+    /// Timeout, output limit and sandbox for `execute_command`.
+    exec: ExecOptions,
+    /// Commands started with `start_background_command`.
+    jobs: JobRegistry,
+    /// Files as they were before each turn changed them, for `/undo` and `/restore`.
+    checkpoints: Checkpoints,
+    /// MCP servers from the config, started by `start_mcp_servers`.
+    mcp: McpServers,
+    /// Tools declared in `.ikode/tools/*.toml`.
+    custom_tools: Vec<CustomTool>,
+    hooks: Hooks,
+    /// Built-in and custom slash commands.
+    commands: SlashCommands,
+    /// The custom command whose turn is running, limiting the tools offered.
+    active_command: Option<CustomCommand>,
+    /// Sub-agents started this session, for numbering them.
+    agents_spawned: usize,
 }
 
 impl App {
-    fn new(model: String, brave: bool, guide_path: Option<String>, max_history: usize, prefix_keep: usize) -> Result<Self> {
-        let mut config = GaiseClientConfig::default();
+    fn new(args: &Args) -> Result<Self> {
+        let working_directory = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let mut ikode_config = IkodeConfig::load(&working_directory)?;
+        confirm_project_trust(&mut ikode_config);
 
-        if let Ok(api_key) = std::env::var("OPENAI_API_KEY") {
-            config.openai_api_key = Some(api_key);
-        }
-        if let Ok(api_url) = std::env::var("OPENAI_API_URL") {
-            config.openai_api_url = Some(api_url);
-        }
-        if let Ok(ollama_url) = std::env::var("OLLAMA_URL") {
-            config.ollama_url = Some(ollama_url);
-        }
-        
-        if let Ok(region) = std::env::var("AWS_REGION") {
-            config.bedrock_region = Some(region);
-        }
+        // Provider settings come from the files in GAISE_CONFIG, the ikode config files and the
+        // usual provider variables, in that order.
+        let mut config = GaiseClientConfig::from_env_with(ikode_config.gaise_layers.clone())
+            .map_err(|e| anyhow!("Could not load gaise configuration: {}", e))?;
+        config.logger = Some(Arc::new(RetryNotice));
 
-        if let Ok(api_url) = std::env::var("VERTEXAI_API_URL") {
-            config.vertexai_api_url = Some(api_url);
-        }
+        let client = GaiseClientService::new(config);
 
-        if let Ok(sa_path) = std::env::var("VERTEXAI_SA_PATH") {
-            if let Ok(sa_content) = std::fs::read_to_string(sa_path) {
-                if let Ok(sa) = serde_json::from_str::<serde_json::Value>(&sa_content) {
-                   // Map serde_json::Value to ServiceAccount if it matches
-                   if let (Some(pk), Some(email)) = (sa["private_key"].as_str(), sa["client_email"].as_str()) {
-                       config.vertexai_sa = Some(ServiceAccount {
-                           private_key: pk.to_string(),
-                           client_email: email.to_string(),
-                       });
-                   }
-                }
-            }
+        fn setting<T>(sources: &mut HashMap<&'static str, ConfigSource>, key: &'static str, (value, source): (T, ConfigSource)) -> T {
+            sources.insert(key, source);
+            value
         }
-        // VertexAI and others can be added as needed
-
-        let client = GaiseClientService::new(config);
+        let mut sources = HashMap::new();
+        let model: String = setting(&mut sources, "model", ikode_config.resolve(args.model.clone(), |f| f.model.clone(), DEFAULT_MODEL.to_string()));
+        let fallback_models: Vec<String> = setting(&mut sources, "fallback_models", ikode_config.resolve(
+            (!args.fallback_models.is_empty()).then(|| args.fallback_models.clone()),
+            |f| f.fallback_models.clone(),
+            Vec::new(),
+        ));
+        let compact_model: Option<String> = setting(&mut sources, "compact_model", ikode_config.resolve_option(args.compact_model.clone(), |f| f.compact_model.clone()));
+        let agent_model: Option<String> = setting(&mut sources, "agent_model", ikode_config.resolve_option(args.agent_model.clone(), |f| f.agent_model.clone()));
+        let brave: bool = setting(&mut sources, "brave", ikode_config.resolve(args.brave.then_some(true), |f| f.brave, false));
+        let max_history: usize = setting(&mut sources, "max_history", ikode_config.resolve(args.max_history, |f| f.max_history, DEFAULT_MAX_HISTORY));
+        let prefix_keep: usize = setting(&mut sources, "prefix_keep", ikode_config.resolve(args.prefix_keep, |f| f.prefix_keep, DEFAULT_PREFIX_KEEP));
+        let max_context_tokens: Option<usize> = setting(&mut sources, "max_context_tokens", ikode_config.resolve_option(args.max_context_tokens, |f| f.max_context_tokens));
+        let auto_compact_tokens: Option<usize> = setting(&mut sources, "auto_compact_tokens", ikode_config.resolve_option(args.auto_compact_tokens, |f| f.auto_compact_tokens));
+        let max_cost: Option<f64> = setting(&mut sources, "max_cost", ikode_config.resolve_option(args.max_cost, |f| f.max_cost));
+        let status_line: bool = setting(&mut sources, "status_line", ikode_config.resolve(args.status_line.then_some(true), |f| f.status_line, false));
+        let stream: bool = setting(&mut sources, "stream", ikode_config.resolve(args.no_stream.then_some(false), |f| f.stream, true));
+        let command_timeout: u64 = setting(&mut sources, "command_timeout", ikode_config.resolve(args.command_timeout, |f| f.command_timeout, exec::DEFAULT_TIMEOUT.as_secs()));
+        let command_output_limit: usize = setting(&mut sources, "command_output_limit", ikode_config.resolve(None, |f| f.command_output_limit, exec::DEFAULT_OUTPUT_LIMIT));
+        let sandbox: SandboxMode = setting(&mut sources, "sandbox", ikode_config.resolve(args.sandbox, |f| f.sandbox, SandboxMode::Off));
+        exec::check_sandbox(sandbox)?;
 
         let system_prompt_raw = include_str!("sys-prompt.md");
         let mut system_prompt = Self::format_system_prompt(system_prompt_raw);
@@ -121,9 +235,17 @@ impl App {
             system_prompt.push_str(&content);
         }
 
-        // Check for guide argument
-        if let Some(path) = guide_path {
-            match fs::read_to_string(&path) {
+        // Guide files from the config files, then the guide argument
+        let mut guides = ikode_config.guides();
+        if let Some(path) = &args.guide {
+            guides.push((path.clone(), ConfigSource::Flag));
+        }
+        let canonical_directory = working_directory.canonicalize().unwrap_or_else(|_| working_directory.clone());
+        for (path, source) in &guides {
+            let content = ikode_config
+                .guide_path(path, source, &canonical_directory)
+                .and_then(|guide_path| Ok(fs::read_to_string(guide_path)?));
+            match content {
                 Ok(content) => {
                     system_prompt.push_str(&format!("\n\nUser Guidelines (from {}):\n", path));
                     system_prompt.push_str(&content);
@@ -132,10 +254,50 @@ impl App {
             }
         }
 
-        let working_directory = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let sessions = SessionStore::for_project(&working_directory);
+        let permissions = Permissions::new(ikode_config.permission_rules(), brave)?;
+        let audit = audit_log_path(&working_directory).map(AuditLog::new);
+        let hooks = Hooks::new(ikode_config.hooks(), working_directory.clone());
+        let exec = ExecOptions {
+            timeout: Duration::from_secs(command_timeout.max(1)),
+            output_limit: command_output_limit,
+            sandbox,
+            ..ExecOptions::new(working_directory.clone())
+        };
+
+        let (custom_tools, errors) = custom_tools::load_custom_tools(&custom_tools::tools_dir(&working_directory));
+        for error in errors {
+            eprintln!("{} Warning: Skipping custom tool {}", "⚠️".yellow(), error);
+        }
+        let custom_tools = confirm_custom_tools_trust(custom_tools, &working_directory);
+        if !custom_tools.is_empty() {
+            let names: Vec<&str> = custom_tools.iter().map(|tool| tool.name.as_str()).collect();
+            println!("{} Custom tools: {}", "🧰".bright_blue(), names.join(", ").bright_magenta());
+        }
+
+        let command_dirs: Vec<PathBuf> = commands::global_commands_dir()
+            .into_iter()
+            .chain(std::iter::once(commands::project_commands_dir(&working_directory)))
+            .collect();
+        let (custom_commands, errors) = commands::load_custom_commands(&command_dirs);
+        for error in errors {
+            eprintln!("{} Warning: Skipping custom command {}", "⚠️".yellow(), error);
+        }
+        if !custom_commands.is_empty() {
+            let names: Vec<String> = custom_commands.iter().map(|command| format!("/{}", command.name)).collect();
+            println!("{} Custom commands: {}", "📜".bright_blue(), names.join(", ").bright_magenta());
+        }
+
+        let prices = match prices_path() {
+            Some(path) => PriceTable::with_overrides(&path).unwrap_or_else(|e| {
+                eprintln!("{} Warning: {}", "⚠️".yellow(), e);
+                PriceTable::default()
+            }),
+            None => PriceTable::default(),
+        };
 
         Ok(Self {
-            client: Box::new(client),
+            client,
             history: vec![GaiseMessage {
                 role: "system".to_string(),
                 content: Some(OneOrMany::One(GaiseContent::Text { text: system_prompt.clone() })),
@@ -146,13 +308,216 @@ impl App {
             model,
             brave,
             system_prompt,
+            session_id: Uuid::new_v4().to_string(),
+            session_created_at: chrono::Utc::now(),
             session_cache_key: Uuid::new_v4().to_string(),
+            sessions,
             working_directory,
             max_history,
             prefix_keep,
+            stream,
+            compaction: None,
+            fallback_models,
+            compact_model,
+            agent_model,
+            auto_compact_tokens,
+            max_context_tokens,
+            calibration: TokenCalibration::default(),
+            last_prompt_tokens: None,
+            prices,
+            turn_usage: UsageTotals::default(),
+            session_usage: UsageTotals::default(),
+            max_cost: max_cost.filter(|limit| *limit > 0.0),
+            status_line,
+            config: ikode_config,
+            config_sources: sources,
+            guides,
+            permissions,
+            audit,
+            exec,
+            jobs: JobRegistry::new(),
+            checkpoints: Checkpoints::new(),
+            mcp: McpServers::default(),
+            custom_tools,
+            hooks,
+            commands: SlashCommands::new(custom_commands),
+            active_command: None,
+            agents_spawned: 0,
         })
     }
 
+    /// Resolves the provider for the configured model and its fallbacks so that an unknown
+    /// provider or missing credentials are reported before the first prompt is sent.
+    async fn check_model(&self) -> Result<()> {
+        for model in std::iter::once(&self.model).chain(&self.fallback_models) {
+            self.client.check_model(model).await.map_err(|e| anyhow!("Model '{}': {}", model, e))?;
+        }
+        Ok(())
+    }
+
+    /// The configured model with an alias such as `fast` or a named endpoint resolved to
+    /// `provider::model`, for looking up its context window and price.
+    fn resolved_model(&self) -> String {
+        self.client.provider_model(&self.model)
+    }
+
+    fn system_message(&self) -> GaiseMessage {
+        GaiseMessage {
+            role: "system".to_string(),
+            content: Some(OneOrMany::One(GaiseContent::Text { text: self.system_prompt.clone() })),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    /// Writes the current conversation to the session store. Sessions without any user
+    /// message are not worth keeping, and a failed write only produces a warning.
+    fn save_session(&self) {
+        let Some(store) = &self.sessions else { return };
+        if !self.history.iter().any(|m| m.role == "user") {
+            return;
+        }
+
+        let session = Session {
+            version: SESSION_FORMAT_VERSION,
+            id: self.session_id.clone(),
+            working_directory: self.working_directory.clone(),
+            model: self.model.clone(),
+            created_at: self.session_created_at,
+            updated_at: chrono::Utc::now(),
+            session_cache_key: self.session_cache_key.clone(),
+            history: self.history.clone(),
+            todos: self.todos.clone(),
+            compaction: self.compaction.clone(),
+            usage: self.session_usage.clone(),
+        };
+
+        if let Err(e) = store.save(&session) {
+            eprintln!("{} Warning: Could not save session: {}", "⚠️".yellow(), e);
+        }
+    }
+
+    /// Replaces the current conversation with a saved one. The stored system prompt is
+    /// swapped for the current one so that updated guidelines and dates take effect.
+    fn restore_session(&mut self, session: Session) {
+        let mut history = session.history;
+        match history.first_mut() {
+            Some(first) if first.role == "system" => *first = self.system_message(),
+            _ => history.insert(0, self.system_message()),
+        }
+
+        self.session_id = session.id;
+        self.session_created_at = session.created_at;
+        self.session_cache_key = session.session_cache_key;
+        self.history = history;
+        self.todos = session.todos;
+        self.compaction = session.compaction;
+        self.session_usage = session.usage;
+        self.checkpoints.forget_history();
+
+        println!(
+            "{} Resumed session {} ({} messages)",
+            "📂".bright_blue(),
+            short_id(&self.session_id).bright_magenta().bold(),
+            self.history.len()
+        );
+
+        // A model picked explicitly for this run wins over the one the session was saved with.
+        if session.model.is_empty() || session.model == self.model {
+            return;
+        }
+        match self.config_sources.get("model") {
+            Some(source @ (ConfigSource::Flag | ConfigSource::Command)) => println!(
+                "  Keeping model {} from {} (the session used {}).",
+                self.model.bright_magenta().bold(),
+                source,
+                session.model.cyan()
+            ),
+            _ => {
+                self.model = session.model;
+                self.config_sources.insert("model", ConfigSource::Command);
+                self.calibration = TokenCalibration::default();
+                self.last_prompt_tokens = None;
+                println!("  Model restored to {}.", self.model.bright_magenta().bold());
+            }
+        }
+    }
+
+    fn resume_session(&mut self, id: &str) -> Result<()> {
+        let store = self.sessions.as_ref().ok_or_else(|| anyhow!("No data directory available for saved sessions"))?;
+        let session = store.load(id)?;
+        self.restore_session(session);
+        Ok(())
+    }
+
+    fn continue_latest_session(&mut self) -> Result<()> {
+        let store = self.sessions.as_ref().ok_or_else(|| anyhow!("No data directory available for saved sessions"))?;
+        let session = store.latest()?
+            .ok_or_else(|| anyhow!("No saved sessions for {}", self.working_directory.display()))?;
+        self.restore_session(session);
+        Ok(())
+    }
+
+    fn list_sessions(&self) {
+        let Some(store) = &self.sessions else {
+            println!("{} No data directory available for saved sessions.", "⚠️".bright_yellow());
+            return;
+        };
+
+        let summaries = match store.list() {
+            Ok(s) => s,
+            Err(e) => {
+                println!("{} Could not list sessions: {}", "⚠️".bright_yellow(), e);
+                return;
+            }
+        };
+
+        if summaries.is_empty() {
+            println!("{} No saved sessions for this directory.", "📂".bright_blue());
+            return;
+        }
+
+        println!("{} Saved sessions ({}):", "📂".bright_blue(), store.dir().display().to_string().dimmed());
+        for (i, summary) in summaries.iter().enumerate() {
+            let current = if summary.id == self.session_id { " (current)".bright_green().to_string() } else { String::new() };
+            let title: String = summary.title.chars().take(60).collect();
+            println!(
+                "  {}) {}  {}  {}  {} msgs  {}{}",
+                i + 1,
+                short_id(&summary.id).bright_magenta().bold(),
+                summary.updated_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"),
+                summary.model.cyan(),
+                summary.message_count,
+                title,
+                current
+            );
+        }
+        println!("{}", "Use /sessions {n} or /sessions {id} to load a session.".dimmed());
+    }
+
+    fn load_session_from_command(&mut self, selector: &str) {
+        let Some(store) = &self.sessions else {
+            println!("{} No data directory available for saved sessions.", "⚠️".bright_yellow());
+            return;
+        };
+
+        let id = match selector.parse::<usize>() {
+            Ok(n) => match store.list() {
+                Ok(summaries) if n >= 1 && n <= summaries.len() => summaries[n - 1].id.clone(),
+                _ => {
+                    println!("{} No session number {}. Use /sessions to list them.", "⚠️".bright_yellow(), n);
+                    return;
+                }
+            },
+            Err(_) => selector.to_string(),
+        };
+
+        self.save_session();
+        if let Err(e) = self.resume_session(&id) {
+            println!("{} {}", "⚠️".bright_yellow(), e);
+        }
+    }
+
     fn format_system_prompt(raw: &str) -> String {
         let wd = std::env::current_dir().unwrap_or_default().to_string_lossy().to_string();
         let platform = std::env::consts::OS;
@@ -167,62 +532,559 @@ impl App {
            .replace("__IS_GIT_REPO__", if is_git { "Yes" } else { "No" })
     }
 
-    fn build_request_history(&self) -> Vec<GaiseMessage> {
-        if self.max_history == 0 {
-            return self.history.clone();
+    /// End of the leading slice of history that is always sent unchanged. It is extended past
+    /// any tool results so an assistant tool call is never separated from its results.
+    fn prefix_end(&self) -> usize {
+        let prefix_end = (1 + self.prefix_keep).min(self.history.len());
+        compaction::skip_tool_results(&self.history, prefix_end)
+    }
+
+    /// The kept prefix, the compaction summary (if any) and the index of the first history
+    /// message that follows them. Everything from that index on is a candidate for the request.
+    fn compacted_head(&self) -> (Vec<GaiseMessage>, usize) {
+        let prefix_end = self.prefix_end();
+        let mut head: Vec<GaiseMessage> = self.history[..prefix_end].to_vec();
+
+        let mut tail_start = prefix_end;
+        if let Some(summary) = &self.compaction {
+            if summary.covered_until <= self.history.len() {
+                head.push(summary.to_message());
+                tail_start = summary.covered_until.max(prefix_end);
+            }
         }
+        (head, tail_start)
+    }
+
+    /// Token budget for a whole request: `--max-context-tokens` if given, otherwise the
+    /// model's context window. 0 means unlimited.
+    fn context_budget(&self) -> usize {
+        self.context_budget_for(&self.model)
+    }
 
+    /// `context_budget` for requests to `model`, e.g. a sub-agent's.
+    fn context_budget_for(&self, model: &str) -> usize {
+        self.max_context_tokens.unwrap_or_else(|| {
+            context::context_window(&self.client.provider_model(model)).unwrap_or(context::DEFAULT_CONTEXT_WINDOW)
+        })
+    }
+
+    fn auto_compact_threshold(&self) -> usize {
+        self.auto_compact_threshold_for(&self.model)
+    }
+
+    fn auto_compact_threshold_for(&self, model: &str) -> usize {
+        self.auto_compact_tokens.unwrap_or_else(|| self.context_budget_for(model) / 10 * 8)
+    }
+
+    /// Estimated prompt tokens for `messages`, calibrated against the usage reported for earlier requests.
+    fn estimate_request_tokens(&self, messages: &[GaiseMessage]) -> usize {
+        self.calibration.apply(compaction::estimate_tokens(messages))
+    }
+
+    /// Estimated size of the request before any truncation is applied.
+    fn untruncated_tokens(&self) -> usize {
+        let (head, tail_start) = self.compacted_head();
+        self.estimate_request_tokens(&head) + self.estimate_request_tokens(&self.history[tail_start..])
+    }
+
+    fn build_request_history(&self) -> Vec<GaiseMessage> {
         let total = self.history.len();
-        if total <= self.max_history {
-            return self.history.clone();
+        let (mut result, tail_start) = self.compacted_head();
+
+        let mut kept_start = tail_start;
+        if self.max_history > 0 && result.len() + (total - kept_start) > self.max_history {
+            let tail_count = self.max_history.saturating_sub(result.len());
+            kept_start = compaction::tool_safe_start(&self.history, total.saturating_sub(tail_count)).max(kept_start);
         }
 
-        let mut result = Vec::with_capacity(self.max_history);
+        let budget = self.context_budget();
+        if budget > 0 {
+            let tail = &self.history[kept_start..];
+            let tail_tokens: Vec<usize> = tail.iter()
+                .map(|m| self.calibration.apply(compaction::estimate_message_tokens(m)))
+                .collect();
+            let fixed_tokens = self.estimate_request_tokens(&result)
+                + self.estimate_request_tokens(&[compaction::truncation_note(total)]);
+            // The latest user message is never dropped, whatever its size.
+            let limit = tail.iter().rposition(|m| m.role == "user").unwrap_or(0);
+            let drop = context::fit_to_budget(&tail_tokens, fixed_tokens, context::prompt_budget(budget), limit);
+            if drop > 0 {
+                kept_start = compaction::skip_tool_results(&self.history, kept_start + drop);
+            }
+        }
 
-        let prefix_end = (1 + self.prefix_keep).min(total);
-        result.extend_from_slice(&self.history[..prefix_end]);
+        if kept_start > tail_start {
+            result.push(compaction::truncation_note(kept_start - tail_start));
+        }
 
-        let tail_count = self.max_history.saturating_sub(prefix_end);
-        let tail_start = total.saturating_sub(tail_count);
+        result.extend_from_slice(&self.history[kept_start..]);
+        result
+    }
 
-        if tail_start > prefix_end {
-            result.push(GaiseMessage {
-                role: "system".to_string(),
-                content: Some(OneOrMany::One(GaiseContent::Text {
-                    text: format!(
-                        "[Note: {} earlier messages were truncated to save context. The conversation continues below.]",
-                        tail_start - prefix_end
-                    ),
-                })),
-                tool_calls: None,
-                tool_call_id: None,
-            });
+    /// Feeds the usage reported for a request back into the token estimate.
+    fn record_usage(&mut self, estimated_tokens: usize, usage: &GaiseUsage) {
+        if let Some(actual) = usage.input_tokens {
+            self.calibration.observe(estimated_tokens, actual);
+            self.last_prompt_tokens = Some(actual);
         }
+    }
 
-        let actual_tail_start = tail_start.max(prefix_end);
-        result.extend_from_slice(&self.history[actual_tail_start..]);
+    /// Adds the usage of a request to `model` (possibly an alias) to the turn and session totals.
+    fn record_cost(&mut self, model: &str, usage: &GaiseUsage) {
+        let tokens = TokenUsage::from_usage(usage);
+        let cost = self.prices.price(&self.client.provider_model(model)).map(|price| price.cost(&tokens));
+        self.turn_usage.record(&tokens, cost);
+        self.session_usage.record(&tokens, cost);
+    }
 
-        result
+    fn print_cost(&self) {
+        fn row(label: &str, totals: &UsageTotals) {
+            let t = &totals.tokens;
+            println!(
+                "  {:<10} {:>8}  {:>16}  {:>18}  {}",
+                label,
+                totals.requests,
+                format!("{} ({})", cost::format_tokens(t.input), cost::format_tokens(t.cache_read)),
+                format!("{} ({})", cost::format_tokens(t.output), cost::format_tokens(t.reasoning)),
+                cost::format_cost(totals.cost).bright_magenta().bold()
+            );
+        }
+
+        println!("{} Usage and cost:", "💰".bright_blue());
+        println!("  {:<10} {:>8}  {:>16}  {:>18}  Cost", "", "Requests", "Input (cached)", "Output (reasoning)");
+        row("Last turn", &self.turn_usage);
+        row("Session", &self.session_usage);
+        if self.session_usage.tokens.cache_write > 0 {
+            println!("  Cache writes: {}", cost::format_tokens(self.session_usage.tokens.cache_write));
+        }
+
+        match self.prices.price(&self.resolved_model()) {
+            Some(price) => println!(
+                "  Price for {}: ${:.2} in / ${:.2} cached / ${:.2} out per 1M tokens",
+                self.model.cyan(),
+                price.input,
+                price.cached_input.unwrap_or(price.input),
+                price.output
+            ),
+            None => println!("  {} No price known for {}.", "⚠️".bright_yellow(), self.model.cyan()),
+        }
+        if self.session_usage.unpriced_requests > 0 {
+            println!(
+                "  {} {} requests had no price and are not included in the cost. Add prices to {}.",
+                "⚠️".bright_yellow(),
+                self.session_usage.unpriced_requests,
+                prices_path().map_or("prices.toml".to_string(), |p| p.display().to_string())
+            );
+        }
+        let limit_display = self.max_cost.map_or("none".to_string(), cost::format_cost);
+        println!("  Cost limit: {}", limit_display.bright_magenta().bold());
     }
 
-    fn validate_path(&self, path: &str) -> Result<PathBuf> {
-        let requested_path = Path::new(path);
-        let canonical_path = if requested_path.is_absolute() {
-            requested_path.canonicalize().unwrap_or_else(|_| requested_path.to_path_buf())
+    fn print_config(&self) {
+        fn or_none<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map_or("none".to_string(), T::to_string)
+        }
+
+        let rows = [
+            ("model", self.model.clone()),
+            ("fallback_models", if self.fallback_models.is_empty() { "none".to_string() } else { self.fallback_models.join(", ") }),
+            ("compact_model", or_none(&self.compact_model)),
+            ("agent_model", or_none(&self.agent_model)),
+            ("brave", self.brave.to_string()),
+            ("max_history", self.max_history.to_string()),
+            ("prefix_keep", self.prefix_keep.to_string()),
+            ("max_context_tokens", or_none(&self.max_context_tokens)),
+            ("auto_compact_tokens", or_none(&self.auto_compact_tokens)),
+            ("max_cost", self.max_cost.map_or("none".to_string(), cost::format_cost)),
+            ("status_line", self.status_line.to_string()),
+            ("stream", self.stream.to_string()),
+            ("command_timeout", format!("{}s", self.exec.timeout.as_secs())),
+            ("command_output_limit", self.exec.output_limit.to_string()),
+            ("sandbox", self.exec.sandbox.to_string()),
+        ];
+
+        println!("{} Effective configuration:", "⚙️".bright_blue());
+        for (key, value) in rows {
+            let source = self.config_sources.get(key).cloned().unwrap_or(ConfigSource::Default);
+            println!("  {:<20} {}  {}", key, value.bright_magenta().bold(), format!("({})", source).dimmed());
+        }
+        for (guide, source) in &self.guides {
+            println!("  {:<20} {}  {}", "guide", guide.bright_magenta().bold(), format!("({})", source).dimmed());
+        }
+        for model in self.config.configured_models() {
+            if let Some(generation_config) = self.config.generation_config(&model) {
+                let settings = serde_json::to_string(&generation_config).unwrap_or_default();
+                println!("  {:<20} {}", format!("models.{}", model), settings.bright_magenta().bold());
+            }
+        }
+
+        println!("  Config files:");
+        let mut paths: Vec<PathBuf> = config::global_config_path().into_iter().collect();
+        paths.push(config::project_config_path(&self.working_directory));
+        for path in paths {
+            let status = if path.exists() { "loaded" } else { "not found" };
+            println!("    {} {}", path.display(), format!("({})", status).dimmed());
+        }
+    }
+
+    fn print_status_line(&self) {
+        let turn = &self.turn_usage.tokens;
+        println!(
+            "{}",
+            format!(
+                "{} · {} in ({} cached) · {} out · {} turn · {} session",
+                self.model,
+                cost::format_tokens(turn.input),
+                cost::format_tokens(turn.cache_read),
+                cost::format_tokens(turn.output),
+                cost::format_cost(self.turn_usage.cost),
+                cost::format_cost(self.session_usage.cost)
+            )
+            .dimmed()
+        );
+    }
+
+    fn print_context_usage(&self) {
+        let window_display = match context::context_window(&self.resolved_model()) {
+            Some(window) => format!("{} tokens", window),
+            None => format!("unknown, assuming {} tokens", context::DEFAULT_CONTEXT_WINDOW),
+        };
+        println!("  Context window:           {}", window_display.bright_magenta().bold());
+
+        let budget = self.context_budget();
+        let budget_display = match (budget, self.max_context_tokens) {
+            (0, _) => "unlimited".to_string(),
+            (n, Some(_)) => format!("{} tokens (--max-context-tokens)", n),
+            (n, None) => format!("{} tokens", n),
+        };
+        println!("  Context budget:           {}", budget_display.bright_magenta().bold());
+
+        if let Some(actual) = self.last_prompt_tokens {
+            println!("  Last request (reported):  {}", format!("{} tokens", actual).bright_magenta().bold());
+        }
+
+        let estimated = self.estimate_request_tokens(&self.build_request_history());
+        let estimated_display = if budget == 0 {
+            format!("~{} tokens", estimated)
         } else {
-            self.working_directory.join(requested_path)
-                .canonicalize()
-                .unwrap_or_else(|_| self.working_directory.join(requested_path))
+            format!("~{} / {} tokens ({}%)", estimated, budget, estimated * 100 / budget)
+        };
+        println!("  Next request (estimated): {}", estimated_display.bright_magenta().bold());
+    }
+
+    /// Summarises everything between the kept prefix and the last `keep_recent` messages with
+    /// the compaction model, folding in any earlier summary. Returns `false` when there is
+    /// nothing to compact.
+    async fn compact(&mut self, keep_recent: usize) -> Result<bool> {
+        let total = self.history.len();
+        let prefix_end = self.prefix_end();
+        let start = self.compaction.as_ref().map_or(prefix_end, |c| c.covered_until.max(prefix_end));
+        let end = compaction::tool_safe_start(&self.history, total.saturating_sub(keep_recent));
+        if end <= start {
+            return Ok(false);
+        }
+
+        let todos = self.todos.iter()
+            .map(|t| format!("- [{}] {}", if t.completed { "x" } else { " " }, t.task))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let model = self.compact_model.clone().unwrap_or_else(|| self.model.clone());
+        let request = compaction::build_compaction_request(
+            &model,
+            self.compaction.as_ref().map(|c| c.summary.as_str()),
+            &self.history[start..end],
+            &todos,
+        );
+
+        let tokens_before = self.untruncated_tokens();
+        let pb = Self::thinking_spinner()?;
+        pb.set_message("Compacting history...");
+        let response = self.client.instruct(&request).await;
+        pb.finish_and_clear();
+        let response = response.map_err(|e| anyhow!("{}", e))?;
+        if let Some(usage) = &response.usage {
+            let served_by = response.model.clone().unwrap_or(model);
+            self.record_cost(&served_by, usage);
+        }
+
+        let summary = compaction::summary_text(response.output)?;
+
+        let previously_compacted = self.compaction.as_ref().map_or(0, |c| c.compacted_messages);
+        self.compaction = Some(CompactionSummary {
+            covered_until: end,
+            compacted_messages: previously_compacted + (end - start),
+            summary,
+        });
+        self.save_session();
+
+        println!(
+            "{} Compacted {} messages into a summary (~{} -> ~{} tokens)",
+            "🗜️".bright_blue(),
+            (end - start).to_string().bright_magenta().bold(),
+            tokens_before,
+            self.untruncated_tokens()
+        );
+        Ok(true)
+    }
+
+    /// Compacts history before a request once it is estimated to exceed the auto-compact threshold.
+    /// A failed compaction only produces a warning; truncation still keeps the request within budget.
+    async fn auto_compact(&mut self) {
+        let threshold = self.auto_compact_threshold();
+        if threshold == 0 || self.untruncated_tokens() <= threshold {
+            return;
+        }
+
+        if let Err(e) = self.compact(compaction::KEEP_RECENT_MESSAGES).await {
+            eprintln!("{} Warning: Could not compact history, falling back to truncation: {}", "⚠️".yellow(), e);
+        }
+    }
+
+    /// Checks `request` against the permission rules, asking the user when they say so, and
+    /// records the decision in the audit log. `preview`, e.g. the diff of a file change, is
+    /// shown above the question.
+    fn authorize(&mut self, request: PermissionRequest, prompt: String, preview: Option<String>) -> Result<Authorization> {
+        let (decision, reason) = self.permissions.check(&request);
+        let (authorization, reason) = match decision {
+            Decision::Allow => (Authorization::Allowed, reason),
+            Decision::Deny => {
+                println!("{} Blocked by permission {}", "⛔".bright_red(), reason);
+                (Authorization::Denied(reason.clone()), reason)
+            }
+            Decision::Ask => {
+                if let Some(preview) = &preview {
+                    println!("{}", diff::colorize(preview));
+                }
+                let always = match &request.command {
+                    Some(command) => format!("Yes, and always allow `{}` this session", command),
+                    None => format!("Yes, and always allow {} this session", request.tool),
+                };
+                let choice = Select::new()
+                    .with_prompt(prompt)
+                    .items(&["Yes", always.as_str(), "No", "No, and tell ikode what to do instead"])
+                    .default(0)
+                    .interact()?;
+                match choice {
+                    0 => (Authorization::Allowed, format!("approved by user ({})", reason)),
+                    1 => {
+                        let rule = self.permissions.allow_for_session(&request);
+                        (Authorization::Allowed, format!("approved by user, {} for this session", rule))
+                    }
+                    2 => (Authorization::Rejected, format!("rejected by user ({})", reason)),
+                    _ => {
+                        let feedback: String = Input::new()
+                            .with_prompt("What should ikode do instead?")
+                            .allow_empty(true)
+                            .interact_text()?;
+                        let authorization = if feedback.trim().is_empty() {
+                            Authorization::Rejected
+                        } else {
+                            Authorization::Feedback(feedback.trim().to_string())
+                        };
+                        (authorization, format!("rejected by user with feedback ({})", reason))
+                    }
+                }
+            }
         };
 
-        if !canonical_path.starts_with(&self.working_directory) {
-            return Err(anyhow!(
-                "Path '{}' is outside the working directory. For security reasons, file operations are restricted to the working directory and its subdirectories.",
-                path
-            ));
+        if let Some(audit) = &self.audit {
+            let entry = AuditEntry {
+                timestamp: chrono::Utc::now(),
+                session_id: self.session_id.clone(),
+                tool: request.tool.clone(),
+                target: request.target().to_string(),
+                decision,
+                approved: authorization == Authorization::Allowed,
+                reason,
+            };
+            if let Err(e) = audit.record(&entry) {
+                eprintln!("{} Warning: Could not write audit log: {}", "⚠️".yellow(), e);
+            }
+        }
+        Ok(authorization)
+    }
+
+    /// `path` relative to the working directory with `/` separators, as permission rules match it.
+    fn relative_path(&self, path: &Path) -> String {
+        path.strip_prefix(&self.working_directory)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    fn print_permissions(&self) {
+        println!("{} Permission rules:", "🔐".bright_blue());
+        let mut any = false;
+        for rule in self.permissions.rules() {
+            println!("  {}", rule);
+            any = true;
+        }
+        for rule in self.permissions.session_rules() {
+            println!("  {}  {}", rule, "(this session)".dimmed());
+            any = true;
+        }
+        if !any {
+            println!("  {}", "No rules configured.".dimmed());
+        }
+        println!("  Anything else: {}", self.permissions.default_decision().to_string().bright_magenta().bold());
+        if let Some(audit) = &self.audit {
+            println!("  Audit log: {}", audit.path().display().to_string().dimmed());
+        }
+    }
+
+    fn print_jobs(&mut self) {
+        let jobs = self.jobs.jobs();
+        if jobs.is_empty() {
+            println!("{} No background jobs.", "📋".bright_blue());
+            return;
+        }
+        println!("{} Background jobs:", "📋".bright_blue());
+        for job in jobs {
+            let status = match job.status {
+                jobs::JobStatus::Running => job.status.to_string().bright_green(),
+                _ => job.status.to_string().dimmed(),
+            };
+            println!(
+                "  {} {}  {}  {}",
+                format!("[{}]", job.id).bright_magenta().bold(),
+                job.command.cyan(),
+                status,
+                format!("({}s, {} unread bytes)", job.runtime.as_secs(), job.unread).dimmed()
+            );
+        }
+    }
+
+    /// Kills the background jobs still running, e.g. before exiting.
+    async fn stop_jobs(&mut self) {
+        let stopped = self.jobs.kill_all().await;
+        if stopped > 0 {
+            println!("{} Stopped {} background job(s)", "🛑".bright_red(), stopped);
+        }
+    }
+
+    /// Launches the configured MCP servers and reports the tools each one offers. Servers
+    /// that fail are reported and left out; the session goes on without them.
+    async fn start_mcp_servers(&mut self) {
+        let configs = self.config.mcp_servers();
+        if configs.is_empty() {
+            return;
+        }
+        self.mcp = McpServers::start(configs, &self.working_directory).await;
+        for server in self.mcp.servers() {
+            match &server.state {
+                McpServerState::Running { tools, .. } => {
+                    println!("{} MCP server {}: {} tools", "🔌".bright_blue(), server.name.bright_magenta().bold(), tools.len())
+                }
+                McpServerState::Failed(e) => {
+                    println!("{} MCP server {} failed to start: {}", "⚠️".bright_yellow(), server.name.bright_magenta().bold(), e)
+                }
+            }
+        }
+    }
+
+    fn print_mcp_servers(&mut self) {
+        let servers = self.mcp.servers();
+        if servers.is_empty() {
+            println!("{} No MCP servers configured. Add them under [mcp_servers.<name>] in .ikode/config.toml.", "🔌".bright_blue());
+            return;
+        }
+        println!("{} MCP servers:", "🔌".bright_blue());
+        for server in servers {
+            let command = std::iter::once(&server.config.command).chain(&server.config.args).cloned().collect::<Vec<_>>().join(" ");
+            match &mut server.state {
+                McpServerState::Running { client, tools } => {
+                    let status = if client.is_running() { "running".bright_green() } else { "exited".bright_red() };
+                    println!(
+                        "  {}  {}  {}  {}",
+                        server.name.bright_magenta().bold(),
+                        status,
+                        client.server_info.cyan(),
+                        format!("({})", command).dimmed()
+                    );
+                    let names: Vec<String> = tools.iter().map(|tool| mcp::qualified_name(&server.name, &tool.name)).collect();
+                    println!("     {}", names.join(", ").dimmed());
+                }
+                McpServerState::Failed(e) => {
+                    println!("  {}  {}  {}", server.name.bright_magenta().bold(), "failed".bright_red(), format!("({})", command).dimmed());
+                    println!("     {}", e.dimmed());
+                }
+            }
+        }
+    }
+
+    fn print_hooks(&self) {
+        let hooks = self.hooks.list();
+        if hooks.is_empty() {
+            println!("{} No hooks configured. Add them as [[hooks]] in .ikode/config.toml.", "🪝".bright_blue());
+            return;
+        }
+        println!("{} Hooks, in the order they run:", "🪝".bright_blue());
+        for hook in hooks {
+            let tool = hook.tool.as_deref().map(|tool| format!(" ({})", tool)).unwrap_or_default();
+            println!("  {}{}  {}", hook.event.to_string().bright_magenta().bold(), tool.dimmed(), hook.command.cyan());
+        }
+    }
+
+    /// The built-in tools, then the project's custom tools, then those of the MCP servers.
+    fn tool_definitions(&self) -> Vec<GaiseTool> {
+        let mut definitions = tools::get_tools();
+        definitions.extend(self.custom_tools.iter().map(CustomTool::definition));
+        definitions.extend(self.mcp.tools());
+        if let Some(command) = &self.active_command {
+            definitions.retain(|tool| command.allows_tool(&tool.name));
+        }
+        definitions
+    }
+
+    fn print_checkpoints(&self) {
+        let checkpoints = self.checkpoints.list();
+        if checkpoints.is_empty() {
+            println!("{} No checkpoints: no files have been changed yet.", "⏪".bright_blue());
+            return;
+        }
+        println!("{} Checkpoints (restore with /restore {{n}}):", "⏪".bright_blue());
+        for (i, checkpoint) in checkpoints.iter().enumerate() {
+            let prompt: String = checkpoint.prompt.lines().next().unwrap_or_default().chars().take(60).collect();
+            println!(
+                "  {}) {}  {}",
+                i + 1,
+                checkpoint.created_at.with_timezone(&chrono::Local).format("%H:%M:%S"),
+                if prompt.is_empty() { "(no prompt)".dimmed().to_string() } else { prompt }
+            );
+            let mut paths: Vec<String> = checkpoint.files.iter().map(|f| self.relative_path(&f.path)).collect();
+            paths.extend(checkpoint.directories.iter().map(|d| format!("{}/", self.relative_path(&d.path))));
+            println!("     {}", paths.join(", ").dimmed());
+        }
+    }
+
+    /// Reports the files a restore put back and, if asked, rewinds the conversation to
+    /// before the restored turn.
+    fn report_restore(&mut self, restored: Restored, rewind_history: bool) {
+        let mut paths: Vec<String> = restored.files.iter().map(|f| self.relative_path(f)).collect();
+        paths.extend(restored.directories.iter().map(|d| format!("{}/", self.relative_path(d))));
+        println!("{} Restored {} path(s): {}", "⏪".bright_green(), paths.len(), paths.join(", "));
+        if !rewind_history {
+            return;
+        }
+        match restored.history_len {
+            Some(len) if len <= self.history.len() => {
+                self.history.truncate(len);
+                if self.compaction.as_ref().is_some_and(|c| c.covered_until > len) {
+                    self.compaction = None;
+                }
+                self.last_prompt_tokens = None;
+                self.save_session();
+                println!("{} Conversation rewound to {} messages.", "⏪".bright_green(), len);
+            }
+            _ => println!(
+                "{} The conversation was not rewound: it was cleared or replaced since that checkpoint.",
+                "⚠️".bright_yellow()
+            ),
         }
+    }
 
-        Ok(canonical_path)
+    fn validate_path(&self, path: &str) -> Result<PathBuf> {
+        paths::validate_path(&self.working_directory, path)
     }
 
 
@@ -241,62 +1103,91 @@ impl App {
         loop {
             print!("{}", "> ".bright_blue().bold());
             io::stdout().flush()?;
-            let mut input = String::new();
-            io::stdin().read_line(&mut input)?;
+            // Read on a blocking thread so that Ctrl-C, handled in `main`, is noticed at the prompt too.
+            let (read, input) = tokio::task::spawn_blocking(|| {
+                let mut input = String::new();
+                io::stdin().read_line(&mut input).map(|read| (read, input))
+            })
+            .await??;
+            if read == 0 {
+                println!();
+                break;
+            }
             let input = input.trim();
 
             if input.is_empty() {
                 continue;
             }
 
-            if input == "/exit" {
-                println!("{}", "👋 Goodbye!".bright_yellow());
-                break;
-            }
-            if input == "/help" {
-                println!("{}", "\nAvailable commands:".bright_green().bold());
-                println!("  {} - Display this help message", "/help".cyan());
-                println!("  {} - Display the current model", "/model".cyan());
-                println!("  {} {{model}} - Switch to a different model", "/model".cyan());
-                println!("  {} - Show history settings and stats", "/history".cyan());
-                println!("  {} {{n}} - Set max history messages (0 = unlimited)", "/max-history".cyan());
-                println!("  {} {{n}} - Set number of prefix messages to always keep", "/prefix-keep".cyan());
-                println!("  {} - Reset the conversation history", "/clear".cyan());
-                println!("  {} - Clear the terminal screen", "/cls".cyan());
-                println!("  {} - Quit the interactive session\n", "/exit".cyan());
+            let Some((command, arguments)) = self.commands.parse(input) else {
+                self.process_prompt(input).await?;
                 continue;
+            };
+            match command {
+                SlashCommand::Builtin(Builtin::Exit) => {
+                    self.save_session();
+                    println!("{}", "👋 Goodbye!".bright_yellow());
+                    break;
+                }
+                SlashCommand::Builtin(builtin) => self.run_builtin(builtin, arguments).await,
+                SlashCommand::Custom(command) => {
+                    let command = command.clone();
+                    self.run_custom_command(&command, arguments).await?;
+                }
             }
-            if input == "/cls" || input == "/clear_screen" {
-                Self::clear_screen();
-                continue;
+        }
+        Ok(())
+    }
+
+    fn print_help(&self) {
+        println!("{}", "\nAvailable commands:".bright_green().bold());
+        for command in BUILTIN_COMMANDS {
+            for (arguments, description) in command.usage {
+                let arguments = if arguments.is_empty() { String::new() } else { format!(" {}", arguments) };
+                println!("  {}{} - {}", format!("/{}", command.name).cyan(), arguments, description);
             }
-            if input == "/clear" {
-                self.history = vec![GaiseMessage {
-                    role: "system".to_string(),
-                    content: Some(OneOrMany::One(GaiseContent::Text { text: self.system_prompt.clone() })),
-                    tool_calls: None,
-                    tool_call_id: None,
-                }];
+        }
+        if !self.commands.custom().is_empty() {
+            println!("{}", "\nCustom commands:".bright_green().bold());
+            for command in self.commands.custom() {
+                let hint = command.argument_hint.as_deref().map(|hint| format!(" {}", hint)).unwrap_or_default();
+                println!("  {}{} - {} {}", format!("/{}", command.name).cyan(), hint, command.summary(), format!("({})", command.source.display()).dimmed());
+            }
+        }
+        println!();
+    }
+
+    /// Runs a built-in command other than `/exit`, which ends the loop.
+    async fn run_builtin(&mut self, builtin: Builtin, arguments: &str) {
+        match builtin {
+            Builtin::Help => self.print_help(),
+            Builtin::ClearScreen => Self::clear_screen(),
+            Builtin::Clear => {
+                self.save_session();
+                self.end_session("clear").await;
+                self.history = vec![self.system_message()];
+                self.todos.clear();
+                self.compaction = None;
+                self.checkpoints.forget_history();
+                self.session_usage = UsageTotals::default();
+                self.session_id = Uuid::new_v4().to_string();
+                self.session_created_at = chrono::Utc::now();
                 self.session_cache_key = Uuid::new_v4().to_string();
                 println!("{}", "🧹 History cleared.".bright_cyan());
-                continue;
             }
-            if input == "/model" {
+            Builtin::Sessions if arguments.is_empty() => self.list_sessions(),
+            Builtin::Sessions => self.load_session_from_command(arguments),
+            Builtin::Model if arguments.is_empty() => {
                 println!("{} Current model: {}", "🤖".bright_blue(), self.model.bright_magenta().bold());
-                continue;
             }
-            if input.starts_with("/model ") {
-                let new_model = input.trim_start_matches("/model ").trim();
-                if !new_model.is_empty() {
-                    self.model = new_model.to_string();
-                    println!("{} Model changed to: {}", "✅".bright_green(), self.model.bright_magenta().bold());
-                } else {
-                    println!("{} Please specify a model name. Usage: /model {{model_name}}", "⚠️".bright_yellow());
-                }
-                continue;
+            Builtin::Model => {
+                self.model = arguments.to_string();
+                self.config_sources.insert("model", ConfigSource::Command);
+                self.calibration = TokenCalibration::default();
+                self.last_prompt_tokens = None;
+                println!("{} Model changed to: {}", "✅".bright_green(), self.model.bright_magenta().bold());
             }
-
-            if input == "/history" {
+            Builtin::History => {
                 let limit_display = if self.max_history == 0 {
                     "unlimited".to_string()
                 } else {
@@ -306,115 +1197,727 @@ impl App {
                 println!("  Max messages per request: {}", limit_display.bright_magenta().bold());
                 println!("  Prefix keep:              {}", self.prefix_keep.to_string().bright_magenta().bold());
                 println!("  Total messages stored:    {}", self.history.len().to_string().bright_magenta().bold());
-                continue;
+                self.print_context_usage();
+                let auto_compact_display = match self.auto_compact_threshold() {
+                    0 => "disabled".to_string(),
+                    n => format!("above ~{} tokens", n),
+                };
+                println!("  Auto-compact:             {}", auto_compact_display.bright_magenta().bold());
+                if let Some(summary) = &self.compaction {
+                    println!("  Compacted messages:       {}", summary.compacted_messages.to_string().bright_magenta().bold());
+                }
             }
-            if input.starts_with("/max-history ") {
-                let value = input.trim_start_matches("/max-history ").trim();
-                match value.parse::<usize>() {
-                    Ok(n) => {
-                        self.max_history = n;
-                        let display = if n == 0 { "unlimited".to_string() } else { n.to_string() };
-                        println!("{} Max history set to: {}", "✅".bright_green(), display.bright_magenta().bold());
+            Builtin::Cost => self.print_cost(),
+            Builtin::Config => self.print_config(),
+            Builtin::Permissions => self.print_permissions(),
+            Builtin::Jobs if arguments.is_empty() => self.print_jobs(),
+            Builtin::Jobs => match arguments.strip_prefix("kill").map(|id| id.trim().parse::<usize>()) {
+                Some(Ok(id)) => match self.jobs.kill(id).await {
+                    Ok(message) => println!("{} {}", "🛑".bright_red(), message),
+                    Err(e) => println!("{} {}", "⚠️".bright_yellow(), e),
+                },
+                _ => println!("{} Invalid job id. Usage: /jobs kill {{id}}", "⚠️".bright_yellow()),
+            },
+            Builtin::Mcp => self.print_mcp_servers(),
+            Builtin::Hooks => self.print_hooks(),
+            Builtin::Checkpoints => self.print_checkpoints(),
+            Builtin::Undo => {
+                if !arguments.is_empty() && arguments != "--history" {
+                    println!("{} Usage: /undo [--history]", "⚠️".bright_yellow());
+                    return;
+                }
+                match self.checkpoints.undo() {
+                    Ok(Some(restored)) => self.report_restore(restored, !arguments.is_empty()),
+                    Ok(None) => println!("{} Nothing to undo: no files have been changed yet.", "⏪".bright_blue()),
+                    Err(e) => println!("{} Could not undo: {}", "⚠️".bright_yellow(), e),
+                }
+            }
+            Builtin::Restore => {
+                let mut options = arguments.split_whitespace();
+                let number = options.next().and_then(|n| n.parse::<usize>().ok());
+                let rewind = match (options.next(), options.next()) {
+                    (None, None) => false,
+                    (Some("--history"), None) => true,
+                    _ => {
+                        println!("{} Usage: /restore {{n}} [--history]", "⚠️".bright_yellow());
+                        return;
                     }
-                    Err(_) => println!("{} Invalid number. Usage: /max-history {{number}}", "⚠️".bright_yellow()),
+                };
+                match number.map(|n| self.checkpoints.restore(n)) {
+                    Some(Ok(restored)) => self.report_restore(restored, rewind),
+                    Some(Err(e)) => println!("{} Could not restore: {}", "⚠️".bright_yellow(), e),
+                    None => println!("{} Invalid checkpoint. Usage: /restore {{n}} [--history]", "⚠️".bright_yellow()),
                 }
-                continue;
             }
-            if input.starts_with("/prefix-keep ") {
-                let value = input.trim_start_matches("/prefix-keep ").trim();
-                match value.parse::<usize>() {
-                    Ok(n) => {
-                        self.prefix_keep = n;
-                        println!("{} Prefix keep set to: {}", "✅".bright_green(), n.to_string().bright_magenta().bold());
+            Builtin::MaxCost if arguments.is_empty() => {
+                let limit_display = self.max_cost.map_or("none".to_string(), cost::format_cost);
+                println!("{} Cost limit: {}", "💸".bright_blue(), limit_display.bright_magenta().bold());
+            }
+            Builtin::MaxCost => match arguments.trim_start_matches('$').parse::<f64>() {
+                Ok(limit) if limit >= 0.0 => {
+                    self.max_cost = if limit == 0.0 { None } else { Some(limit) };
+                    self.config_sources.insert("max_cost", ConfigSource::Command);
+                    let display = self.max_cost.map_or("none".to_string(), cost::format_cost);
+                    println!("{} Cost limit set to: {}", "✅".bright_green(), display.bright_magenta().bold());
+                }
+                _ => println!("{} Invalid amount. Usage: /max-cost {{usd}}", "⚠️".bright_yellow()),
+            },
+            Builtin::Compact => match self.compact(compaction::KEEP_RECENT_MESSAGES).await {
+                Ok(true) => {}
+                Ok(false) => println!("{} Nothing to compact yet.", "🗜️".bright_blue()),
+                Err(e) => println!("{} Could not compact history: {}", "⚠️".bright_yellow(), e),
+            },
+            Builtin::MaxHistory => match arguments.parse::<usize>() {
+                Ok(n) => {
+                    self.max_history = n;
+                    self.config_sources.insert("max_history", ConfigSource::Command);
+                    let display = if n == 0 { "unlimited".to_string() } else { n.to_string() };
+                    println!("{} Max history set to: {}", "✅".bright_green(), display.bright_magenta().bold());
+                }
+                Err(_) => println!("{} Invalid number. Usage: /max-history {{number}}", "⚠️".bright_yellow()),
+            },
+            Builtin::PrefixKeep => match arguments.parse::<usize>() {
+                Ok(n) => {
+                    self.prefix_keep = n;
+                    self.config_sources.insert("prefix_keep", ConfigSource::Command);
+                    println!("{} Prefix keep set to: {}", "✅".bright_green(), n.to_string().bright_magenta().bold());
+                }
+                Err(_) => println!("{} Invalid number. Usage: /prefix-keep {{number}}", "⚠️".bright_yellow()),
+            },
+            Builtin::Exit => {}
+        }
+    }
+
+    /// Runs a custom command's prompt as a turn, with its model and only its allowed tools.
+    async fn run_custom_command(&mut self, command: &CustomCommand, arguments: &str) -> Result<()> {
+        let prompt = command.render(arguments);
+        let previous_model = command.model.clone().map(|model| std::mem::replace(&mut self.model, model));
+        let previous_calibration = previous_model.as_ref().map(|_| std::mem::take(&mut self.calibration));
+        if let Some(model) = &command.model {
+            println!("{} Running /{} with {}", "📜".bright_blue(), command.name, model.bright_magenta().bold());
+        }
+        self.active_command = Some(command.clone());
+
+        let result = self.process_prompt(&prompt).await;
+
+        self.active_command = None;
+        if let (Some(model), Some(calibration)) = (previous_model, previous_calibration) {
+            self.model = model;
+            self.calibration = calibration;
+            self.last_prompt_tokens = None;
+            self.save_session();
+        }
+        result
+    }
+
+    /// Runs `prompt` as a turn, or as the custom command it names.
+    async fn submit(&mut self, prompt: &str) -> Result<()> {
+        if let Some((SlashCommand::Custom(command), arguments)) = self.commands.parse(prompt) {
+            let command = command.clone();
+            return self.run_custom_command(&command, arguments).await;
+        }
+        self.process_prompt(prompt).await
+    }
+
+    async fn process_prompt(&mut self, prompt: &str) -> Result<()> {
+        let mut text = prompt.to_string();
+        if self.hooks.has(HookEvent::UserPromptSubmit) {
+            let outcome = self.hooks.run(HookEvent::UserPromptSubmit, None, self.hook_input(json!({ "prompt": prompt }))).await;
+            Self::report_hook_errors(&outcome);
+            if let Some(reason) = outcome.block {
+                println!("{} Prompt blocked by hook: {}", "⛔".bright_red(), reason);
+                return Ok(());
+            }
+            for context in outcome.context {
+                text.push_str(&format!("\n\n{}", context));
+            }
+        }
+
+        self.checkpoints.begin_turn(prompt, self.history.len());
+        self.history.push(GaiseMessage {
+            role: "user".to_string(),
+            content: Some(OneOrMany::One(GaiseContent::Text { text })),
+            tool_calls: None,
+            tool_call_id: None,
+        });
+        self.turn_usage = UsageTotals::default();
+
+        let result = self.agent_loop().await;
+        self.save_session();
+        if self.status_line {
+            self.print_status_line();
+        }
+        result
+    }
+
+    /// Sends requests and runs the tool calls they ask for until the model answers without
+    /// calling a tool, or the cost limit is reached.
+    async fn agent_loop(&mut self) -> Result<()> {
+        loop {
+            if let Some(limit) = self.max_cost {
+                if self.session_usage.cost >= limit {
+                    println!(
+                        "{} Cost limit of {} reached (session total {}). Stopping; raise it with /max-cost {{usd}}.",
+                        "💸".bright_yellow(),
+                        cost::format_cost(limit),
+                        cost::format_cost(self.session_usage.cost)
+                    );
+                    return Ok(());
+                }
+            }
+
+            self.auto_compact().await;
+
+            let mut generation_config = self.config.generation_config(&self.model);
+            if self.resolved_model().starts_with("openai::gpt-5") {
+                generation_config = Some(GaiseGenerationConfig {
+                    cache_key: Some(self.session_cache_key.clone()),
+                    ..generation_config.unwrap_or_default()
+                });
+            }
+
+            let request_history = self.build_request_history();
+            let estimated_tokens = compaction::estimate_tokens(&request_history);
+            let request = GaiseInstructRequest {
+                input: OneOrMany::Many(request_history),
+                model: self.model.clone(),
+                tools: Some(self.tool_definitions()),
+                generation_config,
+                fallback_models: (!self.fallback_models.is_empty()).then(|| self.fallback_models.clone()),
+                ..Default::default()
+            };
+
+            let (assistant_messages, usage, served_by) = if self.stream {
+                self.stream_response(&request).await?
+            } else {
+                self.instruct_response(&request).await?
+            };
+            if let Some(usage) = &usage {
+                self.record_usage(estimated_tokens, usage);
+                let model = served_by.unwrap_or_else(|| self.model.clone());
+                self.record_cost(&model, usage);
+            }
+
+            for assistant_message in assistant_messages {
+                self.history.push(assistant_message.clone());
+
+                if let Some(tool_calls) = assistant_message.tool_calls {
+                    let results = self.run_tool_calls(&tool_calls).await?;
+                    for (tool_call, result) in tool_calls.into_iter().zip(results) {
+                        self.history.push(GaiseMessage {
+                            role: "tool".to_string(),
+                            content: Some(OneOrMany::One(GaiseContent::Text { text: result })),
+                            tool_calls: None,
+                            tool_call_id: Some(tool_call.id.clone()),
+                        });
+                    }
+                } else {
+                    return Ok(());
+                }
+            }
+
+            self.save_session();
+        }
+    }
+
+    fn thinking_spinner() -> Result<ProgressBar> {
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(ProgressStyle::default_spinner()
+            .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
+            .template("{spinner:.green} {msg}")?);
+        pb.set_message("Thinking...");
+        pb.enable_steady_tick(Duration::from_millis(100));
+        Ok(pb)
+    }
+
+    /// Sends the request with `instruct` and prints the text once the full response has arrived.
+    /// Returns the assistant messages together with the usage reported by the provider
+    /// and the model that served the request.
+    async fn instruct_response(&self, request: &GaiseInstructRequest) -> Result<(Vec<GaiseMessage>, Option<GaiseUsage>, Option<String>)> {
+        let pb = Self::thinking_spinner()?;
+        let response = self.client.instruct(request).await;
+        pb.finish_and_clear();
+        let response = response.map_err(|e| anyhow!("{}", e))?;
+
+        let assistant_messages = match response.output {
+            OneOrMany::One(m) => vec![m],
+            OneOrMany::Many(ms) => ms,
+        };
+
+        for assistant_message in &assistant_messages {
+            match &assistant_message.content {
+                Some(OneOrMany::One(GaiseContent::Text { text })) => {
+                    println!("{}", text);
+                }
+                Some(OneOrMany::Many(parts)) => {
+                    for part in parts {
+                        if let GaiseContent::Text { text } = part {
+                            println!("{}", text);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok((assistant_messages, response.usage, response.model))
+    }
+
+    /// Sends the request with `instruct_stream`, printing text deltas as they arrive.
+    /// Tool calls are rebuilt from their fragments and returned with the final message.
+    async fn stream_response(&self, request: &GaiseInstructRequest) -> Result<(Vec<GaiseMessage>, Option<GaiseUsage>, Option<String>)> {
+        let pb = Self::thinking_spinner()?;
+        let mut stream = match self.client.instruct_stream(request).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                pb.finish_and_clear();
+                return Err(anyhow!("{}", e));
+            }
+        };
+
+        let mut accumulator = GaiseStreamAccumulator::new();
+        let mut printed = false;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    pb.finish_and_clear();
+                    if printed {
+                        println!();
+                    }
+                    return Err(anyhow!("{}", e));
+                }
+            };
+
+            if let GaiseStreamChunk::Text(text) = &chunk.chunk {
+                if !printed {
+                    pb.finish_and_clear();
+                    printed = true;
+                }
+                print!("{}", text);
+                io::stdout().flush()?;
+            }
+
+            accumulator.push(&chunk);
+        }
+
+        pb.finish_and_clear();
+        if printed {
+            println!();
+        }
+
+        let usage = accumulator.usage.take();
+        let served_by = accumulator.model.take();
+        Ok((vec![accumulator.finish()], usage, served_by))
+    }
+
+    /// The fields every hook gets on stdin, followed by the event's own.
+    fn hook_input(&self, fields: serde_json::Value) -> serde_json::Value {
+        let mut input = json!({
+            "session_id": self.session_id,
+            "working_directory": self.working_directory,
+        });
+        if let (Some(input), serde_json::Value::Object(fields)) = (input.as_object_mut(), fields) {
+            input.extend(fields);
+        }
+        input
+    }
+
+    fn report_hook_errors(outcome: &HookOutcome) {
+        for error in &outcome.errors {
+            eprintln!("{} Warning: Hook failed: {}", "⚠️".yellow(), error);
+        }
+    }
+
+    /// Answers the tool calls an interrupted turn left without a result, for a valid history.
+    fn close_interrupted_turn(&mut self) {
+        let Some(index) = self.history.iter().rposition(|m| m.role == "assistant") else {
+            return;
+        };
+        let Some(tool_calls) = self.history[index].tool_calls.clone() else {
+            return;
+        };
+        let answered: Vec<String> = self.history[index + 1..].iter().filter_map(|m| m.tool_call_id.clone()).collect();
+        for tool_call in tool_calls.into_iter().filter(|call| !answered.contains(&call.id)) {
+            self.history.push(GaiseMessage {
+                role: "tool".to_string(),
+                content: Some(OneOrMany::One(GaiseContent::Text {
+                    text: "Error: Interrupted by the user (Ctrl-C) before this tool call finished.".to_string(),
+                })),
+                tool_calls: None,
+                tool_call_id: Some(tool_call.id),
+            });
+        }
+    }
+
+    /// Runs the `SessionEnd` hooks, e.g. on exit (`reason` "exit") or `/clear`.
+    async fn end_session(&self, reason: &str) {
+        if self.hooks.has(HookEvent::SessionEnd) {
+            let outcome = self.hooks.run(HookEvent::SessionEnd, None, self.hook_input(json!({ "reason": reason }))).await;
+            Self::report_hook_errors(&outcome);
+        }
+    }
+
+    /// Runs the tool calls of one response in order. Consecutive `spawn_agent` calls run at
+    /// once, at their place in the order.
+    async fn run_tool_calls(&mut self, tool_calls: &[GaiseToolCall]) -> Result<Vec<String>> {
+        let mut results = Vec::new();
+        for group in agents::group_agent_calls(tool_calls) {
+            if group[0].function.name == agents::SPAWN_AGENT {
+                let agent_calls: Vec<&GaiseToolCall> = group.iter().collect();
+                results.extend(self.run_sub_agents(&agent_calls).await);
+            } else {
+                for tool_call in group {
+                    results.push(self.handle_tool_call(tool_call).await?);
+                }
+            }
+        }
+        Ok(results)
+    }
+
+    /// Runs a tool call between its `PreToolUse` and `PostToolUse` hooks, which may block
+    /// it, change its arguments, or add to its result.
+    async fn handle_tool_call(&mut self, tool_call: &GaiseToolCall) -> Result<String> {
+        let (call, context) = match self.before_tool(tool_call).await {
+            Ok(prepared) => prepared,
+            Err(blocked) => return Ok(blocked),
+        };
+        let result = self.run_tool(&call).await?;
+        Ok(self.after_tool(&call, result, context).await)
+    }
+
+    /// Checks a tool call against the running custom command and runs its `PreToolUse` hooks.
+    /// Returns the call to make, with any arguments the hooks changed, and notes for its
+    /// result; or, as the error, the result to give the model instead of making it.
+    async fn before_tool(&self, tool_call: &GaiseToolCall) -> std::result::Result<(GaiseToolCall, Vec<String>), String> {
+        let name = tool_call.function.name.as_str();
+        if let Some(command) = self.active_command.as_ref().filter(|command| !command.allows_tool(name)) {
+            return Err(format!("Error: The /{} command does not allow the {} tool.", command.name, name));
+        }
+
+        let mut call = tool_call.clone();
+        let mut context = Vec::new();
+        if self.hooks.has(HookEvent::PreToolUse) {
+            let input = self.hook_input(json!({ "tool_call": &call, "tool_input": Self::tool_input(&call) }));
+            let outcome = self.hooks.run(HookEvent::PreToolUse, Some(name), input).await;
+            Self::report_hook_errors(&outcome);
+            if let Some(reason) = outcome.block {
+                println!("{} Tool call {} blocked by hook: {}", "⛔".bright_red(), name.bright_magenta(), reason);
+                return Err(format!("Tool call blocked by hook: {}", reason));
+            }
+            if let Some(arguments) = outcome.arguments {
+                println!("{} A hook changed the arguments of {}", "🪝".bright_blue(), name.bright_magenta());
+                context.push(format!("Note: a hook changed the arguments of this call to: {}", arguments));
+                call.function.arguments = Some(arguments.to_string());
+            }
+            context.extend(outcome.context);
+        }
+        Ok((call, context))
+    }
+
+    /// Runs the `PostToolUse` hooks of a call and adds their notes, and those of
+    /// `before_tool`, to its result.
+    async fn after_tool(&self, call: &GaiseToolCall, mut result: String, mut context: Vec<String>) -> String {
+        let name = call.function.name.as_str();
+        if self.hooks.has(HookEvent::PostToolUse) {
+            let input = self.hook_input(json!({ "tool_call": call, "tool_input": Self::tool_input(call), "tool_result": result }));
+            let outcome = self.hooks.run(HookEvent::PostToolUse, Some(name), input).await;
+            Self::report_hook_errors(&outcome);
+            // The call has already happened; a block becomes feedback for the model.
+            if let Some(reason) = outcome.block {
+                println!("{} Hook feedback on {}: {}", "🪝".bright_blue(), name.bright_magenta(), reason);
+                context.push(format!("Hook feedback: {}", reason));
+            }
+            context.extend(outcome.context);
+        }
+        for text in context {
+            result.push_str(&format!("\n\n{}", text));
+        }
+        result
+    }
+
+    /// The arguments of a call as JSON for hooks, or as a string if they are not valid JSON.
+    fn tool_input(call: &GaiseToolCall) -> serde_json::Value {
+        let arguments = call.function.arguments.as_deref().unwrap_or("{}");
+        serde_json::from_str(arguments).unwrap_or_else(|_| serde_json::Value::String(arguments.to_string()))
+    }
+
+    /// Starts a sub-agent for each `spawn_agent` call and runs them concurrently. Returns the
+    /// result of each call, in order.
+    async fn run_sub_agents(&mut self, tool_calls: &[&GaiseToolCall]) -> Vec<String> {
+        let mut prepared = Vec::new();
+        for tool_call in tool_calls {
+            let (call, context) = match self.before_tool(tool_call).await {
+                Ok(ready) => ready,
+                Err(blocked) => {
+                    prepared.push(Err(blocked));
+                    continue;
+                }
+            };
+            let default_model = self.agent_model.clone().unwrap_or_else(|| self.model.clone());
+            let agent = serde_json::from_str::<SpawnAgentArgs>(call.function.arguments.as_deref().unwrap_or("{}"))
+                .map_err(anyhow::Error::from)
+                .and_then(|args| SubAgent::new(self.agents_spawned + 1, args, &self.system_prompt, &default_model));
+            match agent {
+                Ok(agent) => {
+                    self.agents_spawned += 1;
+                    println!(
+                        "{} Starting agent {} ({}): {}",
+                        "🤖".bright_blue(),
+                        agent.number.to_string().bold(),
+                        agent.model.bright_magenta(),
+                        agent.task.lines().next().unwrap_or_default().dimmed()
+                    );
+                    prepared.push(Ok((call, context, agent)));
+                }
+                Err(e) => prepared.push(Err(format!("Error: Could not start the sub-agent: {}", e))),
+            }
+        }
+
+        let app = &*self;
+        let spent = Mutex::new(0.0);
+        let spent = &spent;
+        let runs = join_all(prepared.iter().map(|prepared| async move {
+            match prepared {
+                Ok((_, _, agent)) => app.run_sub_agent(agent.clone(), spent).await,
+                Err(_) => (String::new(), Vec::new()),
+            }
+        }))
+        .await;
+
+        let mut results = Vec::new();
+        for (prepared, (answer, usage)) in prepared.into_iter().zip(runs) {
+            let result = match prepared {
+                Ok((call, context, _)) => {
+                    for (model, usage) in &usage {
+                        self.record_cost(model, usage);
+                    }
+                    self.after_tool(&call, answer, context).await
+                }
+                Err(result) => result,
+            };
+            results.push(result);
+        }
+        results
+    }
+
+    /// Runs a sub-agent until it answers without calling a tool. Returns its answer, or an
+    /// error for the agent that started it, and the usage of its requests by the model that
+    /// served each one. `spent` adds up the cost of the sub-agents running alongside, which
+    /// counts against the cost limit before it reaches the session total.
+    async fn run_sub_agent(&self, mut agent: SubAgent, spent: &Mutex<f64>) -> (String, Vec<(String, GaiseUsage)>) {
+        let mut usage = Vec::new();
+        loop {
+            if let Some(limit) = self.max_cost {
+                let total = self.session_usage.cost + *spent.lock().unwrap();
+                if total >= limit {
+                    println!("{} Agent {} stopped: cost limit of {} reached", "💸".bright_yellow(), agent.number, cost::format_cost(limit));
+                    return (format!("Error: The sub-agent was stopped because the cost limit of {} was reached.", cost::format_cost(limit)), usage);
+                }
+            }
+            self.auto_compact_sub_agent(&mut agent, spent, &mut usage).await;
+            let budget = self.context_budget_for(&agent.model);
+            let Some(request) = agent.next_request(self.config.generation_config(&agent.model), budget, &self.calibration) else {
+                println!("{} Agent {} stopped after {} requests without an answer", "⚠️".bright_yellow(), agent.number, agents::MAX_REQUESTS);
+                return (format!("Error: The sub-agent did not finish within {} requests.", agents::MAX_REQUESTS), usage);
+            };
+            let response = match self.client.instruct(&request).await {
+                Ok(response) => response,
+                Err(e) => {
+                    println!("{} Agent {} failed: {}", "⚠️".bright_yellow(), agent.number, e);
+                    return (format!("Error: The sub-agent's request failed: {}", e), usage);
+                }
+            };
+            if let Some(response_usage) = response.usage {
+                let served_by = response.model.unwrap_or_else(|| agent.model.clone());
+                self.record_sub_agent_usage(served_by, response_usage, spent, &mut usage);
+            }
+
+            let messages = match response.output {
+                OneOrMany::One(m) => vec![m],
+                OneOrMany::Many(ms) => ms,
+            };
+            let tool_calls: Vec<GaiseToolCall> = messages.iter().flat_map(|m| m.tool_calls.clone().unwrap_or_default()).collect();
+            agent.history.extend(messages);
+            if tool_calls.is_empty() {
+                println!("{} Agent {} finished after {} requests", "🤖".bright_blue(), agent.number.to_string().bold(), agent.requests);
+                return (agent.final_answer(), usage);
+            }
+
+            for tool_call in tool_calls {
+                let result = if !agent.allows(&tool_call.function.name) {
+                    format!("Error: Sub-agents can only use {}.", agent.tools.join(", "))
+                } else {
+                    match self.before_tool(&tool_call).await {
+                        Ok((call, context)) => {
+                            let result = self.run_read_only_tool(&call).unwrap_or_else(|e| format!("Error: {}", e));
+                            self.after_tool(&call, result, context).await
+                        }
+                        Err(blocked) => blocked,
+                    }
+                };
+                agent.push_tool_result(&tool_call.id, result);
+            }
+        }
+    }
+
+    /// Compacts a sub-agent's history once it is estimated to exceed the auto-compact threshold,
+    /// like `auto_compact` does for the main history. A failed compaction only produces a
+    /// warning; truncation still keeps the sub-agent's requests within budget.
+    async fn auto_compact_sub_agent(&self, agent: &mut SubAgent, spent: &Mutex<f64>, usage: &mut Vec<(String, GaiseUsage)>) {
+        let threshold = self.auto_compact_threshold_for(&agent.model);
+        if threshold == 0 || self.estimate_request_tokens(&agent.request_history(0, &self.calibration)) <= threshold {
+            return;
+        }
+        let model = self.compact_model.clone().unwrap_or_else(|| agent.model.clone());
+        let Some((request, covered_until)) = agent.compaction_request(&model, compaction::KEEP_RECENT_MESSAGES) else {
+            return;
+        };
+
+        let response = match self.client.instruct(&request).await {
+            Ok(response) => response,
+            Err(e) => {
+                eprintln!("{} Warning: Agent {} could not compact its history, falling back to truncation: {}", "⚠️".yellow(), agent.number, e);
+                return;
+            }
+        };
+        if let Some(response_usage) = response.usage {
+            self.record_sub_agent_usage(response.model.unwrap_or(model), response_usage, spent, usage);
+        }
+        match compaction::summary_text(response.output) {
+            Ok(summary) => {
+                agent.compact(covered_until, summary);
+                println!("{} Agent {} compacted its history into a summary", "🗜️".bright_blue(), agent.number);
+            }
+            Err(e) => eprintln!("{} Warning: Agent {} could not compact its history, falling back to truncation: {}", "⚠️".yellow(), agent.number, e),
+        }
+    }
+
+    /// Adds the usage of a sub-agent's request to `usage` and its cost to `spent`.
+    fn record_sub_agent_usage(&self, model: String, response_usage: GaiseUsage, spent: &Mutex<f64>, usage: &mut Vec<(String, GaiseUsage)>) {
+        if let Some(price) = self.prices.price(&self.client.provider_model(&model)) {
+            *spent.lock().unwrap() += price.cost(&TokenUsage::from_usage(&response_usage));
+        }
+        usage.push((model, response_usage));
+    }
+
+    /// Runs one of the tools that only read, which sub-agents may use as well.
+    fn run_read_only_tool(&self, tool_call: &GaiseToolCall) -> Result<String> {
+        let arguments = &tool_call.function.arguments;
+        match tool_call.function.name.as_str() {
+            "read_file" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: ReadFileArgs = serde_json::from_str(args_str)?;
+                println!("{} Reading file: {}", "📖".bright_cyan(), args.path.bold().bright_cyan());
+
+                match self.validate_path(&args.path) {
+                    Ok(validated_path) => {
+                        let metadata = match fs::metadata(&validated_path) {
+                            Ok(m) => m,
+                            Err(e) => return Ok(format!("Error reading file: {}", e)),
+                        };
+
+                        const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+                        if metadata.len() > MAX_FILE_SIZE {
+                            return Ok(format!(
+                                "Error: file is too large ({:.1} MB). Maximum supported size is {:.0} MB.",
+                                metadata.len() as f64 / (1024.0 * 1024.0),
+                                MAX_FILE_SIZE as f64 / (1024.0 * 1024.0)
+                            ));
+                        }
+
+                        let content = match fs::read_to_string(&validated_path) {
+                            Ok(c) => c,
+                            Err(e) => return Ok(format!("Error reading file: {}", e)),
+                        };
+
+                        let total_lines = content.lines().count();
+                        let offset = args.offset.unwrap_or(1).max(1);
+                        let limit = args.limit.unwrap_or(2000);
+
+                        let selected: Vec<String> = content
+                            .lines()
+                            .enumerate()
+                            .skip(offset - 1)
+                            .take(limit)
+                            .map(|(i, line)| format!("{:>6}\t{}", i + 1, line))
+                            .collect();
+
+                        let mut result = selected.join("\n");
+
+                        let last_shown = (offset - 1 + selected.len()).min(total_lines);
+                        if last_shown < total_lines {
+                            result.push_str(&format!(
+                                "\n\n... ({} more lines not shown. Use offset={} to continue reading.)",
+                                total_lines - last_shown,
+                                last_shown + 1
+                            ));
+                        }
+
+                        Ok(result)
                     }
-                    Err(_) => println!("{} Invalid number. Usage: /prefix-keep {{number}}", "⚠️".bright_yellow()),
+                    Err(e) => Ok(format!("Error: {}", e)),
                 }
-                continue;
             }
+            "grep" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: GrepArgs = serde_json::from_str(args_str)?;
+                let path = args.path.as_deref().unwrap_or(".");
+                println!("{} Searching for: {} in {}", "🔍".bright_cyan(), args.pattern.bright_cyan(), path.bold().bright_cyan());
 
-            self.process_prompt(input).await?;
-        }
-        Ok(())
-    }
-
-    async fn process_prompt(&mut self, prompt: &str) -> Result<()> {
-        self.history.push(GaiseMessage {
-            role: "user".to_string(),
-            content: Some(OneOrMany::One(GaiseContent::Text { text: prompt.to_string() })),
-            tool_calls: None,
-            tool_call_id: None,
-        });
-
-        loop {
-            let mut generation_config = None;
-            if self.model.starts_with("openai::gpt-5") {
-                generation_config = Some(GaiseGenerationConfig {
-                    cache_key: Some(self.session_cache_key.clone()),
-                    ..Default::default()
-                });
+                let validated_path = match self.validate_path(path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                let options = search::GrepOptions {
+                    include: args.include,
+                    context: args.context.unwrap_or(0),
+                    max_results: args.max_results.unwrap_or(search::DEFAULT_GREP_RESULTS),
+                    case_insensitive: args.case_insensitive.unwrap_or(false),
+                    ..search::GrepOptions::new(&args.pattern)
+                };
+                match search::grep(&self.working_directory, &validated_path, &options) {
+                    Ok(result) => Ok(result),
+                    Err(e) => Ok(format!("Error: {}", e)),
+                }
             }
+            "glob" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: GlobArgs = serde_json::from_str(args_str)?;
+                let path = args.path.as_deref().unwrap_or(".");
+                println!("{} Finding files: {} in {}", "🔍".bright_cyan(), args.pattern.bright_cyan(), path.bold().bright_cyan());
 
-            let request = GaiseInstructRequest {
-                input: OneOrMany::Many(self.build_request_history()),
-                model: self.model.clone(),
-                tools: Some(tools::get_tools()),
-                generation_config,
-                ..Default::default()
-            };
-
-            let pb = ProgressBar::new_spinner();
-            pb.set_style(ProgressStyle::default_spinner()
-                .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-                .template("{spinner:.green} {msg}")?);
-            pb.set_message("Thinking...");
-            pb.enable_steady_tick(Duration::from_millis(100));
-
-            let response = self.client.instruct(&request).await;
-            pb.finish_and_clear();
-            let response = response.map_err(|e| anyhow!("{}", e))?;
-            
-            let assistant_messages = match response.output {
-                OneOrMany::One(m) => vec![m],
-                OneOrMany::Many(ms) => ms,
-            };
-
-            for assistant_message in assistant_messages {
-                self.history.push(assistant_message.clone());
-
-                if let Some(content) = &assistant_message.content {
-                    match content {
-                        OneOrMany::One(GaiseContent::Text { text }) => {
-                            println!("{}", text);
-                        }
-                        OneOrMany::Many(parts) => {
-                            for part in parts {
-                                if let GaiseContent::Text { text } = part {
-                                    println!("{}", text);
-                                }
-                            }
-                        }
-                        _ => {}
-                    }
+                let validated_path = match self.validate_path(path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                let max_results = args.max_results.unwrap_or(search::DEFAULT_GLOB_RESULTS);
+                match search::glob(&self.working_directory, &validated_path, &args.pattern, max_results) {
+                    Ok(result) => Ok(result),
+                    Err(e) => Ok(format!("Error: {}", e)),
                 }
+            }
+            "list_directory" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: ListDirectoryArgs = serde_json::from_str(args_str)?;
+                let path = args.path.as_deref().unwrap_or(".");
+                println!("{} Listing directory: {}", "📂".bright_cyan(), path.bold().bright_cyan());
 
-                if let Some(tool_calls) = assistant_message.tool_calls {
-                    for tool_call in tool_calls {
-                        let result = self.handle_tool_call(&tool_call).await?;
-                        self.history.push(GaiseMessage {
-                            role: "tool".to_string(),
-                            content: Some(OneOrMany::One(GaiseContent::Text { text: result })),
-                            tool_calls: None,
-                            tool_call_id: Some(tool_call.id.clone()),
-                        });
-                    }
-                } else {
-                    return Ok(());
+                let validated_path = match self.validate_path(path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                match search::list_directory(&validated_path, args.depth.unwrap_or(1)) {
+                    Ok(result) => Ok(result),
+                    Err(e) => Ok(format!("Error: {}", e)),
                 }
             }
+            name => Ok(format!("Error: {} is not a read-only tool", name)),
         }
     }
 
-    async fn handle_tool_call(&mut self, tool_call: &GaiseToolCall) -> Result<String> {
+    async fn run_tool(&mut self, tool_call: &GaiseToolCall) -> Result<String> {
         let name = &tool_call.function.name;
         let arguments = &tool_call.function.arguments;
 
@@ -481,77 +1984,68 @@ impl App {
                 let args: ExecuteCommandArgs = serde_json::from_str(args_str)?;
                 println!("{} Executing: {}", "🚀".bright_magenta(), args.command.bright_magenta());
 
-                if !self.brave {
-                    let prompt = format!("{} Execute command: {}?", "❓".bright_yellow(), args.command.cyan());
-                    if !Confirm::new().with_prompt(prompt).interact()? {
-                        return Ok("Command cancelled by user.".to_string());
+                let prompt = format!("{} Execute command: {}?", "❓".bright_yellow(), args.command.cyan());
+                match self.authorize(PermissionRequest::command(name, &args.command), prompt, None)? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Command blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Command cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Command rejected by user with feedback: {}", feedback)),
+                }
+
+                let mut options = self.exec.clone();
+                if let Some(seconds) = args.timeout {
+                    options.timeout = Duration::from_secs(seconds.max(1)).min(exec::MAX_TIMEOUT);
+                }
+                match exec::run_command(&args.command, &options).await {
+                    Ok(output) => {
+                        if let exec::CommandExit::TimedOut(timeout) = &output.exit {
+                            println!("{} Command timed out after {}s and was killed", "⏱️".yellow(), timeout.as_secs());
+                        }
+                        Ok(output.to_tool_result())
                     }
+                    Err(e) => Ok(format!("Error executing command: {}", e)),
+                }
+            }
+            "start_background_command" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: StartBackgroundCommandArgs = serde_json::from_str(args_str)?;
+                println!("{} Starting in background: {}", "🚀".bright_magenta(), args.command.bright_magenta());
+
+                let prompt = format!("{} Start background command: {}?", "❓".bright_yellow(), args.command.cyan());
+                match self.authorize(PermissionRequest::command(name, &args.command), prompt, None)? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Command blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Command cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Command rejected by user with feedback: {}", feedback)),
                 }
 
-                let output = if cfg!(target_os = "windows") {
-                    Command::new("cmd").args(["/C", &args.command]).output()?
-                } else {
-                    Command::new("sh").args(["-c", &args.command]).output()?
+                let id = match self.jobs.start(&args.command, &self.exec) {
+                    Ok(id) => id,
+                    Err(e) => return Ok(format!("Error starting command: {}", e)),
                 };
-
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                Ok(format!("STDOUT:\n{}\nSTDERR:\n{}", stdout, stderr))
+                // Give it a moment, so that a command that fails right away says so.
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(format!("Started background job {}.\n{}", id, self.jobs.read(id)?))
             }
-            "read_file" => {
+            "read_background_output" => {
                 let args_str = arguments.as_deref().unwrap_or("{}");
-                let args: ReadFileArgs = serde_json::from_str(args_str)?;
-                println!("{} Reading file: {}", "📖".bright_cyan(), args.path.bold().bright_cyan());
-
-                match self.validate_path(&args.path) {
-                    Ok(validated_path) => {
-                        let metadata = match fs::metadata(&validated_path) {
-                            Ok(m) => m,
-                            Err(e) => return Ok(format!("Error reading file: {}", e)),
-                        };
-
-                        const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
-                        if metadata.len() > MAX_FILE_SIZE {
-                            return Ok(format!(
-                                "Error: file is too large ({:.1} MB). Maximum supported size is {:.0} MB.",
-                                metadata.len() as f64 / (1024.0 * 1024.0),
-                                MAX_FILE_SIZE as f64 / (1024.0 * 1024.0)
-                            ));
-                        }
-
-                        let content = match fs::read_to_string(&validated_path) {
-                            Ok(c) => c,
-                            Err(e) => return Ok(format!("Error reading file: {}", e)),
-                        };
-
-                        let total_lines = content.lines().count();
-                        let offset = args.offset.unwrap_or(1).max(1);
-                        let limit = args.limit.unwrap_or(2000);
-
-                        let selected: Vec<String> = content
-                            .lines()
-                            .enumerate()
-                            .skip(offset - 1)
-                            .take(limit)
-                            .map(|(i, line)| format!("{:>6}\t{}", i + 1, line))
-                            .collect();
-
-                        let mut result = selected.join("\n");
-
-                        let last_shown = (offset - 1 + selected.len()).min(total_lines);
-                        if last_shown < total_lines {
-                            result.push_str(&format!(
-                                "\n\n... ({} more lines not shown. Use offset={} to continue reading.)",
-                                total_lines - last_shown,
-                                last_shown + 1
-                            ));
-                        }
-
-                        Ok(result)
-                    }
+                let args: BackgroundJobArgs = serde_json::from_str(args_str)?;
+                println!("{} Reading output of job {}", "📖".bright_cyan(), args.id);
+                match self.jobs.read(args.id) {
+                    Ok(output) => Ok(output),
                     Err(e) => Ok(format!("Error: {}", e)),
                 }
             }
+            "kill_background_command" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: BackgroundJobArgs = serde_json::from_str(args_str)?;
+                println!("{} Stopping job {}", "🛑".bright_red(), args.id);
+                match self.jobs.kill(args.id).await {
+                    Ok(message) => Ok(message),
+                    Err(e) => Ok(format!("Error: {}", e)),
+                }
+            }
+            "read_file" | "grep" | "glob" | "list_directory" => self.run_read_only_tool(tool_call),
             "edit_file" => {
                 let args_str = arguments.as_deref().unwrap_or("{}");
                 let args: EditFileArgs = serde_json::from_str(args_str)?;
@@ -564,22 +2058,24 @@ impl App {
                             Err(e) => return Ok(format!("Error reading file: {}", e)),
                         };
 
-                        let count = content.matches(&args.old_text).count();
-                        if count == 0 {
-                            return Ok("Error: old_text not found in file. Make sure it matches exactly, including whitespace and indentation.".to_string());
-                        }
-                        if count > 1 {
-                            return Ok(format!("Error: old_text matches {} locations in the file. Provide more surrounding context to make the match unique.", count));
-                        }
+                        let new_content = match patch::replace_unique(&content, &args.old_text, &args.new_text) {
+                            Ok(c) => c,
+                            Err(e) => return Ok(format!("Error: {}", e)),
+                        };
 
-                        if !self.brave {
-                            let prompt = format!("{} Edit file {}?", "❓".bright_yellow(), args.path.bold().cyan());
-                            if !Confirm::new().with_prompt(prompt).interact()? {
-                                return Ok("File edit cancelled by user.".to_string());
-                            }
+                        let relative = self.relative_path(&validated_path);
+                        let prompt = format!("{} Edit file {}?", "❓".bright_yellow(), args.path.bold().cyan());
+                        let preview = diff::unified_diff(&relative, Some(&content), &new_content);
+                        match self.authorize(PermissionRequest::path(name, &relative), prompt, Some(preview))? {
+                            Authorization::Allowed => {}
+                            Authorization::Denied(reason) => return Ok(format!("File edit blocked by permission {}.", reason)),
+                            Authorization::Rejected => return Ok("File edit cancelled by user.".to_string()),
+                            Authorization::Feedback(feedback) => return Ok(format!("File edit rejected by user with feedback: {}", feedback)),
                         }
 
-                        let new_content = content.replacen(&args.old_text, &args.new_text, 1);
+                        if let Err(e) = self.checkpoints.snapshot(&validated_path) {
+                            return Ok(format!("Error: {}", e));
+                        }
                         match fs::write(&validated_path, &new_content) {
                             Ok(_) => Ok("File updated successfully.".to_string()),
                             Err(e) => Ok(format!("Error writing file: {}", e)),
@@ -588,6 +2084,129 @@ impl App {
                     Err(e) => Ok(format!("Error: {}", e)),
                 }
             }
+            "multi_edit" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: MultiEditArgs = serde_json::from_str(args_str)?;
+                println!("{} Editing file: {} ({} edits)", "✍️".bright_yellow(), args.path.bold().bright_yellow(), args.edits.len());
+
+                let validated_path = match self.validate_path(&args.path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                let content = match fs::read_to_string(&validated_path) {
+                    Ok(c) => c,
+                    Err(e) => return Ok(format!("Error reading file: {}", e)),
+                };
+                let edits = args.edits.iter().map(|edit| (edit.old_text.as_str(), edit.new_text.as_str()));
+                let new_content = match patch::apply_edits(&content, edits) {
+                    Ok(c) => c,
+                    Err(e) => return Ok(format!("Error: {} No edits were applied.", e)),
+                };
+
+                let relative = self.relative_path(&validated_path);
+                let prompt = format!("{} Apply {} edits to {}?", "❓".bright_yellow(), args.edits.len(), args.path.bold().cyan());
+                let preview = diff::unified_diff(&relative, Some(&content), &new_content);
+                match self.authorize(PermissionRequest::path(name, &relative), prompt, Some(preview))? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("File edit blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("File edit cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("File edit rejected by user with feedback: {}", feedback)),
+                }
+
+                if let Err(e) = self.checkpoints.snapshot(&validated_path) {
+                    return Ok(format!("Error: {}", e));
+                }
+                match fs::write(&validated_path, &new_content) {
+                    Ok(_) => Ok(format!("File updated successfully ({} edits applied).", args.edits.len())),
+                    Err(e) => Ok(format!("Error writing file: {}", e)),
+                }
+            }
+            "apply_patch" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: ApplyPatchArgs = serde_json::from_str(args_str)?;
+                let file_patches = match patch::parse_patch(&args.patch) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                let paths: Vec<&str> = file_patches.iter().map(|p| p.path()).collect();
+                println!("{} Applying patch to: {}", "✍️".bright_yellow(), paths.join(", ").bold().bright_yellow());
+
+                // Check every hunk of every file before touching any of them.
+                let mut changes = Vec::new();
+                let mut failures = Vec::new();
+                for file_patch in &file_patches {
+                    let validated_path = match self.validate_path(file_patch.path()) {
+                        Ok(p) => p,
+                        Err(e) => {
+                            failures.push(format!("{}: {}", file_patch.path(), e));
+                            continue;
+                        }
+                    };
+                    let original = match fs::read_to_string(&validated_path) {
+                        Ok(c) => Some(c),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                        Err(e) => {
+                            failures.push(format!("{}: could not read file: {}", file_patch.path(), e));
+                            continue;
+                        }
+                    };
+                    match file_patch.apply(original.as_deref()) {
+                        Ok(new_content) => changes.push((validated_path, original, new_content)),
+                        Err(errors) => failures.extend(errors),
+                    }
+                }
+                if !failures.is_empty() {
+                    return Ok(format!("Error: the patch was not applied, no files were changed.\n{}", failures.join("\n")));
+                }
+
+                for (validated_path, original, new_content) in &changes {
+                    let action = match (original, new_content) {
+                        (_, None) => "Delete",
+                        (None, Some(_)) => "Create",
+                        (Some(_), Some(_)) => "Patch",
+                    };
+                    let relative = self.relative_path(validated_path);
+                    let prompt = format!("{} {} file {}?", "❓".bright_yellow(), action, relative.bold().cyan());
+                    let preview = diff::unified_diff(&relative, original.as_deref(), new_content.as_deref().unwrap_or_default());
+                    match self.authorize(PermissionRequest::path(name, &relative), prompt, Some(preview))? {
+                        Authorization::Allowed => {}
+                        Authorization::Denied(reason) => return Ok(format!("Patch blocked by permission {} for {}. No files were changed.", reason, relative)),
+                        Authorization::Rejected => return Ok(format!("Patch cancelled by user at {}. No files were changed.", relative)),
+                        Authorization::Feedback(feedback) => return Ok(format!("Patch rejected by user at {} with feedback: {}. No files were changed.", relative, feedback)),
+                    }
+                }
+
+                for (validated_path, _, _) in &changes {
+                    if let Err(e) = self.checkpoints.snapshot(validated_path) {
+                        return Ok(format!("Error: {}. No files were changed.", e));
+                    }
+                    if let Some(parent) = validated_path.parent().filter(|parent| !parent.exists()) {
+                        self.checkpoints.snapshot_directory(parent);
+                    }
+                }
+                let mut written = Vec::new();
+                for (validated_path, _, new_content) in &changes {
+                    let relative = self.relative_path(validated_path);
+                    let result = match new_content {
+                        Some(content) => validated_path
+                            .parent()
+                            .map_or(Ok(()), fs::create_dir_all)
+                            .and_then(|_| fs::write(validated_path, content)),
+                        None => fs::remove_file(validated_path),
+                    };
+                    if let Err(e) = result {
+                        return Ok(format!(
+                            "Error writing {}: {}. Files already changed: {}",
+                            relative,
+                            e,
+                            if written.is_empty() { "none".to_string() } else { written.join(", ") }
+                        ));
+                    }
+                    written.push(relative);
+                }
+                let hunks: usize = file_patches.iter().map(|p| p.hunks.len()).sum();
+                Ok(format!("Patch applied: {} hunks in {} files ({}).", hunks, written.len(), written.join(", ")))
+            }
             "create_file" => {
                 let args_str = arguments.as_deref().unwrap_or("{}");
                 let args: CreateFileArgs = serde_json::from_str(args_str)?;
@@ -599,15 +2218,22 @@ impl App {
                             return Ok(format!("Error: file '{}' already exists. Use edit_file to modify existing files.", args.path));
                         }
 
-                        if !self.brave {
-                            let prompt = format!("{} Create file {}?", "❓".bright_yellow(), args.path.bold().cyan());
-                            if !Confirm::new().with_prompt(prompt).interact()? {
-                                return Ok("File creation cancelled by user.".to_string());
-                            }
+                        let relative = self.relative_path(&validated_path);
+                        let prompt = format!("{} Create file {}?", "❓".bright_yellow(), args.path.bold().cyan());
+                        let preview = diff::unified_diff(&relative, None, &args.content);
+                        match self.authorize(PermissionRequest::path(name, &relative), prompt, Some(preview))? {
+                            Authorization::Allowed => {}
+                            Authorization::Denied(reason) => return Ok(format!("File creation blocked by permission {}.", reason)),
+                            Authorization::Rejected => return Ok("File creation cancelled by user.".to_string()),
+                            Authorization::Feedback(feedback) => return Ok(format!("File creation rejected by user with feedback: {}", feedback)),
                         }
 
+                        if let Err(e) = self.checkpoints.snapshot(&validated_path) {
+                            return Ok(format!("Error: {}", e));
+                        }
                         if let Some(parent) = validated_path.parent() {
                             if !parent.exists() {
+                                self.checkpoints.snapshot_directory(parent);
                                 if let Err(e) = fs::create_dir_all(parent) {
                                     return Ok(format!("Error creating directories: {}", e));
                                 }
@@ -622,11 +2248,335 @@ impl App {
                     Err(e) => Ok(format!("Error: {}", e)),
                 }
             }
+            "delete_file" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: DeleteFileArgs = serde_json::from_str(args_str)?;
+                println!("{} Deleting: {}", "🗑️".bright_red(), args.path.bold().bright_red());
+
+                let validated_path = match self.validate_path(&args.path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                if validated_path == self.working_directory {
+                    return Ok("Error: the working directory itself cannot be deleted.".to_string());
+                }
+                let is_dir = validated_path.is_dir();
+                let preview = if is_dir {
+                    match fs::read_dir(&validated_path).map(|mut entries| entries.next().is_none()) {
+                        Ok(true) => None,
+                        Ok(false) => return Ok(format!("Error: directory '{}' is not empty. Delete or move its contents first.", args.path)),
+                        Err(e) => return Ok(format!("Error reading directory: {}", e)),
+                    }
+                } else {
+                    match fs::read(&validated_path) {
+                        Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+                        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(format!("Error: '{}' does not exist.", args.path)),
+                        Err(e) => return Ok(format!("Error reading file: {}", e)),
+                    }
+                };
+
+                let relative = self.relative_path(&validated_path);
+                let prompt = format!("{} Delete {}?", "❓".bright_yellow(), args.path.bold().cyan());
+                let preview = preview.map(|content| diff::unified_diff(&relative, Some(&content), ""));
+                match self.authorize(PermissionRequest::path(name, &relative), prompt, preview)? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Deletion blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Deletion cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Deletion rejected by user with feedback: {}", feedback)),
+                }
+
+                if is_dir {
+                    self.checkpoints.snapshot_directory(&validated_path);
+                    return match fs::remove_dir(&validated_path) {
+                        Ok(_) => Ok("Directory deleted successfully.".to_string()),
+                        Err(e) => Ok(format!("Error deleting directory: {}", e)),
+                    };
+                }
+                if let Err(e) = self.checkpoints.snapshot(&validated_path) {
+                    return Ok(format!("Error: {}", e));
+                }
+                match fs::remove_file(&validated_path) {
+                    Ok(_) => Ok("File deleted successfully.".to_string()),
+                    Err(e) => Ok(format!("Error deleting file: {}", e)),
+                }
+            }
+            "move_file" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: MoveFileArgs = serde_json::from_str(args_str)?;
+                println!(
+                    "{} Moving: {} -> {}",
+                    "🚚".bright_yellow(),
+                    args.source.bold().bright_yellow(),
+                    args.destination.bold().bright_yellow()
+                );
+
+                let (source, destination) = match (self.validate_path(&args.source), self.validate_path(&args.destination)) {
+                    (Ok(source), Ok(destination)) => (source, destination),
+                    (Err(e), _) | (_, Err(e)) => return Ok(format!("Error: {}", e)),
+                };
+                if !source.exists() {
+                    return Ok(format!("Error: '{}' does not exist.", args.source));
+                }
+                if destination.exists() {
+                    return Ok(format!("Error: '{}' already exists. Delete it first or choose another destination.", args.destination));
+                }
+                if source == self.working_directory || destination.starts_with(&source) {
+                    return Ok(format!("Error: cannot move '{}' into itself.", args.source));
+                }
+
+                // Both ends of the move are checked against the rules and the stricter one
+                // decides, so a move cannot write where the other file tools may not.
+                let source_relative = self.relative_path(&source);
+                let destination_relative = self.relative_path(&destination);
+                let source_request = PermissionRequest::path(name, &source_relative);
+                let destination_request = PermissionRequest::path(name, &destination_relative);
+                let request = if self.permissions.check(&destination_request).0 > self.permissions.check(&source_request).0 {
+                    destination_request
+                } else {
+                    source_request
+                };
+                let prompt = format!(
+                    "{} Move {} to {}?",
+                    "❓".bright_yellow(),
+                    args.source.bold().cyan(),
+                    args.destination.bold().cyan()
+                );
+                match self.authorize(request, prompt, None)? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Move blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Move cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Move rejected by user with feedback: {}", feedback)),
+                }
+
+                if let Err(e) = self.checkpoints.snapshot_move(&source, &destination) {
+                    return Ok(format!("Error: {}", e));
+                }
+                if let Some(parent) = destination.parent() {
+                    if let Err(e) = fs::create_dir_all(parent) {
+                        return Ok(format!("Error creating directories: {}", e));
+                    }
+                }
+                match fs::rename(&source, &destination) {
+                    Ok(_) => Ok(format!("Moved {} to {}.", source_relative, destination_relative)),
+                    Err(e) => Ok(format!("Error moving file: {}", e)),
+                }
+            }
+            "create_directory" => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: CreateDirectoryArgs = serde_json::from_str(args_str)?;
+                println!("{} Creating directory: {}", "📁".bright_green(), args.path.bold().bright_green());
+
+                let validated_path = match self.validate_path(&args.path) {
+                    Ok(p) => p,
+                    Err(e) => return Ok(format!("Error: {}", e)),
+                };
+                if validated_path.is_dir() {
+                    return Ok("Directory already exists.".to_string());
+                }
+                if validated_path.exists() {
+                    return Ok(format!("Error: '{}' already exists and is not a directory.", args.path));
+                }
+
+                let relative = self.relative_path(&validated_path);
+                let prompt = format!("{} Create directory {}?", "❓".bright_yellow(), args.path.bold().cyan());
+                match self.authorize(PermissionRequest::path(name, &relative), prompt, None)? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Directory creation blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Directory creation cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Directory creation rejected by user with feedback: {}", feedback)),
+                }
+
+                self.checkpoints.snapshot_directory(&validated_path);
+                match fs::create_dir_all(&validated_path) {
+                    Ok(_) => Ok("Directory created successfully.".to_string()),
+                    Err(e) => Ok(format!("Error creating directory: {}", e)),
+                }
+            }
+            name if self.custom_tools.iter().any(|tool| tool.name == name) => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: serde_json::Value = serde_json::from_str(args_str)?;
+                let tool = self.custom_tools.iter().find(|tool| tool.name == name).cloned().expect("matched above");
+
+                let prompt = format!("{} Run custom tool {} (`{}`)?", "❓".bright_yellow(), name.bold().cyan(), tool.command.bright_magenta());
+                let preview = serde_json::to_string_pretty(&args)?;
+                match self.authorize(PermissionRequest::tool(name), prompt, Some(preview))? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Tool call blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Tool call cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Tool call rejected by user with feedback: {}", feedback)),
+                }
+
+                println!("{} Running custom tool: {}", "🧰".bright_blue(), tool.command.bright_magenta());
+                match tool.run(&args, &self.exec).await {
+                    Ok(output) => {
+                        if let exec::CommandExit::TimedOut(timeout) = &output.exit {
+                            println!("{} Custom tool timed out after {}s and was killed", "⏱️".yellow(), timeout.as_secs());
+                        }
+                        Ok(output.to_tool_result())
+                    }
+                    Err(e) => Ok(format!("Error: {}", e)),
+                }
+            }
+            name if name.starts_with(mcp::TOOL_PREFIX) => {
+                let args_str = arguments.as_deref().unwrap_or("{}");
+                let args: serde_json::Value = serde_json::from_str(args_str)?;
+                let Some((server, tool)) = self.mcp.find(name) else {
+                    return Ok(format!("Unknown tool: {}", name));
+                };
+                println!("{} {} on MCP server {}", "🔌".bright_blue(), tool.bold().bright_blue(), server.bright_magenta());
+
+                let prompt = format!("{} Call {} on MCP server {}?", "❓".bright_yellow(), tool.bold().cyan(), server.bold().cyan());
+                let preview = serde_json::to_string_pretty(&args)?;
+                match self.authorize(PermissionRequest::tool(name), prompt, Some(preview))? {
+                    Authorization::Allowed => {}
+                    Authorization::Denied(reason) => return Ok(format!("Tool call blocked by permission {}.", reason)),
+                    Authorization::Rejected => return Ok("Tool call cancelled by user.".to_string()),
+                    Authorization::Feedback(feedback) => return Ok(format!("Tool call rejected by user with feedback: {}", feedback)),
+                }
+
+                match self.mcp.call(name, args).await {
+                    Ok(result) => Ok(result),
+                    Err(e) => Ok(format!("Error: {}", e)),
+                }
+            }
             _ => Ok(format!("Unknown tool: {}", name)),
         }
     }
 }
 
+/// Tells the user when a request failed transiently and is being retried or handed to a fallback model;
+/// other log events are ignored.
+#[derive(Debug)]
+struct RetryNotice;
+
+impl IGaiseLogger for RetryNotice {
+    fn log_request(&self, _: Option<&str>, _: &str, _: &str, _: serde_json::Value) {}
+    fn log_response(&self, _: Option<&str>, _: &str, _: &str, _: serde_json::Value, _: Option<serde_json::Value>) {}
+    fn log_stream_chunk(&self, _: Option<&str>, _: &str, _: &str, _: serde_json::Value) {}
+
+    fn log_retry(&self, _: Option<&str>, _: &str, model: &str, attempt: u32, delay: Duration, error: &GaiseError) {
+        eprintln!(
+            "{} {} ({}), retrying in {:.1}s (attempt {} failed)",
+            "⏳".yellow(),
+            model,
+            error.kind().replace('_', " "),
+            delay.as_secs_f64(),
+            attempt
+        );
+    }
+    fn log_fallback(&self, _: Option<&str>, _: &str, model: &str, next_model: &str, error: &GaiseError) {
+        eprintln!(
+            "{} {} ({}), falling back to {}",
+            "↪️".yellow(),
+            model,
+            error.kind().replace('_', " "),
+            next_model
+        );
+    }
+}
+
+/// Asks before applying the settings of the project config file that `settings_needing_trust`
+/// lists, and drops them unless the user agrees. A trusted file is remembered until its
+/// content changes; without a terminal to ask on, the settings are dropped.
+fn confirm_project_trust(config: &mut IkodeConfig) {
+    let settings = config.settings_needing_trust();
+    let Some((index, content)) = &config.project else {
+        return;
+    };
+    if settings.is_empty() {
+        return;
+    }
+    let path = config.layers[*index].0.clone();
+    let message = format!(
+        "{} sets {}, which only apply if you trust it. Only trust config files you wrote or reviewed.",
+        path.display().to_string().cyan(),
+        settings.join(", ").bright_magenta()
+    );
+    if !confirm_trust(&[(&path, content)], message, "Trust this file?") {
+        eprintln!(
+            "{} Warning: Ignoring {} from the untrusted project config {}",
+            "⚠️".yellow(),
+            settings.join(", "),
+            path.display()
+        );
+        config.drop_untrusted_settings();
+    }
+}
+
+/// Keeps the custom tools whose files the user trusts, asking about the others: a tool from
+/// a cloned repository runs whatever command it declares.
+fn confirm_custom_tools_trust(tools: Vec<CustomTool>, working_directory: &Path) -> Vec<CustomTool> {
+    let store = TrustStore::for_user();
+    let (mut trusted, tools): (Vec<CustomTool>, Vec<CustomTool>) = tools
+        .into_iter()
+        .partition(|tool| store.as_ref().is_some_and(|store| store.is_trusted(&tool.source, &tool.content)));
+    if tools.is_empty() {
+        return trusted;
+    }
+    let files: Vec<(&Path, &str)> = tools.iter().map(|tool| (tool.source.as_path(), tool.content.as_str())).collect();
+    let list: Vec<String> = tools
+        .iter()
+        .map(|tool| {
+            let source = tool.source.strip_prefix(working_directory).unwrap_or(&tool.source);
+            format!("  {} ({}): {}", tool.name.bold(), source.display(), tool.command.bright_magenta())
+        })
+        .collect();
+    let message = format!(
+        "The project declares custom tools that run these commands, which only apply if you trust them. Only trust tools you wrote or reviewed.\n{}",
+        list.join("\n")
+    );
+    if confirm_trust(&files, message, "Trust these tools?") {
+        trusted.extend(tools);
+    } else {
+        let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+        eprintln!("{} Warning: Ignoring the untrusted custom tools {}", "⚠️".yellow(), names.join(", "));
+    }
+    trusted
+}
+
+/// Whether the user trusts the project `files` (path and content): either they trusted
+/// exactly these contents before, or they say so now after `message`, which is then
+/// remembered. Without a terminal to ask on, nothing new is trusted.
+fn confirm_trust(files: &[(&Path, &str)], message: String, question: &str) -> bool {
+    let store = TrustStore::for_user();
+    if store.as_ref().is_some_and(|store| files.iter().all(|(path, content)| store.is_trusted(path, content))) {
+        return true;
+    }
+
+    let trusted = io::stdin().is_terminal() && {
+        println!("{} {}", "🔒".bright_yellow(), message);
+        Select::new()
+            .with_prompt(question)
+            .items(&["Yes", "No, ignore them this time"])
+            .default(1)
+            .interact()
+            .is_ok_and(|choice| choice == 0)
+    };
+    if trusted {
+        for (path, content) in files {
+            if let Some(Err(e)) = store.as_ref().map(|store| store.trust(path, content)) {
+                eprintln!("{} Warning: Could not remember the trusted file {}: {}", "⚠️".yellow(), path.display(), e);
+            }
+        }
+    }
+    trusted
+}
+
+/// Where permission decisions for `working_directory` are recorded,
+/// e.g. `~/.local/share/ikode/projects/-home-me-project-225df3094012/audit.jsonl`.
+fn audit_log_path(working_directory: &Path) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("ikode").join("projects").join(session::project_key(working_directory)).join("audit.jsonl"))
+}
+
+/// User price overrides, e.g. `~/.config/ikode/prices.toml`.
+fn prices_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("ikode").join("prices.toml"))
+}
+
+fn short_id(id: &str) -> &str {
+    id.get(..8).unwrap_or(id)
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let args: Vec<String> = std::env::args()
@@ -647,13 +2597,52 @@ async fn main() -> Result<()> {
         .collect();
 
     let args = Args::parse_from(args);
-    let mut app = App::new(args.model, args.brave, args.guide, args.max_history, args.prefix_keep)?;
+    let mut app = App::new(&args)?;
+    app.check_model().await?;
+    app.start_mcp_servers().await;
+
+    if let Some(id) = &args.resume {
+        app.resume_session(id)?;
+    } else if args.continue_session {
+        app.continue_latest_session()?;
+    }
 
-    if let Some(prompt) = args.prompt {
-        app.process_prompt(&prompt).await?;
-    } else {
-        app.run_loop().await?;
+    // Ctrl-C abandons the running turn, which kills its command, and then shuts down like
+    // `/exit`. A second Ctrl-C while that is under way quits at once.
+    let run = async {
+        if let Some(prompt) = &args.prompt {
+            app.submit(prompt).await
+        } else {
+            app.run_loop().await
+        }
+    };
+    let (result, interrupted) = tokio::select! {
+        result = run => (result, false),
+        _ = tokio::signal::ctrl_c() => (Ok(()), true),
+    };
+    if interrupted {
+        println!("\n{} Interrupted, shutting down (Ctrl-C again to quit now)", "⛔".bright_red());
+        let groups = [app.exec.process_groups.clone(), app.jobs.process_groups()];
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                for groups in &groups {
+                    for pid in groups.lock().unwrap().iter() {
+                        exec::kill_process_group(*pid);
+                    }
+                }
+                std::process::exit(130);
+            }
+        });
+        app.close_interrupted_turn();
+        app.save_session();
     }
+    app.end_session(if interrupted { "interrupted" } else { "exit" }).await;
+    app.stop_jobs().await;
+    app.mcp.shutdown().await;
 
-    Ok(())
+    if interrupted {
+        // The prompt's blocking read may still be waiting for a line; do not wait for it.
+        std::process::exit(130);
+    }
+    result
 }
\ No newline at end of file
@@ -0,0 +1,401 @@
+use crate::tools::parameter_from_schema;
+use anyhow::{Result, anyhow};
+use futures_util::future::join_all;
+use gaise_core::contracts::GaiseTool;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::oneshot;
+
+/// The MCP revision ikode speaks; servers answer with the one they picked.
+pub const PROTOCOL_VERSION: &str = "2024-11-05";
+/// Names of MCP tools start with this, followed by the server and the tool name.
+pub const TOOL_PREFIX: &str = "mcp__";
+/// How long a server has to answer a request unless its config says otherwise.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest tool result handed to the model, in bytes.
+const MAX_RESULT_LENGTH: usize = 100 * 1024;
+/// Providers reject longer tool names.
+const MAX_TOOL_NAME_LENGTH: usize = 64;
+
+/// How to launch one MCP server, from `[mcp_servers.<name>]` in a config file.
+///
+/// ```toml
+/// [mcp_servers.github]
+/// command = "npx"
+/// args = ["-y", "@modelcontextprotocol/server-github"]
+/// env = { GITHUB_PERSONAL_ACCESS_TOKEN = "${GITHUB_TOKEN}" }
+/// ```
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct McpServerConfig {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extra environment variables; `${NAME}` is replaced with ikode's own variable.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Seconds to wait for each response.
+    pub timeout: Option<u64>,
+}
+
+/// A tool as a server lists it in `tools/list`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "inputSchema", default)]
+    pub input_schema: Value,
+}
+
+/// `mcp__<server>__<tool>`, with characters providers do not allow in tool names replaced
+/// by `_`.
+pub fn qualified_name(server: &str, tool: &str) -> String {
+    let sanitize = |name: &str| -> String {
+        name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect()
+    };
+    let mut name = format!("{}{}__{}", TOOL_PREFIX, sanitize(server), sanitize(tool));
+    name.truncate(MAX_TOOL_NAME_LENGTH);
+    name
+}
+
+/// Replaces `${NAME}` with the value of the environment variable `NAME`, or nothing if unset.
+pub fn expand_env(value: &str) -> String {
+    let pattern = Regex::new(r"\$\{([A-Za-z_][A-Za-z0-9_]*)\}").expect("valid regex");
+    pattern
+        .replace_all(value, |captures: &regex::Captures| std::env::var(&captures[1]).unwrap_or_default())
+        .into_owned()
+}
+
+/// The text of a `tools/call` result for the model. Non-text content is described rather
+/// than dropped, and results the server flags as errors start with `Error:`.
+pub fn format_result(result: &Value) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for item in result.get("content").and_then(Value::as_array).into_iter().flatten() {
+        let kind = item.get("type").and_then(Value::as_str).unwrap_or_default();
+        let part = match kind {
+            "text" => item.get("text").and_then(Value::as_str).unwrap_or_default().to_string(),
+            "image" | "audio" => format!(
+                "[{} content: {}]",
+                kind,
+                item.get("mimeType").and_then(Value::as_str).unwrap_or("unknown type")
+            ),
+            "resource" => {
+                let resource = item.get("resource").unwrap_or(&Value::Null);
+                match resource.get("text").and_then(Value::as_str) {
+                    Some(text) => text.to_string(),
+                    None => format!("[resource: {}]", resource.get("uri").and_then(Value::as_str).unwrap_or_default()),
+                }
+            }
+            "resource_link" => format!("[resource: {}]", item.get("uri").and_then(Value::as_str).unwrap_or_default()),
+            _ => item.to_string(),
+        };
+        parts.push(part);
+    }
+    if parts.is_empty() {
+        if let Some(structured) = result.get("structuredContent") {
+            parts.push(structured.to_string());
+        }
+    }
+
+    let mut text = parts.join("\n");
+    if text.is_empty() {
+        text = "(no output)".to_string();
+    }
+    if text.len() > MAX_RESULT_LENGTH {
+        let mut end = MAX_RESULT_LENGTH;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let omitted = text.len() - end;
+        text.truncate(end);
+        text.push_str(&format!("\n... [{} bytes omitted]", omitted));
+    }
+    if result.get("isError").and_then(Value::as_bool).unwrap_or(false) {
+        text = format!("Error: {}", text);
+    }
+    text
+}
+
+type Pending = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// A running MCP server, spoken to with newline-delimited JSON-RPC over its stdin and
+/// stdout. Requests can be in flight concurrently; a reader task routes the responses.
+pub struct McpClient {
+    child: Child,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: Pending,
+    next_id: AtomicU64,
+    timeout: Duration,
+    /// Name and version the server reported.
+    pub server_info: String,
+}
+
+impl McpClient {
+    /// Launches the server in `working_directory` and performs the initialize handshake.
+    pub async fn connect(config: &McpServerConfig, working_directory: &Path) -> Result<Self> {
+        let mut child = Command::new(&config.command)
+            .args(&config.args)
+            .envs(config.env.iter().map(|(key, value)| (key, expand_env(value))))
+            .current_dir(working_directory)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| anyhow!("Could not start `{}`: {}", config.command, e))?;
+        let stdin = Arc::new(tokio::sync::Mutex::new(child.stdin.take().expect("stdin is piped")));
+        let stdout = child.stdout.take().expect("stdout is piped");
+        let pending: Pending = Arc::default();
+        tokio::spawn(read_messages(stdout, Arc::downgrade(&stdin), pending.clone()));
+
+        let mut client = Self {
+            child,
+            stdin,
+            pending,
+            next_id: AtomicU64::new(1),
+            timeout: config.timeout.map_or(DEFAULT_TIMEOUT, Duration::from_secs),
+            server_info: String::new(),
+        };
+        let result = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "ikode", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        let info = result.get("serverInfo").unwrap_or(&Value::Null);
+        client.server_info = format!(
+            "{} {}",
+            info.get("name").and_then(Value::as_str).unwrap_or("unknown"),
+            info.get("version").and_then(Value::as_str).unwrap_or_default()
+        )
+        .trim()
+        .to_string();
+        client.notify("notifications/initialized", json!({})).await?;
+        Ok(client)
+    }
+
+    /// Every tool the server offers, following `nextCursor` across pages.
+    pub async fn list_tools(&self) -> Result<Vec<McpTool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let result = self.request("tools/list", params).await?;
+            let page: Vec<McpTool> = serde_json::from_value(result.get("tools").cloned().unwrap_or(json!([])))
+                .map_err(|e| anyhow!("Invalid tools/list response: {}", e))?;
+            tools.extend(page);
+            cursor = result.get("nextCursor").and_then(Value::as_str).map(String::from);
+            if cursor.is_none() {
+                return Ok(tools);
+            }
+        }
+    }
+
+    /// Calls `tool` and returns its result as text for the model.
+    pub async fn call_tool(&self, tool: &str, arguments: Value) -> Result<String> {
+        let result = self.request("tools/call", json!({ "name": tool, "arguments": arguments })).await?;
+        Ok(format_result(&result))
+    }
+
+    async fn send(&self, message: &Value) -> Result<()> {
+        let mut line = serde_json::to_string(message)?;
+        line.push('\n');
+        let mut stdin = self.stdin.lock().await;
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?;
+        Ok(())
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, sender);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = self.send(&message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(anyhow!("Could not send {} to the server: {}", method, e));
+        }
+
+        match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(anyhow!("{}", error)),
+            Ok(Err(_)) => Err(anyhow!("The server exited before answering {}", method)),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                let cancel = json!({ "requestId": id, "reason": "timed out" });
+                let _ = self.notify("notifications/cancelled", cancel).await;
+                Err(anyhow!("The server did not answer {} within {}s", method, self.timeout.as_secs()))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params })).await
+    }
+
+    /// Whether the server process is still running.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Closes the server's stdin, which tells it to exit, and kills it if it does not.
+    pub async fn shutdown(mut self) {
+        drop(self.stdin);
+        if tokio::time::timeout(Duration::from_secs(2), self.child.wait()).await.is_err() {
+            let _ = self.child.kill().await;
+        }
+    }
+}
+
+/// Routes responses from the server to the requests waiting for them. Requests from the
+/// server are answered: `ping` with an empty result, anything else as not supported. Only
+/// the client owns stdin, so dropping it still closes the pipe.
+async fn read_messages(stdout: ChildStdout, stdin: Weak<tokio::sync::Mutex<ChildStdin>>, pending: Pending) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        // Servers sometimes print logs to stdout; anything that is not JSON-RPC is skipped.
+        let Ok(message) = serde_json::from_str::<Value>(&line) else { continue };
+        let id = message.get("id").cloned();
+        match (message.get("method"), id) {
+            (Some(method), Some(id)) => {
+                let reply = if method == "ping" {
+                    json!({ "jsonrpc": "2.0", "id": id, "result": {} })
+                } else {
+                    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32601, "message": "Method not supported by ikode" } })
+                };
+                let Some(stdin) = stdin.upgrade() else { break };
+                let mut stdin = stdin.lock().await;
+                let _ = stdin.write_all(format!("{}\n", reply).as_bytes()).await;
+                let _ = stdin.flush().await;
+            }
+            (None, Some(id)) => {
+                let Some(sender) = id.as_u64().and_then(|id| pending.lock().unwrap().remove(&id)) else { continue };
+                let response = match message.get("error") {
+                    Some(error) => Err(format!(
+                        "Server error {}: {}",
+                        error.get("code").unwrap_or(&Value::Null),
+                        error.get("message").and_then(Value::as_str).unwrap_or("unknown error")
+                    )),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(response);
+            }
+            // Notifications such as logging or progress.
+            _ => {}
+        }
+    }
+    // Dropping the senders tells every waiting request that the server is gone.
+    pending.lock().unwrap().clear();
+}
+
+/// Whether a server started and which tools it offers.
+pub enum McpServerState {
+    Running { client: Box<McpClient>, tools: Vec<McpTool> },
+    Failed(String),
+}
+
+pub struct McpServer {
+    pub name: String,
+    pub config: McpServerConfig,
+    pub state: McpServerState,
+}
+
+/// The MCP servers configured for the project.
+#[derive(Default)]
+pub struct McpServers {
+    servers: Vec<McpServer>,
+}
+
+impl McpServers {
+    /// Starts every configured server at once. A server that fails to start or list its
+    /// tools is kept with its error, so `/mcp` can show it.
+    pub async fn start(configs: BTreeMap<String, McpServerConfig>, working_directory: &Path) -> Self {
+        let servers = join_all(configs.into_iter().map(|(name, config)| async move {
+            let state = match connect(&config, working_directory).await {
+                Ok((client, tools)) => McpServerState::Running { client: Box::new(client), tools },
+                Err(e) => McpServerState::Failed(e.to_string()),
+            };
+            McpServer { name, config, state }
+        }))
+        .await;
+        Self { servers }
+    }
+
+    pub fn servers(&mut self) -> &mut [McpServer] {
+        &mut self.servers
+    }
+
+    /// The tools of every running server, named `mcp__<server>__<tool>`.
+    pub fn tools(&self) -> Vec<GaiseTool> {
+        let mut tools = Vec::new();
+        for server in &self.servers {
+            let McpServerState::Running { tools: server_tools, .. } = &server.state else { continue };
+            for tool in server_tools {
+                let description = match &tool.description {
+                    Some(description) => format!("{} (from MCP server `{}`)", description, server.name),
+                    None => format!("Tool `{}` from MCP server `{}`.", tool.name, server.name),
+                };
+                tools.push(GaiseTool {
+                    name: qualified_name(&server.name, &tool.name),
+                    description: Some(description),
+                    parameters: Some(parameter_from_schema(&tool.input_schema)),
+                });
+            }
+        }
+        tools
+    }
+
+    /// The server and the server's own name for the tool called `qualified`.
+    pub fn find(&self, qualified: &str) -> Option<(&str, &str)> {
+        self.servers.iter().find_map(|server| match &server.state {
+            McpServerState::Running { tools, .. } => tools
+                .iter()
+                .find(|tool| qualified_name(&server.name, &tool.name) == qualified)
+                .map(|tool| (server.name.as_str(), tool.name.as_str())),
+            McpServerState::Failed(_) => None,
+        })
+    }
+
+    /// Calls the tool called `qualified` on its server.
+    pub async fn call(&self, qualified: &str, arguments: Value) -> Result<String> {
+        let (server_name, tool) = self.find(qualified).ok_or_else(|| anyhow!("Unknown MCP tool {}", qualified))?;
+        let server = self.servers.iter().find(|server| server.name == server_name).expect("found above");
+        match &server.state {
+            McpServerState::Running { client, .. } => client.call_tool(tool, arguments).await,
+            McpServerState::Failed(e) => Err(anyhow!("MCP server {} is not running: {}", server_name, e)),
+        }
+    }
+
+    /// Stops every running server.
+    pub async fn shutdown(&mut self) {
+        let clients = self.servers.drain(..).filter_map(|server| match server.state {
+            McpServerState::Running { client, .. } => Some(client.shutdown()),
+            McpServerState::Failed(_) => None,
+        });
+        join_all(clients).await;
+    }
+}
+
+async fn connect(config: &McpServerConfig, working_directory: &Path) -> Result<(McpClient, Vec<McpTool>)> {
+    let client = McpClient::connect(config, working_directory).await?;
+    let tools = client.list_tools().await?;
+    Ok((client, tools))
+}
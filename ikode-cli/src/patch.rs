@@ -0,0 +1,231 @@
+use anyhow::{Result, anyhow};
+
+/// Replaces the single occurrence of `old_text` in `content`, failing if it occurs zero
+/// or several times, so an edit can never land in the wrong place.
+pub fn replace_unique(content: &str, old_text: &str, new_text: &str) -> Result<String, String> {
+    match content.matches(old_text).count() {
+        0 => Err("old_text not found in file. Make sure it matches exactly, including whitespace and indentation.".to_string()),
+        1 => Ok(content.replacen(old_text, new_text, 1)),
+        count => Err(format!("old_text matches {} locations in the file. Provide more surrounding context to make the match unique.", count)),
+    }
+}
+
+/// Applies `(old_text, new_text)` pairs in order, each to the result of the previous one.
+/// Either every edit applies or the error names the first one that did not.
+pub fn apply_edits<'a>(content: &str, edits: impl IntoIterator<Item = (&'a str, &'a str)>) -> Result<String, String> {
+    let mut content = content.to_string();
+    for (index, (old_text, new_text)) in edits.into_iter().enumerate() {
+        content = replace_unique(&content, old_text, new_text).map_err(|e| format!("Edit {}: {}", index + 1, e))?;
+    }
+    Ok(content)
+}
+
+/// One `@@ -a,b +c,d @@` section of a unified diff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub header: String,
+    /// Line the hunk claims to start at (1-based), used as a hint only.
+    pub old_start: usize,
+    /// Context and removed lines: what the file must contain.
+    pub old_lines: Vec<String>,
+    /// Context and added lines: what replaces them.
+    pub new_lines: Vec<String>,
+    /// The hunk's last added or context line is followed by `\ No newline at end of file`.
+    pub no_final_newline: bool,
+}
+
+/// The changes to one file. A missing old path creates the file, a missing new path deletes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+impl FilePatch {
+    /// The file the patch applies to.
+    pub fn path(&self) -> &str {
+        self.new_path.as_deref().or(self.old_path.as_deref()).unwrap_or_default()
+    }
+
+    pub fn is_creation(&self) -> bool {
+        self.old_path.is_none()
+    }
+
+    pub fn is_deletion(&self) -> bool {
+        self.new_path.is_none()
+    }
+
+    /// The new content of the file (`None` when it is deleted) given its current content
+    /// (`None` when it does not exist), or a message for every hunk that does not apply.
+    pub fn apply(&self, original: Option<&str>) -> Result<Option<String>, Vec<String>> {
+        let original = match (original, self.is_creation()) {
+            (Some(_), true) => return Err(vec![format!("{}: file already exists", self.path())]),
+            (None, false) => return Err(vec![format!("{}: file not found", self.path())]),
+            (original, _) => original.unwrap_or_default(),
+        };
+        let line_ending = if original.contains("\r\n") { "\r\n" } else { "\n" };
+        let mut lines: Vec<String> = original.lines().map(String::from).collect();
+        let mut final_newline = original.is_empty() || original.ends_with('\n');
+
+        // Hunks are located in the original file, then applied from the last to the first
+        // so that earlier positions stay valid.
+        let mut placements = Vec::new();
+        let mut errors = Vec::new();
+        let mut search_from = 0;
+        for (index, hunk) in self.hunks.iter().enumerate() {
+            match locate(&lines, hunk, search_from) {
+                Some(position) => {
+                    placements.push((position, hunk));
+                    search_from = position + hunk.old_lines.len();
+                }
+                None => errors.push(format!(
+                    "{}: hunk {} ({}) does not match the file: {}",
+                    self.path(),
+                    index + 1,
+                    hunk.header,
+                    hunk.old_lines.first().map_or("<empty>".to_string(), |line| format!("expected `{}`", line.trim()))
+                )),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Whether the last hunk reaches the end of the file decides its final newline.
+        let reaches_end = placements.last().is_some_and(|(position, hunk)| position + hunk.old_lines.len() == lines.len());
+        for (position, hunk) in placements.iter().rev() {
+            lines.splice(*position..*position + hunk.old_lines.len(), hunk.new_lines.iter().cloned());
+        }
+        if self.is_deletion() {
+            if !lines.is_empty() {
+                return Err(vec![format!("{}: the patch deletes the file but does not remove all of its lines", self.path())]);
+            }
+            return Ok(None);
+        }
+        if let Some((_, hunk)) = placements.last().filter(|_| reaches_end) {
+            final_newline = !hunk.no_final_newline;
+        }
+
+        let mut content = lines.join(line_ending);
+        if final_newline && !lines.is_empty() {
+            content.push_str(line_ending);
+        }
+        Ok(Some(content))
+    }
+}
+
+/// Where `hunk` applies, at or after `from`: at its stated line if it matches there,
+/// otherwise at the nearest match, trying an exact match before one that ignores
+/// trailing whitespace.
+fn locate(lines: &[String], hunk: &Hunk, from: usize) -> Option<usize> {
+    let size = hunk.old_lines.len();
+    if size == 0 {
+        // Pure insertion: trust the line number.
+        let position = if hunk.old_start == 0 { 0 } else { hunk.old_start.min(lines.len()) };
+        return Some(position.max(from.min(lines.len())));
+    }
+    if lines.len() < size {
+        return None;
+    }
+
+    let hint = hunk.old_start.saturating_sub(1).max(from);
+    let mut candidates: Vec<usize> = (from..=lines.len() - size).collect();
+    candidates.sort_by_key(|&start| start.abs_diff(hint));
+    let matches = |start: usize, same: &dyn Fn(&str, &str) -> bool| {
+        hunk.old_lines.iter().zip(&lines[start..start + size]).all(|(expected, actual)| same(expected, actual))
+    };
+    candidates
+        .iter()
+        .copied()
+        .find(|&start| matches(start, &|a, b| a == b))
+        .or_else(|| candidates.iter().copied().find(|&start| matches(start, &|a, b| a.trim_end() == b.trim_end())))
+}
+
+/// Parses a unified diff such as `git diff` or `diff -u` produces. Hunk line counts are not
+/// trusted; a hunk runs until the next hunk or file header.
+pub fn parse_patch(text: &str) -> Result<Vec<FilePatch>> {
+    let lines: Vec<&str> = text.trim_end_matches('\n').lines().collect();
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        if line.starts_with("--- ") && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ ")) {
+            patches.push(FilePatch {
+                old_path: header_path(&line[4..]),
+                new_path: header_path(&lines[i + 1][4..]),
+                hunks: Vec::new(),
+            });
+            i += 2;
+            continue;
+        }
+        if line.starts_with("@@") {
+            let patch = patches
+                .last_mut()
+                .ok_or_else(|| anyhow!("Hunk '{}' comes before any ---/+++ file header", line))?;
+            let old_start = parse_hunk_start(line).ok_or_else(|| anyhow!("Invalid hunk header '{}'", line))?;
+            let mut hunk = Hunk {
+                header: line.trim_end().to_string(),
+                old_start,
+                old_lines: Vec::new(),
+                new_lines: Vec::new(),
+                no_final_newline: false,
+            };
+            i += 1;
+            let mut last_in_new = false;
+            while i < lines.len() {
+                let body = lines[i];
+                let next_file = body.starts_with("--- ") && lines.get(i + 1).is_some_and(|next| next.starts_with("+++ "));
+                if body.starts_with("@@") || body.starts_with("diff ") || next_file {
+                    break;
+                }
+                if let Some(rest) = body.strip_prefix('+') {
+                    hunk.new_lines.push(rest.to_string());
+                    last_in_new = true;
+                } else if let Some(rest) = body.strip_prefix('-') {
+                    hunk.old_lines.push(rest.to_string());
+                    last_in_new = false;
+                } else if body.starts_with('\\') {
+                    hunk.no_final_newline = last_in_new;
+                } else {
+                    // Context; an empty line is context whose leading space was lost.
+                    let rest = body.strip_prefix(' ').unwrap_or(body);
+                    hunk.old_lines.push(rest.to_string());
+                    hunk.new_lines.push(rest.to_string());
+                    last_in_new = true;
+                }
+                i += 1;
+            }
+            patch.hunks.push(hunk);
+            continue;
+        }
+        // `diff --git`, `index`, and anything else between files.
+        i += 1;
+    }
+
+    if patches.is_empty() {
+        return Err(anyhow!("No file headers (--- a/path, +++ b/path) found in the patch"));
+    }
+    if let Some(patch) = patches.iter().find(|patch| patch.old_path.is_none() && patch.new_path.is_none()) {
+        return Err(anyhow!("A file header in the patch names no file: {:?}", patch));
+    }
+    Ok(patches)
+}
+
+/// The path of a `---`/`+++` header without its `a/`/`b/` prefix or timestamp, `None`
+/// for `/dev/null`.
+fn header_path(header: &str) -> Option<String> {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path);
+    Some(path.to_string())
+}
+
+fn parse_hunk_start(header: &str) -> Option<usize> {
+    let old = header.strip_prefix("@@")?.trim_start().strip_prefix('-')?;
+    let digits: String = old.chars().take_while(|c| c.is_ascii_digit()).collect();
+    digits.parse().ok()
+}
@@ -0,0 +1,64 @@
+use anyhow::{Result, anyhow};
+use std::path::{Component, Path, PathBuf};
+
+/// Resolves `path` against `working_directory` and rejects anything outside it. The longest
+/// existing part of the path is canonicalized, so symlinks cannot escape, whether the path
+/// points at them or at something not created yet below them; the rest is normalized
+/// lexically so `..` cannot either.
+pub fn validate_path(working_directory: &Path, path: &str) -> Result<PathBuf> {
+    let requested_path = Path::new(path);
+    let joined = if requested_path.is_absolute() {
+        requested_path.to_path_buf()
+    } else {
+        working_directory.join(requested_path)
+    };
+    let canonical_path = joined.canonicalize().or_else(|_| resolve_missing(&normalize_path(&joined)))?;
+
+    if !canonical_path.starts_with(working_directory) {
+        return Err(anyhow!(
+            "Path '{}' is outside the working directory. For security reasons, file operations are restricted to the working directory and its subdirectories.",
+            path
+        ));
+    }
+
+    Ok(canonical_path)
+}
+
+/// Canonicalizes the longest existing ancestor of `path`, which does not exist itself, and
+/// appends the missing components to it. A dangling symlink on the way is rejected, since
+/// writing through it would create its target wherever that is.
+fn resolve_missing(path: &Path) -> Result<PathBuf> {
+    let mut missing = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return Ok(missing.iter().rev().fold(canonical, |resolved, name| resolved.join(name)));
+        }
+        if existing.symlink_metadata().is_ok() {
+            return Err(anyhow!("Path '{}' goes through a symlink whose target does not exist.", path.display()));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return Ok(path.to_path_buf()),
+        }
+    }
+}
+
+/// Resolves `.` and `..` components without touching the filesystem, so paths that
+/// do not exist yet cannot escape the working directory through `..`.
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
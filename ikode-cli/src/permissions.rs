@@ -0,0 +1,309 @@
+use anyhow::{Result, anyhow};
+use globset::{GlobBuilder, GlobMatcher};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+/// What happens to a tool call, ordered from the most to the least permissive.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl fmt::Display for Decision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Allow => "allow",
+            Self::Ask => "ask",
+            Self::Deny => "deny",
+        })
+    }
+}
+
+/// The outcome of asking for permission.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Authorization {
+    Allowed,
+    /// Blocked by a rule, for the given reason.
+    Denied(String),
+    /// The user said no.
+    Rejected,
+    /// The user said no and told the model what to do instead.
+    Feedback(String),
+}
+
+/// A permission rule from a config file:
+///
+/// ```toml
+/// [[permissions]]
+/// tool = "execute_command"
+/// command = "cargo test*"
+/// action = "allow"
+///
+/// [[permissions]]
+/// tool = "edit_file"
+/// path = "migrations/**"
+/// action = "ask"
+/// ```
+///
+/// `tool` is a pattern over the tool name: `*` for every tool, `mcp__github__*` for the tools
+/// of one MCP server. `path` is a glob over paths relative to the working directory, where
+/// `*` stays within one directory and `**` crosses them. `command` is a pattern over the
+/// command line, where `*` matches anything. A rule with `path` or `command` only applies
+/// to calls that have one.
+///
+/// Command lines are shell scripts, so a `*` in an allow pattern could otherwise let a
+/// chained command through: an allow pattern with `*` never matches a command that uses
+/// shell control syntax (see `has_shell_syntax`), which is asked about instead. Deny and
+/// ask patterns match the whole command line or any command in it, so `rm -rf*` also
+/// catches `make clean && rm -rf /`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionRule {
+    pub tool: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    pub action: Decision,
+}
+
+impl PermissionRule {
+    fn matches(&self, request: &PermissionRequest, path_glob: Option<&GlobMatcher>) -> bool {
+        if !wildcard_match(&self.tool, &request.tool) {
+            return false;
+        }
+        if let Some(glob) = path_glob {
+            match &request.path {
+                Some(path) if glob.is_match(path) => {}
+                _ => return false,
+            }
+        }
+        if let Some(pattern) = &self.command {
+            let Some(command) = &request.command else {
+                return false;
+            };
+            let command = command.trim();
+            let matched = match self.action {
+                Decision::Allow => (!pattern.contains('*') || !has_shell_syntax(command)) && wildcard_match(pattern, command),
+                Decision::Ask | Decision::Deny => {
+                    wildcard_match(pattern, command) || command_segments(command).any(|segment| wildcard_match(pattern, segment))
+                }
+            };
+            if !matched {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Whether this session grant covers `request`. Grants hold the literal tool name and
+    /// command line the user approved, so unlike configured rules they have no wildcards:
+    /// approving `rm *.tmp` does not allow `rm -rf ~ x.tmp`.
+    fn grants(&self, request: &PermissionRequest) -> bool {
+        self.tool == request.tool && (self.command.is_none() || self.command == request.command)
+    }
+}
+
+impl fmt::Display for PermissionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action, self.tool)?;
+        if let Some(path) = &self.path {
+            write!(f, " path={}", path)?;
+        }
+        if let Some(command) = &self.command {
+            write!(f, " command=\"{}\"", command)?;
+        }
+        Ok(())
+    }
+}
+
+/// A tool call that needs permission, described by what the rules can match on.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionRequest {
+    pub tool: String,
+    /// Path relative to the working directory, with `/` separators.
+    pub path: Option<String>,
+    pub command: Option<String>,
+}
+
+impl PermissionRequest {
+    pub fn command(tool: &str, command: &str) -> Self {
+        Self { tool: tool.to_string(), command: Some(command.to_string()), ..Default::default() }
+    }
+
+    pub fn path(tool: &str, path: &str) -> Self {
+        Self { tool: tool.to_string(), path: Some(path.to_string()), ..Default::default() }
+    }
+
+    /// A call matched by its tool name alone, such as an MCP tool.
+    pub fn tool(tool: &str) -> Self {
+        Self { tool: tool.to_string(), ..Default::default() }
+    }
+
+    /// What the call acts on, for prompts and the audit log.
+    pub fn target(&self) -> &str {
+        self.command.as_deref().or(self.path.as_deref()).unwrap_or_default()
+    }
+}
+
+/// The permission rules in force: configured rules, rules granted during the session,
+/// and the decision for calls no rule matches (`ask`, or `allow` in brave mode).
+///
+/// Deny rules win over ask rules, which win over allow rules, so an allow rule can never
+/// unlock something a deny rule blocks.
+pub struct Permissions {
+    rules: Vec<(PermissionRule, Option<GlobMatcher>)>,
+    session_rules: Vec<PermissionRule>,
+    default: Decision,
+}
+
+impl Permissions {
+    pub fn new(rules: Vec<PermissionRule>, brave: bool) -> Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                let glob = match &rule.path {
+                    Some(path) => Some(
+                        GlobBuilder::new(path)
+                            .literal_separator(true)
+                            .build()
+                            .map_err(|e| anyhow!("Invalid path pattern in permission rule '{}': {}", rule, e))?
+                            .compile_matcher(),
+                    ),
+                    None => None,
+                };
+                Ok((rule, glob))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules, session_rules: Vec::new(), default: if brave { Decision::Allow } else { Decision::Ask } })
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &PermissionRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    pub fn session_rules(&self) -> &[PermissionRule] {
+        &self.session_rules
+    }
+
+    pub fn default_decision(&self) -> Decision {
+        self.default
+    }
+
+    /// The decision for `request` and the reason for it.
+    pub fn check(&self, request: &PermissionRequest) -> (Decision, String) {
+        let matching: Vec<&PermissionRule> = self.rules
+            .iter()
+            .filter(|(rule, glob)| rule.matches(request, glob.as_ref()))
+            .map(|(rule, _)| rule)
+            .collect();
+
+        for decision in [Decision::Deny, Decision::Ask] {
+            if let Some(rule) = matching.iter().find(|rule| rule.action == decision) {
+                return (decision, format!("rule: {}", rule));
+            }
+        }
+        if let Some(rule) = matching.first() {
+            return (Decision::Allow, format!("rule: {}", rule));
+        }
+        if let Some(rule) = self.session_rules.iter().find(|rule| rule.grants(request)) {
+            return (Decision::Allow, format!("allowed for this session: {}", rule));
+        }
+        let reason = if self.default == Decision::Allow { "brave mode" } else { "no matching rule" };
+        (self.default, reason.to_string())
+    }
+
+    /// Allows calls like `request` for the rest of the session: exactly the same command line
+    /// for commands, the tool as a whole for file changes.
+    pub fn allow_for_session(&mut self, request: &PermissionRequest) -> PermissionRule {
+        let rule = PermissionRule {
+            tool: request.tool.clone(),
+            path: None,
+            command: request.command.clone(),
+            action: Decision::Allow,
+        };
+        self.session_rules.push(rule.clone());
+        rule
+    }
+}
+
+/// One line of the audit log.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEntry {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub session_id: String,
+    pub tool: String,
+    pub target: String,
+    pub decision: Decision,
+    /// Whether the call went ahead, after asking the user if the decision was `ask`.
+    pub approved: bool,
+    pub reason: String,
+}
+
+/// Appends every permission decision as a JSON line, e.g. to
+/// `~/.local/share/ikode/projects/<project>/audit.jsonl`.
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn record(&self, entry: &AuditEntry) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+}
+
+/// Whether `command` does more than run one simple command: chains (`;`, `&&`, `||`,
+/// newlines), pipes, background jobs, command substitution (`` ` ``, `$(`) or redirections.
+pub fn has_shell_syntax(command: &str) -> bool {
+    command.contains(['\n', ';', '&', '|', '`', '<', '>']) || command.contains("$(")
+}
+
+/// The commands a command line is made of, split at every operator and bracket that can
+/// start a new one. Quotes are ignored, so this may split more than the shell would.
+pub fn command_segments(command: &str) -> impl Iterator<Item = &str> {
+    command
+        .split(['\n', ';', '&', '|', '`', '(', ')', '{', '}'])
+        .map(|segment| segment.trim().trim_start_matches('!').trim_start())
+        .filter(|segment| !segment.is_empty())
+}
+
+/// Matches `text` against `pattern`, where `*` stands for any run of characters and
+/// everything else must match literally.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
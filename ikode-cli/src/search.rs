@@ -0,0 +1,209 @@
+use anyhow::{Result, anyhow};
+use globset::{GlobBuilder, GlobMatcher};
+use ignore::{DirEntry, WalkBuilder};
+use regex::{Regex, RegexBuilder};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+/// Matches returned by `grep` unless the model asks for fewer (or more, up to `MAX_RESULTS`).
+pub const DEFAULT_GREP_RESULTS: usize = 100;
+/// Paths returned by `glob` unless the model asks for fewer (or more, up to `MAX_RESULTS`).
+pub const DEFAULT_GLOB_RESULTS: usize = 500;
+pub const MAX_RESULTS: usize = 2000;
+/// Entries listed by `list_directory`.
+pub const MAX_ENTRIES: usize = 1000;
+/// Longest line shown by `grep`; minified files would otherwise flood the context.
+const MAX_LINE_LENGTH: usize = 300;
+/// Files larger than this are skipped by `grep`, like `read_file` refuses them.
+const MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// What to search for with `grep`.
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    pub pattern: String,
+    /// Glob over paths relative to the search path, e.g. `*.rs` or `src/**/*.ts`.
+    pub include: Option<String>,
+    /// Lines shown before and after each match.
+    pub context: usize,
+    pub max_results: usize,
+    pub case_insensitive: bool,
+}
+
+impl GrepOptions {
+    pub fn new(pattern: &str) -> Self {
+        Self {
+            pattern: pattern.to_string(),
+            include: None,
+            context: 0,
+            max_results: DEFAULT_GREP_RESULTS,
+            case_insensitive: false,
+        }
+    }
+}
+
+/// Walks `path` like git would see it: `.gitignore`, `.ignore` and global git excludes
+/// are honoured (in or outside a repository), hidden files are included, `.git` is not.
+fn walker(path: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(path);
+    builder
+        .hidden(false)
+        .require_git(false)
+        .sort_by_file_name(|a, b| a.cmp(b))
+        .filter_entry(|entry| entry.file_name() != ".git");
+    builder
+}
+
+fn glob_matcher(pattern: &str) -> Result<GlobMatcher> {
+    Ok(GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .map_err(|e| anyhow!("Invalid glob '{}': {}", pattern, e))?
+        .compile_matcher())
+}
+
+/// `path` relative to `root`, with `/` separators, for output and glob matching.
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().replace('\\', "/")
+}
+
+fn files(path: &Path) -> impl Iterator<Item = DirEntry> {
+    walker(path)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+}
+
+/// Searches the files under `path` (or the file `path`) for a regular expression, printing
+/// `file:line:text` for matches and `file-line-text` for context lines, with paths relative
+/// to `root`.
+pub fn grep(root: &Path, path: &Path, options: &GrepOptions) -> Result<String> {
+    let regex = RegexBuilder::new(&options.pattern)
+        .case_insensitive(options.case_insensitive)
+        .build()
+        .map_err(|e| anyhow!("Invalid regex '{}': {}", options.pattern, e))?;
+    let include = options.include.as_deref().map(glob_matcher).transpose()?;
+    let max_results = options.max_results.clamp(1, MAX_RESULTS);
+
+    let mut output = Vec::new();
+    let mut matches = 0;
+    for entry in files(path) {
+        if let Some(include) = &include {
+            if !include.is_match(relative(path, entry.path())) {
+                continue;
+            }
+        }
+        if entry.metadata().map_or(true, |m| m.len() > MAX_FILE_SIZE) {
+            continue;
+        }
+        let Ok(bytes) = fs::read(entry.path()) else { continue };
+        if bytes[..bytes.len().min(8192)].contains(&0) {
+            continue;
+        }
+        let content = String::from_utf8_lossy(&bytes);
+        let name = relative(root, entry.path());
+        matches += grep_file(&name, &content, &regex, options.context, max_results - matches, &mut output);
+        if matches >= max_results {
+            output.push(format!(
+                "[Stopped after {} matches. Narrow the search with path or include, or raise max_results.]",
+                max_results
+            ));
+            break;
+        }
+    }
+
+    if output.is_empty() {
+        return Ok("No matches found.".to_string());
+    }
+    Ok(output.join("\n"))
+}
+
+/// Appends up to `limit` matches of one file to `output`, returning how many it found.
+fn grep_file(name: &str, content: &str, regex: &Regex, context: usize, limit: usize, output: &mut Vec<String>) -> usize {
+    let lines: Vec<&str> = content.lines().collect();
+    let matched: Vec<usize> = (0..lines.len()).filter(|&i| regex.is_match(lines[i])).take(limit).collect();
+    if matched.is_empty() {
+        return 0;
+    }
+
+    let shown: BTreeSet<usize> = matched
+        .iter()
+        .flat_map(|&i| i.saturating_sub(context)..(i + context + 1).min(lines.len()))
+        .collect();
+    let mut previous: Option<usize> = None;
+    for i in shown {
+        if context > 0 && previous.map_or(!output.is_empty(), |p| i > p + 1) {
+            output.push("--".to_string());
+        }
+        let separator = if matched.binary_search(&i).is_ok() { ':' } else { '-' };
+        output.push(format!("{}{}{}{}{}", name, separator, i + 1, separator, shorten(lines[i])));
+        previous = Some(i);
+    }
+    matched.len()
+}
+
+fn shorten(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LENGTH) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+/// Files under `path` whose path relative to `path` matches `pattern` (`*` stays within a
+/// directory, `**` crosses them), sorted and relative to `root`.
+pub fn glob(root: &Path, path: &Path, pattern: &str, max_results: usize) -> Result<String> {
+    let matcher = glob_matcher(pattern)?;
+    let max_results = max_results.clamp(1, MAX_RESULTS);
+
+    let mut found: Vec<String> = Vec::new();
+    let mut truncated = false;
+    for entry in files(path) {
+        if matcher.is_match(relative(path, entry.path())) {
+            if found.len() == max_results {
+                truncated = true;
+                break;
+            }
+            found.push(relative(root, entry.path()));
+        }
+    }
+
+    if found.is_empty() {
+        return Ok(format!("No files match '{}'.", pattern));
+    }
+    if truncated {
+        found.push(format!("[Stopped after {} files. Use a narrower pattern or path.]", max_results));
+    }
+    Ok(found.join("\n"))
+}
+
+/// The entries of the directory `path` down to `depth` levels, indented by level, with
+/// directories marked by a trailing `/` and files followed by their size.
+pub fn list_directory(path: &Path, depth: usize) -> Result<String> {
+    if !path.is_dir() {
+        return Err(anyhow!("'{}' is not a directory", path.display()));
+    }
+
+    let mut lines = Vec::new();
+    for entry in walker(path).max_depth(Some(depth.max(1))).build().filter_map(|entry| entry.ok()) {
+        if entry.depth() == 0 {
+            continue;
+        }
+        if lines.len() == MAX_ENTRIES {
+            lines.push(format!("[Stopped after {} entries. List a subdirectory or use a smaller depth.]", MAX_ENTRIES));
+            break;
+        }
+        let indent = "  ".repeat(entry.depth() - 1);
+        let name = entry.file_name().to_string_lossy();
+        if entry.file_type().is_some_and(|t| t.is_dir()) {
+            lines.push(format!("{}{}/", indent, name));
+        } else {
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            lines.push(format!("{}{} ({} bytes)", indent, name, size));
+        }
+    }
+
+    if lines.is_empty() {
+        return Ok("The directory is empty.".to_string());
+    }
+    Ok(lines.join("\n"))
+}
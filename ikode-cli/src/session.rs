@@ -0,0 +1,203 @@
+use crate::compaction::CompactionSummary;
+use crate::cost::UsageTotals;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
+use gaise_core::contracts::{GaiseContent, GaiseMessage, OneOrMany};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Version of the on-disk session format. Bump this when the layout of `Session` changes
+/// in a way older builds cannot read.
+pub const SESSION_FORMAT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Todo {
+    pub id: usize,
+    pub task: String,
+    pub completed: bool,
+}
+
+/// Everything needed to pick a conversation back up: the message history, the todo list,
+/// the cached compaction summary, the token and cost totals and the cache key sent to
+/// providers that support prompt caching.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    pub version: u32,
+    pub id: String,
+    pub working_directory: PathBuf,
+    pub model: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub session_cache_key: String,
+    pub history: Vec<GaiseMessage>,
+    #[serde(default)]
+    pub todos: Vec<Todo>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compaction: Option<CompactionSummary>,
+    #[serde(default)]
+    pub usage: UsageTotals,
+}
+
+/// A lightweight view of a saved session used when listing sessions.
+#[derive(Debug, Clone)]
+pub struct SessionSummary {
+    pub id: String,
+    pub model: String,
+    pub updated_at: DateTime<Utc>,
+    pub message_count: usize,
+    pub title: String,
+}
+
+impl Session {
+    pub fn summary(&self) -> SessionSummary {
+        let title = self.history.iter()
+            .find(|m| m.role == "user")
+            .and_then(|m| match &m.content {
+                Some(OneOrMany::One(GaiseContent::Text { text })) => Some(text.clone()),
+                Some(OneOrMany::Many(parts)) => parts.iter().find_map(|p| match p {
+                    GaiseContent::Text { text } => Some(text.clone()),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .unwrap_or_default();
+
+        SessionSummary {
+            id: self.id.clone(),
+            model: self.model.clone(),
+            updated_at: self.updated_at,
+            message_count: self.history.len(),
+            title: title.lines().next().unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// Stores sessions as one JSON file per session inside a per-project directory.
+pub struct SessionStore {
+    dir: PathBuf,
+    /// The project whose sessions `list` and `latest` offer; `None` offers them all.
+    working_directory: Option<PathBuf>,
+}
+
+impl SessionStore {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir, working_directory: None }
+    }
+
+    /// Returns the store for `working_directory`, located under the user's data directory
+    /// (e.g. `~/.local/share/ikode/projects/-home-me-project-225df3094012/sessions`).
+    pub fn for_project(working_directory: &Path) -> Option<Self> {
+        let data_dir = dirs::data_dir()?;
+        let store = Self::new(data_dir.join("ikode").join("projects").join(project_key(working_directory)).join("sessions"));
+        Some(store.only_for(working_directory))
+    }
+
+    /// Limits `list` and `latest` to the sessions of `working_directory`, so a session saved
+    /// for another project is never resumed here.
+    pub fn only_for(mut self, working_directory: &Path) -> Self {
+        self.working_directory = Some(working_directory.to_path_buf());
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Writes the session to disk. The file is written to a temporary path first and then
+    /// renamed so a crash mid-write never leaves a truncated session behind.
+    pub fn save(&self, session: &Session) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(format!("{}.json", session.id));
+        let tmp_path = self.dir.join(format!("{}.json.tmp", session.id));
+        fs::write(&tmp_path, serde_json::to_vec_pretty(session)?)?;
+        fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Loads a session by its full id or by a unique id prefix. Ids that could name a file
+    /// outside the store (path separators, `..`) are rejected.
+    pub fn load(&self, id: &str) -> Result<Session> {
+        if id.is_empty() || id.contains(['/', '\\']) || id.contains("..") {
+            return Err(anyhow!("Invalid session id '{}'", id));
+        }
+
+        let exact = self.dir.join(format!("{}.json", id));
+        if exact.exists() {
+            return Self::read(&exact);
+        }
+
+        let matches: Vec<PathBuf> = self.session_files()?
+            .into_iter()
+            .filter(|p| p.file_stem().and_then(|s| s.to_str()).is_some_and(|s| s.starts_with(id)))
+            .collect();
+
+        match matches.len() {
+            0 => Err(anyhow!("No saved session matches '{}'", id)),
+            1 => Self::read(&matches[0]),
+            n => Err(anyhow!("Session id '{}' is ambiguous ({} sessions match)", id, n)),
+        }
+    }
+
+    /// Lists saved sessions of the project, most recently updated first. Unreadable files are
+    /// skipped.
+    pub fn list(&self) -> Result<Vec<SessionSummary>> {
+        let mut summaries: Vec<SessionSummary> = self.session_files()?
+            .iter()
+            .filter_map(|p| Self::read(p).ok())
+            .filter(|s| self.working_directory.as_ref().is_none_or(|dir| *dir == s.working_directory))
+            .map(|s| s.summary())
+            .collect();
+        summaries.sort_by_key(|s| std::cmp::Reverse(s.updated_at));
+        Ok(summaries)
+    }
+
+    /// Loads the most recently updated session, if any.
+    pub fn latest(&self) -> Result<Option<Session>> {
+        match self.list()?.first() {
+            Some(summary) => Ok(Some(self.load(&summary.id)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn session_files(&self) -> Result<Vec<PathBuf>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                files.push(path);
+            }
+        }
+        Ok(files)
+    }
+
+    fn read(path: &Path) -> Result<Session> {
+        let content = fs::read_to_string(path)?;
+        let session: Session = serde_json::from_str(&content)
+            .map_err(|e| anyhow!("Could not parse session file {}: {}", path.display(), e))?;
+        if session.version > SESSION_FORMAT_VERSION {
+            return Err(anyhow!(
+                "Session {} was written by a newer version of ikode (format {}, supported {})",
+                session.id, session.version, SESSION_FORMAT_VERSION
+            ));
+        }
+        Ok(session)
+    }
+}
+
+/// Turns a working directory into a single directory name, e.g. `/home/me/project` ->
+/// `-home-me-project-225df3094012`. The readable part alone would give `/a/b-c` and `/a-b/c`
+/// the same name, so a hash of the whole path follows it.
+pub fn project_key(working_directory: &Path) -> String {
+    let name: String = working_directory
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '.' { c } else { '-' })
+        .collect();
+    let hash = format!("{:x}", Sha256::digest(working_directory.as_os_str().as_encoded_bytes()));
+    format!("{}-{}", name, &hash[..12])
+}
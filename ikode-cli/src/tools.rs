@@ -1,5 +1,6 @@
 use gaise_core::contracts::{GaiseTool, GaiseToolParameter};
 use serde::Deserialize;
+use serde_json::Value;
 use std::collections::HashMap;
 
 #[derive(Deserialize)]
@@ -21,6 +22,18 @@ pub struct TodoCompleteArgs {
 #[derive(Deserialize)]
 pub struct ExecuteCommandArgs {
     pub command: String,
+    /// Seconds before the command is killed.
+    pub timeout: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct StartBackgroundCommandArgs {
+    pub command: String,
+}
+
+#[derive(Deserialize)]
+pub struct BackgroundJobArgs {
+    pub id: usize,
 }
 
 #[derive(Deserialize)]
@@ -30,6 +43,29 @@ pub struct ReadFileArgs {
     pub limit: Option<usize>,
 }
 
+#[derive(Deserialize)]
+pub struct GrepArgs {
+    pub pattern: String,
+    pub path: Option<String>,
+    pub include: Option<String>,
+    pub context: Option<usize>,
+    pub max_results: Option<usize>,
+    pub case_insensitive: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct GlobArgs {
+    pub pattern: String,
+    pub path: Option<String>,
+    pub max_results: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct ListDirectoryArgs {
+    pub path: Option<String>,
+    pub depth: Option<usize>,
+}
+
 #[derive(Deserialize)]
 pub struct EditFileArgs {
     pub path: String,
@@ -37,12 +73,52 @@ pub struct EditFileArgs {
     pub new_text: String,
 }
 
+#[derive(Deserialize)]
+pub struct TextEdit {
+    pub old_text: String,
+    pub new_text: String,
+}
+
+#[derive(Deserialize)]
+pub struct MultiEditArgs {
+    pub path: String,
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Deserialize)]
+pub struct ApplyPatchArgs {
+    pub patch: String,
+}
+
 #[derive(Deserialize)]
 pub struct CreateFileArgs {
     pub path: String,
     pub content: String,
 }
 
+#[derive(Deserialize)]
+pub struct DeleteFileArgs {
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct MoveFileArgs {
+    pub source: String,
+    pub destination: String,
+}
+
+#[derive(Deserialize)]
+pub struct CreateDirectoryArgs {
+    pub path: String,
+}
+
+#[derive(Deserialize)]
+pub struct SpawnAgentArgs {
+    pub task: String,
+    pub model: Option<String>,
+    pub tools: Option<Vec<String>>,
+}
+
 pub fn get_tools() -> Vec<GaiseTool> {
     vec![
         GaiseTool {
@@ -62,7 +138,6 @@ pub fn get_tools() -> Vec<GaiseTool> {
                             ..Default::default()
                         })),
                         required: None,
-                        ..Default::default()
                     });
                     p
                 }),
@@ -111,7 +186,6 @@ pub fn get_tools() -> Vec<GaiseTool> {
                             ..Default::default()
                         })),
                         required: None,
-                        ..Default::default()
                     });
                     p
                 }),
@@ -132,7 +206,7 @@ pub fn get_tools() -> Vec<GaiseTool> {
         },
         GaiseTool {
             name: "execute_command".to_string(),
-            description: Some("Executes a shell command.".to_string()),
+            description: Some("Executes a shell command and returns its exit code, stdout and stderr. Stdin is closed, long output is shortened to its beginning and end, and the command is killed when it exceeds its timeout. Do not use it for commands that never finish on their own, such as watchers or servers.".to_string()),
             parameters: Some(GaiseToolParameter {
                 r#type: Some("object".to_string()),
                 description: None,
@@ -145,12 +219,74 @@ pub fn get_tools() -> Vec<GaiseTool> {
                         required: None,
                         ..Default::default()
                     });
+                    p.insert("timeout".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("Seconds to wait before killing the command. Defaults to 120, at most 1800.".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["command".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "start_background_command".to_string(),
+            description: Some("Starts a shell command that keeps running in the background, such as a dev server or a file watcher, and returns its job id with its first output. It is not subject to a timeout; stop it with kill_background_command when it is no longer needed.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("command".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("The command to start".to_string()),
+                        ..Default::default()
+                    });
                     p
                 }),
                 required: Some(vec!["command".to_string()]),
                 ..Default::default()
             }),
         },
+        GaiseTool {
+            name: "read_background_output".to_string(),
+            description: Some("Returns the status of a background job and the output it produced since the last read.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("id".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("The job id returned by start_background_command".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["id".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "kill_background_command".to_string(),
+            description: Some("Stops a background job and every process it started.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("id".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("The job id returned by start_background_command".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["id".to_string()]),
+                ..Default::default()
+            }),
+        },
         GaiseTool {
             name: "read_file".to_string(),
             description: Some("Reads a file's content with line numbers. Returns at most 2000 lines. Use offset and limit to read specific line ranges of large files.".to_string()),
@@ -182,6 +318,103 @@ pub fn get_tools() -> Vec<GaiseTool> {
                 ..Default::default()
             }),
         },
+        GaiseTool {
+            name: "grep".to_string(),
+            description: Some("Searches file contents with a regular expression and returns matching lines as path:line:text. Skips files ignored by .gitignore, binary files and the .git directory. Prefer this over grep or find in execute_command.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("pattern".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Regular expression (Rust regex syntax) to search for".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("File or directory to search. Defaults to the working directory.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("include".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Only search files whose path relative to `path` matches this glob, e.g. \"**/*.rs\"".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("context".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("Lines to show before and after each match. Defaults to 0.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("max_results".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("Maximum number of matching lines. Defaults to 100.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("case_insensitive".to_string(), GaiseToolParameter {
+                        r#type: Some("boolean".to_string()),
+                        description: Some("Ignore case when matching. Defaults to false.".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["pattern".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "glob".to_string(),
+            description: Some("Finds files by path pattern, e.g. \"**/*.rs\" or \"src/*/mod.rs\", skipping files ignored by .gitignore. Returns matching paths, sorted.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("pattern".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Glob over paths relative to `path`; * stays within a directory, ** matches any number of directories".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Directory to search. Defaults to the working directory.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("max_results".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("Maximum number of paths. Defaults to 500.".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["pattern".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "list_directory".to_string(),
+            description: Some("Lists the files and subdirectories of a directory with file sizes, skipping files ignored by .gitignore.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Directory to list. Defaults to the working directory.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("depth".to_string(), GaiseToolParameter {
+                        r#type: Some("integer".to_string()),
+                        description: Some("How many levels of subdirectories to descend into. Defaults to 1 (only the directory itself).".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: None,
+                ..Default::default()
+            }),
+        },
         GaiseTool {
             name: "edit_file".to_string(),
             description: Some("Performs a search-and-replace edit on an existing file. The old_text must match exactly (including whitespace and indentation). For multiple edits to the same file, call this tool multiple times.".to_string()),
@@ -217,6 +450,68 @@ pub fn get_tools() -> Vec<GaiseTool> {
                 ..Default::default()
             }),
         },
+        GaiseTool {
+            name: "multi_edit".to_string(),
+            description: Some("Applies several search-and-replace edits to one file in order, each to the result of the previous one. Every old_text must match exactly once at the time it is applied; if any edit fails, the file is left unchanged. Prefer this over repeated edit_file calls on the same file.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Path to the file to edit".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("edits".to_string(), GaiseToolParameter {
+                        r#type: Some("array".to_string()),
+                        description: Some("The edits, applied in order".to_string()),
+                        items: Some(Box::new(GaiseToolParameter {
+                            r#type: Some("object".to_string()),
+                            properties: Some({
+                                let mut e = HashMap::new();
+                                e.insert("old_text".to_string(), GaiseToolParameter {
+                                    r#type: Some("string".to_string()),
+                                    description: Some("The exact text to find and replace".to_string()),
+                                    ..Default::default()
+                                });
+                                e.insert("new_text".to_string(), GaiseToolParameter {
+                                    r#type: Some("string".to_string()),
+                                    description: Some("The replacement text".to_string()),
+                                    ..Default::default()
+                                });
+                                e
+                            }),
+                            required: Some(vec!["old_text".to_string(), "new_text".to_string()]),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["path".to_string(), "edits".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "apply_patch".to_string(),
+            description: Some("Applies a unified diff (as produced by `git diff` or `diff -u`) that may change, create (--- /dev/null) or delete (+++ /dev/null) several files. Every hunk is checked before anything is written; if any hunk does not match, nothing is changed and the failing hunks are listed. Include a few lines of unchanged context around each change.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("patch".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("The unified diff, with ---/+++ headers for each file and @@ hunks".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["patch".to_string()]),
+                ..Default::default()
+            }),
+        },
         GaiseTool {
             name: "create_file".to_string(),
             description: Some("Creates a new file with the given content. Fails if the file already exists.".to_string()),
@@ -245,5 +540,140 @@ pub fn get_tools() -> Vec<GaiseTool> {
                 ..Default::default()
             }),
         },
+        GaiseTool {
+            name: "delete_file".to_string(),
+            description: Some("Deletes a file or an empty directory. Use this instead of `rm` so the deletion can be undone.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Path of the file or empty directory to delete".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["path".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "move_file".to_string(),
+            description: Some("Moves or renames a file or directory. Fails if the destination already exists; missing parent directories are created.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("source".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Path of the file or directory to move".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("destination".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("New path for it".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["source".to_string(), "destination".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "create_directory".to_string(),
+            description: Some("Creates a directory and any missing parent directories.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("path".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Path of the directory to create".to_string()),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["path".to_string()]),
+                ..Default::default()
+            }),
+        },
+        GaiseTool {
+            name: "spawn_agent".to_string(),
+            description: Some("Delegates a task to a sub-agent with a fresh context and read-only tools (read_file, grep, glob, list_directory), and returns only its final answer. Use it for broad explorations, such as finding every place that parses dates, so their file contents do not fill your context. Sub-agents called in the same response run concurrently. The sub-agent sees none of this conversation, so describe the task completely.".to_string()),
+            parameters: Some(GaiseToolParameter {
+                r#type: Some("object".to_string()),
+                description: None,
+                properties: Some({
+                    let mut p = HashMap::new();
+                    p.insert("task".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("The complete task, including what the answer should contain".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("model".to_string(), GaiseToolParameter {
+                        r#type: Some("string".to_string()),
+                        description: Some("Model for the sub-agent. Defaults to the configured agent model or the current one.".to_string()),
+                        ..Default::default()
+                    });
+                    p.insert("tools".to_string(), GaiseToolParameter {
+                        r#type: Some("array".to_string()),
+                        description: Some("The read-only tools the sub-agent may use. Defaults to all of them.".to_string()),
+                        items: Some(Box::new(GaiseToolParameter {
+                            r#type: Some("string".to_string()),
+                            ..Default::default()
+                        })),
+                        ..Default::default()
+                    });
+                    p
+                }),
+                required: Some(vec!["task".to_string()]),
+                ..Default::default()
+            }),
+        },
     ]
 }
+
+/// Converts a JSON Schema, as external tools describe their parameters, to a tool parameter.
+/// `enum` and `default` have no field of their own and are added to the description.
+pub fn parameter_from_schema(schema: &Value) -> GaiseToolParameter {
+    let r#type = match schema.get("type") {
+        Some(Value::String(t)) => Some(t.clone()),
+        // `["string", "null"]`: the parameter is optional, which `required` already says.
+        Some(Value::Array(types)) => types.iter().filter_map(Value::as_str).find(|t| *t != "null").map(String::from),
+        _ => None,
+    };
+
+    let mut notes: Vec<String> = schema.get("description").and_then(Value::as_str).map(String::from).into_iter().collect();
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let values: Vec<String> = values.iter().map(Value::to_string).collect();
+        notes.push(format!("One of: {}.", values.join(", ")));
+    }
+    if let Some(default) = schema.get("default") {
+        notes.push(format!("Default: {}.", default));
+    }
+
+    let mut properties: Option<HashMap<String, GaiseToolParameter>> = schema
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| properties.iter().map(|(name, property)| (name.clone(), parameter_from_schema(property))).collect());
+    if properties.is_none() && r#type.as_deref() == Some("object") {
+        // Providers expect an object parameter to list its properties, even when there are none.
+        properties = Some(HashMap::new());
+    }
+
+    GaiseToolParameter {
+        r#type,
+        description: (!notes.is_empty()).then(|| notes.join(" ")),
+        properties,
+        items: schema.get("items").map(|items| Box::new(parameter_from_schema(items))),
+        required: schema
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|names| names.iter().filter_map(Value::as_str).map(String::from).collect()),
+    }
+}
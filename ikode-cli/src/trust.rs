@@ -0,0 +1,57 @@
+use anyhow::{Result, anyhow};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Project config files the user has trusted, remembered by path and by a hash of their
+/// content, so a file that changes after it was trusted has to be confirmed again.
+pub struct TrustStore {
+    path: PathBuf,
+}
+
+impl TrustStore {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The store under the user's data directory, e.g. `~/.local/share/ikode/trusted_projects.json`.
+    pub fn for_user() -> Option<Self> {
+        dirs::data_dir().map(|dir| Self::new(dir.join("ikode").join("trusted_projects.json")))
+    }
+
+    /// Whether `content` is what `config_path` held when it was trusted.
+    pub fn is_trusted(&self, config_path: &Path, content: &str) -> bool {
+        self.read().get(&key(config_path)) == Some(&content_hash(content))
+    }
+
+    /// Remembers `content` of `config_path` as trusted, replacing an earlier version.
+    pub fn trust(&self, config_path: &Path, content: &str) -> Result<()> {
+        let mut trusted = self.read();
+        trusted.insert(key(config_path), content_hash(content));
+
+        let dir = self.path.parent().ok_or_else(|| anyhow!("Invalid trust store path {}", self.path.display()))?;
+        fs::create_dir_all(dir)?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_vec_pretty(&trusted)?)?;
+        fs::rename(&tmp_path, &self.path)?;
+        Ok(())
+    }
+
+    /// Trusted files by path. A missing or unreadable store trusts nothing.
+    fn read(&self) -> BTreeMap<String, String> {
+        fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Hex SHA-256 of `content`.
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn key(config_path: &Path) -> String {
+    config_path.canonicalize().unwrap_or_else(|_| config_path.to_path_buf()).display().to_string()
+}
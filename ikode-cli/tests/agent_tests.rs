@@ -0,0 +1,178 @@
+#[allow(dead_code)]
+#[path = "../src/agents.rs"]
+mod agents;
+
+#[allow(dead_code)]
+#[path = "../src/compaction.rs"]
+mod compaction;
+
+#[allow(dead_code)]
+#[path = "../src/context.rs"]
+mod context;
+
+#[allow(dead_code)]
+#[path = "../src/tools.rs"]
+mod tools;
+
+use agents::{MAX_REQUESTS, READ_ONLY_TOOLS, SPAWN_AGENT, SubAgent, group_agent_calls};
+use context::TokenCalibration;
+use gaise_core::contracts::{GaiseContent, GaiseFunctionCall, GaiseMessage, GaiseToolCall, OneOrMany};
+use tools::SpawnAgentArgs;
+
+fn args(task: &str, model: Option<&str>, tools: Option<&[&str]>) -> SpawnAgentArgs {
+    SpawnAgentArgs {
+        task: task.to_string(),
+        model: model.map(str::to_string),
+        tools: tools.map(|tools| tools.iter().map(|tool| tool.to_string()).collect()),
+    }
+}
+
+fn assistant(text: Option<&str>, tool_call: Option<&str>) -> GaiseMessage {
+    GaiseMessage {
+        role: "assistant".to_string(),
+        content: text.map(|text| OneOrMany::One(GaiseContent::Text { text: text.to_string() })),
+        tool_calls: tool_call.map(|id| {
+            vec![GaiseToolCall {
+                id: id.to_string(),
+                r#type: "function".to_string(),
+                function: GaiseFunctionCall { name: "grep".to_string(), arguments: Some("{\"pattern\":\"parse_date\"}".to_string()) },
+            }]
+        }),
+        tool_call_id: None,
+    }
+}
+
+#[test]
+fn test_sub_agent_starts_with_its_own_history() {
+    let agent = SubAgent::new(1, args("Find every place we parse dates", None, None), "You are iKode.", "openai::gpt-4o").unwrap();
+
+    assert_eq!(agent.model, "openai::gpt-4o");
+    assert_eq!(agent.tools, READ_ONLY_TOOLS);
+    assert_eq!(agent.history.len(), 2);
+    let system = compaction::content_text(agent.history[0].content.as_ref().unwrap());
+    assert!(system.starts_with("You are iKode."));
+    assert!(system.contains("read_file, grep, glob, list_directory"));
+    assert_eq!(compaction::content_text(agent.history[1].content.as_ref().unwrap()), "Find every place we parse dates");
+
+    let names: Vec<String> = agent.tool_definitions().into_iter().map(|tool| tool.name).collect();
+    assert_eq!(names, READ_ONLY_TOOLS);
+}
+
+#[test]
+fn test_sub_agent_model_and_tools() {
+    let agent = SubAgent::new(2, args("Count the tests", Some("ollama::llama3"), Some(&["grep", "glob", "grep"])), "", "openai::gpt-4o").unwrap();
+    assert_eq!(agent.model, "ollama::llama3");
+    assert_eq!(agent.tools, vec!["grep", "glob"]);
+    assert!(agent.allows("grep"));
+    assert!(!agent.allows("read_file"));
+
+    let error = SubAgent::new(3, args("Fix the bug", None, Some(&["read_file", "edit_file"])), "", "m").unwrap_err();
+    assert!(error.to_string().contains("sub-agents cannot use 'edit_file'"));
+    assert!(SubAgent::new(3, args("  ", None, None), "", "m").is_err());
+}
+
+#[test]
+fn test_requests_are_limited() {
+    let mut agent = SubAgent::new(1, args("Explore", None, Some(&[])), "", "openai::gpt-4o").unwrap();
+
+    let calibration = TokenCalibration::default();
+    let request = agent.next_request(None, 0, &calibration).unwrap();
+    assert_eq!(request.model, "openai::gpt-4o");
+    assert!(request.tools.is_none());
+    for _ in 1..MAX_REQUESTS {
+        assert!(agent.next_request(None, 0, &calibration).is_some());
+    }
+    assert!(agent.next_request(None, 0, &calibration).is_none());
+}
+
+#[test]
+fn test_final_answer_is_the_text_after_the_last_tool_result() {
+    let mut agent = SubAgent::new(1, args("Find date parsing", None, None), "", "m").unwrap();
+    assert_eq!(agent.final_answer(), "(The sub-agent finished without an answer.)");
+
+    agent.history.push(assistant(Some("Let me search."), Some("call_1")));
+    agent.push_tool_result("call_1", "src/date.rs:12: fn parse_date".to_string());
+    agent.history.push(assistant(Some("Dates are parsed in src/date.rs:12."), None));
+
+    assert_eq!(agent.final_answer(), "Dates are parsed in src/date.rs:12.");
+    assert_eq!(agent.history[3].tool_call_id.as_deref(), Some("call_1"));
+}
+
+/// A sub-agent that has made `rounds` tool calls, each with a result of about 1000 tokens.
+fn agent_with_rounds(rounds: usize) -> SubAgent {
+    let mut agent = SubAgent::new(1, args("Explore", None, None), "", "m").unwrap();
+    for round in 0..rounds {
+        let id = format!("call_{}", round);
+        agent.history.push(assistant(None, Some(&id)));
+        agent.push_tool_result(&id, "x".repeat(4000));
+    }
+    agent
+}
+
+fn text(message: &GaiseMessage) -> String {
+    message.content.as_ref().map(compaction::content_text).unwrap_or_default()
+}
+
+#[test]
+fn test_request_history_fits_the_context_budget() {
+    let agent = agent_with_rounds(10);
+    let calibration = TokenCalibration::default();
+
+    assert_eq!(agent.request_history(0, &calibration).len(), 22);
+
+    let history = agent.request_history(3000, &calibration);
+    assert!(compaction::estimate_tokens(&history) <= context::prompt_budget(3000));
+    assert_eq!(text(&history[1]), "Explore");
+    assert!(text(&history[2]).contains("earlier messages were truncated"));
+    // Whole rounds are dropped, so no tool result loses its call.
+    assert_eq!(history[3].role, "assistant");
+    assert_eq!(history.last().unwrap().tool_call_id.as_deref(), Some("call_9"));
+
+    // The latest round is kept even if it does not fit.
+    let history = agent.request_history(100, &calibration);
+    assert_eq!(history.len(), 5);
+    assert_eq!(history[4].tool_call_id.as_deref(), Some("call_9"));
+}
+
+#[test]
+fn test_compaction_replaces_older_messages() {
+    let mut agent = agent_with_rounds(10);
+    let calibration = TokenCalibration::default();
+
+    let (request, covered_until) = agent.compaction_request("summarizer", 4).unwrap();
+    assert_eq!(request.model, "summarizer");
+    assert_eq!(covered_until, 18);
+    agent.compact(covered_until, "Searched for parse_date.".to_string());
+
+    let history = agent.request_history(0, &calibration);
+    assert_eq!(history.len(), 7);
+    assert!(text(&history[2]).contains("Summary of 16 earlier messages"));
+    assert!(text(&history[2]).contains("Searched for parse_date."));
+    assert_eq!(history[3].role, "assistant");
+    assert!(agent.compaction_request("summarizer", 4).is_none());
+
+    // A later compaction folds in the earlier summary.
+    agent.history.push(assistant(None, Some("call_10")));
+    agent.push_tool_result("call_10", "more".to_string());
+    let (request, covered_until) = agent.compaction_request("summarizer", 2).unwrap();
+    let OneOrMany::Many(input) = &request.input else { panic!("expected several messages") };
+    assert!(text(&input[1]).contains("Searched for parse_date."));
+    agent.compact(covered_until, "Searched twice.".to_string());
+    assert_eq!(agent.compaction.as_ref().unwrap().compacted_messages, 20);
+}
+
+#[test]
+fn test_agent_calls_are_grouped_in_place() {
+    let call = |name: &str| GaiseToolCall {
+        id: name.to_string(),
+        r#type: "function".to_string(),
+        function: GaiseFunctionCall { name: name.to_string(), arguments: None },
+    };
+    let calls = vec![call("edit_file"), call(SPAWN_AGENT), call(SPAWN_AGENT), call("grep"), call("read_file"), call(SPAWN_AGENT)];
+
+    let groups: Vec<usize> = group_agent_calls(&calls).iter().map(|group| group.len()).collect();
+
+    assert_eq!(groups, vec![1, 2, 2, 1]);
+    assert_eq!(group_agent_calls(&calls)[1][0].function.name, SPAWN_AGENT);
+    assert!(group_agent_calls(&[]).is_empty());
+}
@@ -0,0 +1,201 @@
+#[allow(dead_code)]
+mod common;
+
+#[allow(dead_code)]
+#[path = "../src/checkpoints.rs"]
+mod checkpoints;
+
+use checkpoints::Checkpoints;
+use common::TestFixture;
+use std::fs;
+
+#[test]
+fn test_undo_restores_changed_and_deletes_created_files() {
+    let fixture = TestFixture::new();
+    let existing = fixture.create_file("src/main.rs", "fn main() {}\n");
+    let created = fixture.path().join("src/new.rs");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("refactor main", 3);
+    checkpoints.snapshot(&existing).unwrap();
+    fs::write(&existing, "fn main() { run(); }\n").unwrap();
+    checkpoints.snapshot(&existing).unwrap();
+    fs::write(&existing, "fn main() { run(); run(); }\n").unwrap();
+    checkpoints.snapshot(&created).unwrap();
+    fs::write(&created, "fn run() {}\n").unwrap();
+
+    assert_eq!(checkpoints.list().len(), 1);
+    assert_eq!(checkpoints.list()[0].prompt, "refactor main");
+    assert_eq!(checkpoints.list()[0].files.len(), 2);
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert_eq!(restored.files, vec![existing.clone(), created.clone()]);
+    assert_eq!(restored.history_len, Some(3));
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "fn main() {}\n");
+    assert!(!created.exists());
+    assert!(checkpoints.list().is_empty());
+    assert_eq!(checkpoints.undo().unwrap(), None);
+}
+
+#[test]
+fn test_turns_without_changes_get_no_checkpoint() {
+    let fixture = TestFixture::new();
+    let file = fixture.create_file("a.txt", "one\n");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("just look around", 1);
+    checkpoints.begin_turn("change a", 5);
+    checkpoints.snapshot(&file).unwrap();
+
+    assert_eq!(checkpoints.list().len(), 1);
+    assert_eq!(checkpoints.list()[0].prompt, "change a");
+    assert_eq!(checkpoints.list()[0].history_len, Some(5));
+}
+
+#[test]
+fn test_restore_rolls_back_later_turns_too() {
+    let fixture = TestFixture::new();
+    let file = fixture.create_file("a.txt", "one\n");
+    let other = fixture.create_file("b.txt", "b\n");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("first", 1);
+    checkpoints.snapshot(&file).unwrap();
+    fs::write(&file, "two\n").unwrap();
+    checkpoints.begin_turn("second", 5);
+    checkpoints.snapshot(&file).unwrap();
+    fs::write(&file, "three\n").unwrap();
+    checkpoints.snapshot(&other).unwrap();
+    fs::write(&other, "bb\n").unwrap();
+    checkpoints.begin_turn("third", 9);
+    checkpoints.snapshot(&file).unwrap();
+    fs::write(&file, "four\n").unwrap();
+
+    let restored = checkpoints.restore(2).unwrap();
+
+    assert_eq!(restored.files, vec![file.clone(), other.clone()]);
+    assert_eq!(restored.history_len, Some(5));
+    assert_eq!(fs::read_to_string(&file).unwrap(), "two\n");
+    assert_eq!(fs::read_to_string(&other).unwrap(), "b\n");
+    assert_eq!(checkpoints.list().len(), 1);
+
+    assert!(checkpoints.restore(2).unwrap_err().to_string().contains("No checkpoint 2"));
+    assert!(checkpoints.restore(0).is_err());
+    checkpoints.restore(1).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one\n");
+}
+
+#[test]
+fn test_forget_history_keeps_files() {
+    let fixture = TestFixture::new();
+    let file = fixture.create_file("a.txt", "one\n");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("change a", 3);
+    checkpoints.snapshot(&file).unwrap();
+    fs::write(&file, "two\n").unwrap();
+    checkpoints.forget_history();
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert_eq!(restored.history_len, None);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "one\n");
+}
+
+#[test]
+fn test_deleted_and_moved_files_come_back() {
+    let fixture = TestFixture::new();
+    let deleted = fixture.create_file("old.txt", "old\n");
+    fixture.create_nested_structure();
+    let source = fixture.path().join("src/components");
+    let destination = fixture.path().join("lib/components");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("tidy up", 1);
+    checkpoints.snapshot(&deleted).unwrap();
+    fs::remove_file(&deleted).unwrap();
+    let files = checkpoints::files_under(&source).unwrap();
+    checkpoints.snapshot_move(&source, &destination).unwrap();
+    fs::create_dir_all(destination.parent().unwrap()).unwrap();
+    fs::rename(&source, &destination).unwrap();
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert_eq!(fs::read_to_string(&deleted).unwrap(), "old\n");
+    assert_eq!(files.len(), 2);
+    assert_eq!(checkpoints::files_under(&source).unwrap(), files);
+    // The destination and the parent the move created are gone again.
+    assert!(!fixture.path().join("lib").exists());
+    assert!(restored.directories.contains(&fixture.path().join("lib")));
+}
+
+#[test]
+fn test_undo_of_a_move_brings_back_empty_directories() {
+    let fixture = TestFixture::new();
+    let source = fixture.create_dir("assets/empty");
+    let destination = fixture.path().join("static");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("rename assets", 1);
+    checkpoints.snapshot_move(&fixture.path().join("assets"), &destination).unwrap();
+    fs::rename(fixture.path().join("assets"), &destination).unwrap();
+    assert!(destination.join("empty").is_dir());
+
+    checkpoints.undo().unwrap();
+
+    assert!(source.is_dir());
+    assert!(!destination.exists());
+}
+
+#[test]
+fn test_undo_removes_created_directories_and_their_parents() {
+    let fixture = TestFixture::new();
+    fixture.create_dir("src");
+    let created = fixture.path().join("src/a/b");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("make dirs", 1);
+    checkpoints.snapshot_directory(&created);
+    fs::create_dir_all(&created).unwrap();
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert_eq!(restored.directories, vec![fixture.path().join("src/a"), created]);
+    assert!(!fixture.path().join("src/a").exists());
+    assert!(fixture.path().join("src").is_dir());
+}
+
+#[test]
+fn test_undo_keeps_created_directories_holding_other_files() {
+    let fixture = TestFixture::new();
+    let created = fixture.path().join("build");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("make build dir", 1);
+    checkpoints.snapshot_directory(&created);
+    fs::create_dir(&created).unwrap();
+    // Written by a shell command, so not in the checkpoint.
+    fs::write(created.join("out.o"), "").unwrap();
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert!(restored.directories.is_empty());
+    assert!(created.join("out.o").exists());
+}
+
+#[test]
+fn test_undo_recreates_deleted_directories() {
+    let fixture = TestFixture::new();
+    let deleted = fixture.create_dir("logs");
+    let mut checkpoints = Checkpoints::new();
+
+    checkpoints.begin_turn("remove logs", 1);
+    checkpoints.snapshot_directory(&deleted);
+    fs::remove_dir(&deleted).unwrap();
+
+    let restored = checkpoints.undo().unwrap().unwrap();
+
+    assert_eq!(restored.directories, vec![deleted.clone()]);
+    assert!(deleted.is_dir());
+}
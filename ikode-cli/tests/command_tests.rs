@@ -0,0 +1,119 @@
+#[allow(dead_code)]
+#[path = "../src/commands.rs"]
+mod commands;
+
+#[allow(dead_code)]
+#[path = "../src/permissions.rs"]
+mod permissions;
+
+use commands::{BUILTIN_COMMANDS, Builtin, SlashCommand, SlashCommands, load_custom_commands};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn commands_dir(dir: &TempDir, name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let path = dir.path().join(name);
+    fs::create_dir_all(&path).unwrap();
+    for (file, content) in files {
+        fs::write(path.join(file), content).unwrap();
+    }
+    path
+}
+
+const REVIEW: &str = "---
+description: Review a file for bugs
+argument-hint: {file}
+model: \"anthropic::claude-sonnet-4-5\"
+allowed-tools: [read_file, grep, \"mcp__github__*\"]
+---
+Review $ARGUMENTS for bugs.
+
+Explain each bug in $ARGUMENTS.
+";
+
+#[test]
+fn test_builtin_commands_and_arguments() {
+    let commands = SlashCommands::default();
+
+    assert_eq!(commands.parse("/help"), Some((SlashCommand::Builtin(Builtin::Help), "")));
+    assert_eq!(commands.parse("/model  ollama::llama3 "), Some((SlashCommand::Builtin(Builtin::Model), "ollama::llama3")));
+    assert_eq!(commands.parse("/jobs kill 3"), Some((SlashCommand::Builtin(Builtin::Jobs), "kill 3")));
+    assert_eq!(commands.parse("/clear_screen"), Some((SlashCommand::Builtin(Builtin::ClearScreen), "")));
+    assert_eq!(commands.parse("/etc/hosts looks wrong"), None);
+    assert_eq!(commands.parse("what does /help do?"), None);
+
+    let mut names: Vec<&str> = BUILTIN_COMMANDS.iter().map(|command| command.name).collect();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), BUILTIN_COMMANDS.len());
+}
+
+#[test]
+fn test_loads_commands_with_frontmatter() {
+    let dir = TempDir::new().unwrap();
+    let project = commands_dir(&dir, "project", &[("review.md", REVIEW), ("changelog.md", "Write a changelog entry.\n"), ("notes.txt", "not a command")]);
+
+    let (custom, errors) = load_custom_commands(&[project]);
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(custom.iter().map(|command| command.name.as_str()).collect::<Vec<_>>(), vec!["changelog", "review"]);
+    let review = &custom[1];
+    assert_eq!(review.description.as_deref(), Some("Review a file for bugs"));
+    assert_eq!(review.argument_hint.as_deref(), Some("{file}"));
+    assert_eq!(review.model.as_deref(), Some("anthropic::claude-sonnet-4-5"));
+    assert_eq!(review.allowed_tools, Some(vec!["read_file".to_string(), "grep".to_string(), "mcp__github__*".to_string()]));
+    assert_eq!(review.render("src/lib.rs"), "Review src/lib.rs for bugs.\n\nExplain each bug in src/lib.rs.");
+    assert!(review.allows_tool("mcp__github__list_issues"));
+    assert!(!review.allows_tool("execute_command"));
+
+    let changelog = &custom[0];
+    assert_eq!(changelog.summary(), "Write a changelog entry.");
+    assert_eq!(changelog.render(""), "Write a changelog entry.");
+    assert_eq!(changelog.render("for 1.2"), "Write a changelog entry.\n\nfor 1.2");
+    assert!(changelog.allows_tool("execute_command"));
+
+    let commands = SlashCommands::new(custom.clone());
+    assert_eq!(commands.parse("/review src/lib.rs"), Some((SlashCommand::Custom(review), "src/lib.rs")));
+}
+
+#[test]
+fn test_project_commands_replace_global_ones() {
+    let dir = TempDir::new().unwrap();
+    let global = commands_dir(&dir, "global", &[("review.md", "Global review."), ("explain.md", "Explain $ARGUMENTS.")]);
+    let project = commands_dir(&dir, "project", &[("review.md", "Project review.")]);
+
+    let (custom, errors) = load_custom_commands(&[global, project, dir.path().join("missing")]);
+
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(custom.len(), 2);
+    assert_eq!(custom[0].template, "Explain $ARGUMENTS.");
+    assert_eq!(custom[1].template, "Project review.");
+    assert!(custom[1].source.starts_with(dir.path().join("project")));
+}
+
+#[test]
+fn test_invalid_commands_are_skipped_with_errors() {
+    let dir = TempDir::new().unwrap();
+    let project = commands_dir(
+        &dir,
+        "project",
+        &[
+            ("a.md", "---\nmodel: x\n"),
+            ("b.md", "---\ntools: read_file\n---\nRead it."),
+            ("c.md", "---\ndescription: nothing\n---\n"),
+            ("help.md", "Override help."),
+            ("my command.md", "Spaces."),
+            ("ok.md", "Fine."),
+        ],
+    );
+
+    let (custom, errors) = load_custom_commands(&[project]);
+
+    assert_eq!(custom.len(), 1);
+    assert_eq!(errors.len(), 5);
+    assert!(errors[0].contains("a.md: the frontmatter is not closed"), "{}", errors[0]);
+    assert!(errors[1].contains("unknown frontmatter key `tools`"), "{}", errors[1]);
+    assert!(errors[2].contains("the command has no prompt"), "{}", errors[2]);
+    assert!(errors[3].contains("/help is a built-in command"), "{}", errors[3]);
+    assert!(errors[4].contains("invalid command name 'my command'"), "{}", errors[4]);
+}
@@ -0,0 +1,146 @@
+#[allow(dead_code)]
+#[path = "../src/compaction.rs"]
+mod compaction;
+
+use compaction::{
+    CompactionSummary, build_compaction_request, estimate_tokens, render_transcript, skip_tool_results,
+    tool_safe_start,
+};
+use gaise_core::contracts::{GaiseContent, GaiseFunctionCall, GaiseMessage, GaiseToolCall, OneOrMany};
+
+fn text_message(role: &str, text: &str) -> GaiseMessage {
+    GaiseMessage {
+        role: role.to_string(),
+        content: Some(OneOrMany::One(GaiseContent::Text { text: text.to_string() })),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+fn tool_call_message(calls: &[(&str, &str, &str)]) -> GaiseMessage {
+    GaiseMessage {
+        role: "assistant".to_string(),
+        content: None,
+        tool_calls: Some(
+            calls
+                .iter()
+                .map(|(id, name, args)| GaiseToolCall {
+                    id: id.to_string(),
+                    r#type: "function".to_string(),
+                    function: GaiseFunctionCall { name: name.to_string(), arguments: Some(args.to_string()) },
+                })
+                .collect(),
+        ),
+        tool_call_id: None,
+    }
+}
+
+fn tool_result(id: &str, text: &str) -> GaiseMessage {
+    GaiseMessage {
+        role: "tool".to_string(),
+        content: Some(OneOrMany::One(GaiseContent::Text { text: text.to_string() })),
+        tool_calls: None,
+        tool_call_id: Some(id.to_string()),
+    }
+}
+
+fn sample_history() -> Vec<GaiseMessage> {
+    vec![
+        text_message("system", "You are iKode."),
+        text_message("user", "Fix the parser"),
+        tool_call_message(&[("1", "read_file", r#"{"path":"src/parser.rs"}"#), ("2", "read_file", r#"{"path":"src/lib.rs"}"#)]),
+        tool_result("1", "fn parse() {}"),
+        tool_result("2", "mod parser;"),
+        text_message("assistant", "The parser is empty."),
+    ]
+}
+
+#[test]
+fn test_tool_safe_start_moves_back_to_tool_call() {
+    let history = sample_history();
+
+    assert_eq!(tool_safe_start(&history, 4), 2);
+    assert_eq!(tool_safe_start(&history, 3), 2);
+    assert_eq!(tool_safe_start(&history, 5), 5);
+    assert_eq!(tool_safe_start(&history, 1), 1);
+}
+
+#[test]
+fn test_tool_safe_start_handles_out_of_range_index() {
+    let history = sample_history();
+    assert_eq!(tool_safe_start(&history, 10), 10);
+}
+
+#[test]
+fn test_skip_tool_results_moves_forward() {
+    let history = sample_history();
+
+    assert_eq!(skip_tool_results(&history, 3), 5);
+    assert_eq!(skip_tool_results(&history, 2), 2);
+    assert_eq!(skip_tool_results(&history[..5], 3), 5);
+}
+
+#[test]
+fn test_estimate_tokens_grows_with_content() {
+    let short = vec![text_message("user", "hi")];
+    let long = vec![text_message("user", &"word ".repeat(400))];
+
+    assert!(estimate_tokens(&short) < estimate_tokens(&long));
+    assert!(estimate_tokens(&long) >= 500);
+    assert!(estimate_tokens(&[tool_call_message(&[("1", "read_file", r#"{"path":"a"}"#)])]) > 4);
+}
+
+#[test]
+fn test_render_transcript_includes_calls_and_results() {
+    let transcript = render_transcript(&sample_history()[1..]);
+
+    assert!(transcript.contains("[user]\nFix the parser"));
+    assert!(transcript.contains(r#"[tool call] read_file({"path":"src/parser.rs"})"#));
+    assert!(transcript.contains("[tool result]\nfn parse() {}"));
+    assert!(transcript.contains("[assistant]\nThe parser is empty."));
+}
+
+#[test]
+fn test_render_transcript_shortens_long_tool_results() {
+    let transcript = render_transcript(&[tool_result("1", &"x".repeat(10_000))]);
+
+    assert!(transcript.len() < 3000);
+    assert!(transcript.contains("(tool result shortened)"));
+}
+
+#[test]
+fn test_build_compaction_request_includes_previous_summary_and_todos() {
+    let request = build_compaction_request(
+        "openai::gpt-4o-mini",
+        Some("## Goal\nEarlier work"),
+        &sample_history()[1..],
+        "- [ ] Add tests",
+    );
+
+    assert_eq!(request.model, "openai::gpt-4o-mini");
+    assert!(request.tools.is_none());
+
+    let OneOrMany::Many(messages) = request.input else { panic!("expected a list of messages") };
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].role, "system");
+    let Some(OneOrMany::One(GaiseContent::Text { text: prompt })) = &messages[0].content else { panic!() };
+    assert!(prompt.contains("## Files touched"));
+    assert!(prompt.contains("## Decisions made"));
+    assert!(prompt.contains("## Open todos"));
+
+    let Some(OneOrMany::One(GaiseContent::Text { text })) = &messages[1].content else { panic!() };
+    assert!(text.contains("Earlier work"));
+    assert!(text.contains("Fix the parser"));
+    assert!(text.contains("- [ ] Add tests"));
+}
+
+#[test]
+fn test_summary_message_is_system_message() {
+    let summary = CompactionSummary { covered_until: 6, compacted_messages: 4, summary: "## Goal\nFix it".to_string() };
+    let message = summary.to_message();
+
+    assert_eq!(message.role, "system");
+    let Some(OneOrMany::One(GaiseContent::Text { text })) = message.content else { panic!() };
+    assert!(text.contains("4 earlier messages"));
+    assert!(text.ends_with("## Goal\nFix it"));
+}
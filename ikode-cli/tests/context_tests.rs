@@ -0,0 +1,68 @@
+#[allow(dead_code)]
+#[path = "../src/context.rs"]
+mod context;
+
+use context::{TokenCalibration, context_window, fit_to_budget, prompt_budget};
+
+#[test]
+fn test_context_window_prefers_most_specific_match() {
+    assert_eq!(context_window("openai::gpt-4o"), Some(128_000));
+    assert_eq!(context_window("openai::gpt-4o-mini"), Some(128_000));
+    assert_eq!(context_window("openai::gpt-4"), Some(8_192));
+    assert_eq!(context_window("openai::gpt-4.1-mini"), Some(1_047_576));
+    assert_eq!(context_window("ollama::llama3.1:8b"), Some(131_072));
+    assert_eq!(context_window("ollama::llama3"), Some(8_192));
+}
+
+#[test]
+fn test_context_window_handles_provider_specific_ids() {
+    assert_eq!(context_window("anthropic::claude-3-5-sonnet-20241022"), Some(200_000));
+    assert_eq!(context_window("bedrock::anthropic.claude-3-haiku-20240307-v1:0"), Some(200_000));
+    assert_eq!(context_window("vertexai::gemini-1.5-pro-002"), Some(2_097_152));
+    assert_eq!(context_window("vertexai::gemini-2.0-flash"), Some(1_048_576));
+    assert_eq!(context_window("gpt-4o"), Some(128_000));
+}
+
+#[test]
+fn test_context_window_unknown_model() {
+    assert_eq!(context_window("ollama::my-custom-model"), None);
+}
+
+#[test]
+fn test_calibration_scales_estimates() {
+    let mut calibration = TokenCalibration::default();
+    assert_eq!(calibration.apply(100), 100);
+
+    calibration.observe(1000, 1500);
+    assert_eq!(calibration.apply(100), 150);
+
+    // Ignores empty observations and clamps outliers.
+    calibration.observe(0, 500);
+    assert_eq!(calibration.apply(100), 150);
+    calibration.observe(10, 1000);
+    assert_eq!(calibration.apply(100), 400);
+}
+
+#[test]
+fn test_prompt_budget_leaves_room_for_response() {
+    assert_eq!(prompt_budget(1000), 900);
+    assert_eq!(prompt_budget(0), 0);
+}
+
+#[test]
+fn test_fit_to_budget_drops_oldest_messages() {
+    let tokens = [500, 300, 100, 50];
+
+    assert_eq!(fit_to_budget(&tokens, 100, 2000, 3), 0);
+    assert_eq!(fit_to_budget(&tokens, 100, 600, 3), 1);
+    assert_eq!(fit_to_budget(&tokens, 100, 300, 3), 2);
+}
+
+#[test]
+fn test_fit_to_budget_respects_limit() {
+    let tokens = [500, 300, 100, 50];
+
+    assert_eq!(fit_to_budget(&tokens, 0, 10, 3), 3);
+    assert_eq!(fit_to_budget(&tokens, 0, 10, 1), 1);
+    assert_eq!(fit_to_budget(&tokens, 0, 10, 10), 4);
+}
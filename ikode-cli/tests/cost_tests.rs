@@ -0,0 +1,100 @@
+#[allow(dead_code)]
+#[path = "../src/cost.rs"]
+mod cost;
+
+use cost::{PriceTable, TokenUsage, UsageTotals, format_cost, format_tokens};
+use gaise_core::contracts::GaiseUsage;
+use tempfile::TempDir;
+
+#[test]
+fn test_token_usage_from_canonical_fields() {
+    let usage = GaiseUsage {
+        cache_read_tokens: Some(800),
+        cache_write_tokens: Some(50),
+        reasoning_tokens: Some(60),
+        ..GaiseUsage::new(950, 300)
+    };
+    let tokens = TokenUsage::from_usage(&usage);
+    assert_eq!(tokens, TokenUsage { input: 950, output: 300, cache_read: 800, cache_write: 50, reasoning: 60 });
+}
+
+#[test]
+fn test_token_usage_ignores_raw_provider_keys() {
+    let usage = GaiseUsage::new(1000, 200).with_raw_input("prompt_tokens", 1000).with_raw_output("completion_tokens", 200);
+    assert_eq!(TokenUsage::from_usage(&usage), TokenUsage { input: 1000, output: 200, ..Default::default() });
+    assert_eq!(TokenUsage::from_usage(&GaiseUsage::default()), TokenUsage::default());
+}
+
+#[test]
+fn test_price_lookup_prefers_longest_key() {
+    let prices = PriceTable::default();
+
+    let mini = prices.price("openai::gpt-4o-mini").unwrap();
+    let full = prices.price("openai::gpt-4o-2024-08-06").unwrap();
+    assert!(mini.input < full.input);
+    assert_eq!(prices.price("ollama::llama3").unwrap().output, 0.0);
+    assert!(prices.price("unknown::model").is_none());
+}
+
+#[test]
+fn test_price_cost_applies_cached_rate() {
+    let price = PriceTable::default().price("openai::gpt-4o").unwrap();
+    let tokens = TokenUsage { input: 1_000_000, output: 1_000_000, cache_read: 500_000, ..Default::default() };
+
+    let expected = 0.5 * price.input + 0.5 * price.cached_input.unwrap() + price.output;
+    assert!((price.cost(&tokens) - expected).abs() < 1e-9);
+}
+
+#[test]
+fn test_price_overrides_from_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("prices.toml");
+    std::fs::write(&path, "[\"openai::gpt-4o\"]\ninput = 1.0\noutput = 2.0\n\n[\"ollama::llama3\"]\ninput = 0.5\noutput = 0.5\n").unwrap();
+
+    let prices = PriceTable::with_overrides(&path).unwrap();
+    let price = prices.price("openai::gpt-4o").unwrap();
+    assert_eq!(price.input, 1.0);
+    assert_eq!(price.cached_input, None);
+    assert_eq!(prices.price("ollama::llama3:8b").unwrap().input, 0.5);
+    assert!(prices.price("openai::gpt-4o-mini").is_some());
+}
+
+#[test]
+fn test_price_overrides_missing_file_uses_defaults() {
+    let temp_dir = TempDir::new().unwrap();
+    let prices = PriceTable::with_overrides(&temp_dir.path().join("missing.toml")).unwrap();
+    assert!(prices.price("openai::gpt-4o").is_some());
+}
+
+#[test]
+fn test_price_overrides_invalid_file_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("prices.toml");
+    std::fs::write(&path, "[\"openai::gpt-4o\"]\ninput = \"cheap\"\n").unwrap();
+
+    let err = PriceTable::with_overrides(&path).unwrap_err().to_string();
+    assert!(err.contains("Could not parse price table"));
+}
+
+#[test]
+fn test_usage_totals_track_unpriced_requests() {
+    let mut totals = UsageTotals::default();
+    let tokens = TokenUsage { input: 10, output: 5, ..Default::default() };
+
+    totals.record(&tokens, Some(0.25));
+    totals.record(&tokens, None);
+
+    assert_eq!(totals.requests, 2);
+    assert_eq!(totals.tokens.input, 20);
+    assert_eq!(totals.unpriced_requests, 1);
+    assert!((totals.cost - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn test_formatting() {
+    assert_eq!(format_tokens(950), "950");
+    assert_eq!(format_tokens(12_345), "12.3k");
+    assert_eq!(format_tokens(1_200_000), "1.2M");
+    assert_eq!(format_cost(0.001234), "$0.0012");
+    assert_eq!(format_cost(1.5), "$1.50");
+}
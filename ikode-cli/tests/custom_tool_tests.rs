@@ -0,0 +1,138 @@
+#![cfg(unix)]
+
+#[allow(dead_code)]
+#[path = "../src/custom_tools.rs"]
+mod custom_tools;
+
+#[allow(dead_code)]
+#[path = "../src/exec.rs"]
+mod exec;
+
+#[allow(dead_code)]
+#[path = "../src/mcp.rs"]
+mod mcp;
+
+#[allow(dead_code)]
+#[path = "../src/tools.rs"]
+mod tools;
+
+use custom_tools::{ArgumentInput, argument_env, load_custom_tools, tools_dir};
+use exec::{CommandExit, ExecOptions};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn write_tool(dir: &TempDir, file: &str, content: &str) {
+    let tools = tools_dir(dir.path());
+    fs::create_dir_all(&tools).unwrap();
+    fs::write(tools.join(file), content).unwrap();
+}
+
+fn load(dir: &Path) -> Vec<custom_tools::CustomTool> {
+    let (tools, errors) = load_custom_tools(&tools_dir(dir));
+    assert!(errors.is_empty(), "{:?}", errors);
+    tools
+}
+
+const GREET: &str = r#"
+name = "greet"
+description = "Greets someone."
+command = 'printf "hello %s x%s" "$IKODE_ARG_NAME" "$IKODE_ARG_TIMES"'
+
+[parameters]
+type = "object"
+required = ["name"]
+properties.name = { type = "string", description = "Who to greet" }
+properties.times = { type = "integer" }
+"#;
+
+#[test]
+fn test_loads_tools_and_maps_parameters() {
+    let dir = TempDir::new().unwrap();
+    write_tool(&dir, "greet.toml", GREET);
+    write_tool(&dir, "status.toml", "name = \"status\"\ndescription = \"Shows status.\"\ncommand = \"git status\"\ninput = \"stdin\"\n");
+    write_tool(&dir, "notes.txt", "not a tool");
+
+    let tools = load(dir.path());
+
+    assert_eq!(tools.len(), 2);
+    assert_eq!(tools[0].input, ArgumentInput::Env);
+    assert_eq!(tools[1].input, ArgumentInput::Stdin);
+    // The file content is kept so the user's trust can be tied to it.
+    assert_eq!(tools[0].content, GREET);
+    assert_eq!(tools[0].source, tools_dir(dir.path()).join("greet.toml"));
+    let definition = tools[0].definition();
+    assert_eq!(definition.name, "greet");
+    let parameters = definition.parameters.unwrap();
+    assert_eq!(parameters.required, Some(vec!["name".to_string()]));
+    assert_eq!(parameters.properties.unwrap()["name"].description.as_deref(), Some("Who to greet"));
+    assert!(tools[1].definition().parameters.unwrap().properties.unwrap().is_empty());
+}
+
+#[test]
+fn test_invalid_tools_are_skipped_with_errors() {
+    let dir = TempDir::new().unwrap();
+    write_tool(&dir, "a.toml", GREET);
+    write_tool(&dir, "b.toml", &GREET.replace("Greets someone.", "Greets again."));
+    write_tool(&dir, "c.toml", "name = \"read_file\"\ndescription = \"x\"\ncommand = \"cat\"\n");
+    write_tool(&dir, "d.toml", "name = \"bad name\"\ndescription = \"x\"\ncommand = \"true\"\n");
+    write_tool(&dir, "e.toml", "name = \"mcp__x__y\"\ndescription = \"x\"\ncommand = \"true\"\n");
+    write_tool(&dir, "f.toml", "name = \"arr\"\ndescription = \"x\"\ncommand = \"true\"\nparameters = { type = \"array\" }\n");
+    write_tool(&dir, "g.toml", "name = \"typo\"\ndescription = \"x\"\ncmd = \"true\"\n");
+
+    let (tools, errors) = load_custom_tools(&tools_dir(dir.path()));
+
+    assert_eq!(tools.len(), 1);
+    assert_eq!(errors.len(), 6);
+    assert!(errors[0].contains("b.toml: a tool named 'greet' already exists"));
+    assert!(errors[1].contains("c.toml: a tool named 'read_file' already exists"));
+    assert!(errors[2].contains("invalid tool name 'bad name'"));
+    assert!(errors[3].contains("reserved for MCP servers"));
+    assert!(errors[4].contains("type = \"object\""));
+    assert!(errors[5].contains("g.toml"));
+    assert!(load_custom_tools(&dir.path().join("missing")).1.is_empty());
+}
+
+#[test]
+fn test_argument_env() {
+    let env = argument_env(&json!({ "name": "Ada", "dry-run": true, "tags": ["a", "b"], "skip": null }));
+
+    assert!(env.contains(&("IKODE_ARG_NAME".to_string(), "Ada".to_string())));
+    assert!(env.contains(&("IKODE_ARG_DRY_RUN".to_string(), "true".to_string())));
+    assert!(env.contains(&("IKODE_ARG_TAGS".to_string(), "[\"a\",\"b\"]".to_string())));
+    assert!(!env.iter().any(|(name, _)| name == "IKODE_ARG_SKIP"));
+    assert_eq!(env.last().unwrap().0, "IKODE_ARGS");
+}
+
+#[tokio::test]
+async fn test_arguments_are_never_interpolated() {
+    let dir = TempDir::new().unwrap();
+    write_tool(&dir, "greet.toml", GREET);
+    let tool = load(dir.path()).remove(0);
+
+    let output = tool
+        .run(&json!({ "name": "$(touch pwned); `touch pwned`", "times": 2 }), &ExecOptions::new(dir.path().to_path_buf()))
+        .await
+        .unwrap();
+
+    assert_eq!(output.exit, CommandExit::Code(0));
+    assert_eq!(output.stdout, "hello $(touch pwned); `touch pwned` x2");
+    assert!(!dir.path().join("pwned").exists());
+}
+
+#[tokio::test]
+async fn test_stdin_input_and_limits() {
+    let dir = TempDir::new().unwrap();
+    write_tool(&dir, "echo.toml", "name = \"echo_args\"\ndescription = \"x\"\ncommand = \"cat; seq 1 5000\"\ninput = \"stdin\"\n");
+    write_tool(&dir, "slow.toml", "name = \"slow\"\ndescription = \"x\"\ncommand = \"sleep 30\"\ntimeout = 1\n");
+    let tools = load(dir.path());
+    let options = ExecOptions { output_limit: 200, ..ExecOptions::new(dir.path().to_path_buf()) };
+
+    let output = tools[0].run(&json!({ "query": "select 1" }), &options).await.unwrap();
+    assert!(output.stdout.starts_with("{\"query\":\"select 1\"}1\n2\n"));
+    assert!(output.stdout.contains("bytes omitted"));
+
+    let output = tools[1].run(&json!({}), &options).await.unwrap();
+    assert_eq!(output.exit, CommandExit::TimedOut(std::time::Duration::from_secs(1)));
+}
@@ -0,0 +1,55 @@
+#[allow(dead_code)]
+#[path = "../src/diff.rs"]
+mod diff;
+
+use diff::{MAX_PREVIEW_LINES, colorize, unified_diff};
+
+#[test]
+fn test_edit_diff() {
+    let old = "fn main() {\n    println!(\"hi\");\n}\n";
+    let new = "fn main() {\n    println!(\"hello\");\n}\n";
+
+    let result = unified_diff("src/main.rs", Some(old), new);
+
+    assert_eq!(
+        result,
+        "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,3 +1,3 @@\n fn main() {\n-    println!(\"hi\");\n+    println!(\"hello\");\n }\n"
+    );
+}
+
+#[test]
+fn test_creation_diff_shows_full_content() {
+    let result = unified_diff("notes.md", None, "# Notes\ntodo\n");
+
+    assert_eq!(result, "--- /dev/null\n+++ b/notes.md\n@@ -0,0 +1,2 @@\n+# Notes\n+todo\n");
+}
+
+#[test]
+fn test_unchanged_file_has_empty_diff() {
+    assert_eq!(unified_diff("a.txt", Some("same\n"), "same\n"), "");
+}
+
+#[test]
+fn test_colorize() {
+    let diff = unified_diff("a.txt", Some("old\n"), "new\n");
+
+    colored::control::set_override(false);
+    assert_eq!(colorize(&diff), diff.trim_end());
+
+    colored::control::set_override(true);
+    let coloured = colorize(&diff);
+    colored::control::unset_override();
+    assert!(coloured.contains("\u{1b}[31m-old\u{1b}[0m"));
+    assert!(coloured.contains("\u{1b}[32m+new\u{1b}[0m"));
+}
+
+#[test]
+fn test_long_previews_are_cut() {
+    let content: String = (0..MAX_PREVIEW_LINES + 50).map(|i| format!("line {}\n", i)).collect();
+    let diff = unified_diff("big.txt", None, &content);
+
+    let preview = colorize(&diff);
+
+    assert_eq!(preview.lines().count(), MAX_PREVIEW_LINES + 1);
+    assert!(preview.lines().last().unwrap().contains("... 53 more lines"));
+}
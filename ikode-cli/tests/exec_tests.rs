@@ -0,0 +1,166 @@
+#![cfg(unix)]
+
+#[allow(dead_code)]
+#[path = "../src/exec.rs"]
+mod exec;
+
+use exec::{CappedOutput, CommandExit, ExecOptions, SandboxMode, check_sandbox, run_command, sandbox_args};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn options(dir: &TempDir) -> ExecOptions {
+    ExecOptions::new(dir.path().to_path_buf())
+}
+
+#[tokio::test]
+async fn test_reports_exit_code_and_both_streams() {
+    let dir = TempDir::new().unwrap();
+
+    let output = run_command("echo out; echo err >&2; exit 3", &options(&dir)).await.unwrap();
+
+    assert_eq!(output.exit, CommandExit::Code(3));
+    assert_eq!(output.stdout, "out\n");
+    assert_eq!(output.stderr, "err\n");
+    assert!(output.to_tool_result().starts_with("Exit code: 3\nSTDOUT:\nout\n"));
+}
+
+#[tokio::test]
+async fn test_runs_in_working_directory_with_stdin_closed() {
+    let dir = TempDir::new().unwrap();
+    std::fs::write(dir.path().join("marker.txt"), "here").unwrap();
+
+    let output = run_command("cat marker.txt; cat; echo done", &options(&dir)).await.unwrap();
+
+    assert_eq!(output.exit, CommandExit::Code(0));
+    assert_eq!(output.stdout, "heredone\n");
+}
+
+#[tokio::test]
+async fn test_timeout_kills_command() {
+    let dir = TempDir::new().unwrap();
+    let options = ExecOptions { timeout: Duration::from_millis(300), ..options(&dir) };
+    let started = Instant::now();
+
+    let output = run_command("echo started; sleep 30", &options).await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(10));
+    assert_eq!(output.exit, CommandExit::TimedOut(Duration::from_millis(300)));
+    assert_eq!(output.stdout, "started\n");
+    assert!(output.to_tool_result().starts_with("Timed out after 0.3s and was killed"));
+}
+
+#[tokio::test]
+async fn test_background_children_do_not_hang_the_call() {
+    let dir = TempDir::new().unwrap();
+    let options = ExecOptions { timeout: Duration::from_secs(60), ..options(&dir) };
+    let started = Instant::now();
+
+    let output = run_command("(sleep 1; touch survived) & echo detached", &options).await.unwrap();
+
+    // Killed shortly after the shell exits rather than waited for until the timeout.
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(output.exit, CommandExit::Code(0));
+    assert_eq!(output.stdout, "detached\n");
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!dir.path().join("survived").exists());
+}
+
+#[tokio::test]
+async fn test_running_commands_register_their_process_group() {
+    let dir = TempDir::new().unwrap();
+    let groups = options(&dir).process_groups;
+    let running = ExecOptions { process_groups: groups.clone(), ..options(&dir) };
+
+    let task = tokio::spawn(async move { run_command("echo $$ > pid; sleep 30", &running).await });
+    let pid_file = dir.path().join("pid");
+    let started = Instant::now();
+    while !pid_file.exists() || std::fs::read_to_string(&pid_file).unwrap().trim().is_empty() {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let pid: u32 = std::fs::read_to_string(&pid_file).unwrap().trim().parse().unwrap();
+    assert!(groups.lock().unwrap().contains(&pid));
+
+    // Abandoning the call, as Ctrl-C does, kills the command instead of orphaning it.
+    task.abort();
+    let _ = task.await;
+    assert!(groups.lock().unwrap().is_empty());
+    let started = Instant::now();
+    while std::process::Command::new("kill").args(["-0", &pid.to_string()]).status().unwrap().success() {
+        assert!(started.elapsed() < Duration::from_secs(10), "command {} still running", pid);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let options = ExecOptions { process_groups: groups.clone(), ..options(&dir) };
+    let output = run_command("true", &options).await.unwrap();
+    assert_eq!(output.exit, CommandExit::Code(0));
+    assert!(groups.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_long_output_keeps_head_and_tail() {
+    let dir = TempDir::new().unwrap();
+    let options = ExecOptions { output_limit: 100, ..options(&dir) };
+
+    let output = run_command("echo first; seq 1 10000; echo last", &options).await.unwrap();
+
+    assert!(output.stdout.starts_with("first\n1\n2\n"));
+    assert!(output.stdout.ends_with("9999\n10000\nlast\n"));
+    assert!(output.stdout.contains("bytes omitted"));
+    assert!(output.stdout.len() < 200);
+}
+
+#[test]
+fn test_capped_output_counts_omitted_bytes() {
+    let mut output = CappedOutput::new(10);
+    output.push(b"abc");
+    output.push(b"defghijklmnopqrstuvwxyz");
+
+    assert_eq!(output.into_string(), "abcde\n... [16 bytes omitted] ...\nvwxyz");
+
+    let mut short = CappedOutput::new(10);
+    short.push(b"0123456789");
+    assert_eq!(short.into_string(), "0123456789");
+}
+
+#[test]
+fn test_sandbox_mode_parsing() {
+    assert_eq!("off".parse::<SandboxMode>(), Ok(SandboxMode::Off));
+    assert_eq!("bubblewrap".parse::<SandboxMode>(), Ok(SandboxMode::Bubblewrap));
+    assert_eq!("bwrap".parse::<SandboxMode>(), Ok(SandboxMode::Bubblewrap));
+    assert!("docker".parse::<SandboxMode>().is_err());
+    assert!(check_sandbox(SandboxMode::Off).is_ok());
+}
+
+#[test]
+fn test_sandbox_args_confine_writes_and_network() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().display().to_string();
+
+    let args = sandbox_args(dir.path()).join(" ");
+
+    assert!(args.starts_with("--ro-bind / /"));
+    assert!(args.contains(&format!("--bind {} {}", path, path)));
+    assert!(args.contains(&format!("--chdir {}", path)));
+    assert!(args.contains("--unshare-net"));
+    assert!(args.contains("--clearenv"));
+    assert!(!args.contains("OPENAI_API_KEY"));
+}
+
+#[tokio::test]
+async fn test_sandbox_blocks_writes_outside_working_directory() {
+    if check_sandbox(SandboxMode::Bubblewrap).is_err() {
+        eprintln!("bwrap not available, skipping");
+        return;
+    }
+    let dir = TempDir::new().unwrap();
+    let outside = TempDir::new_in(std::env::current_dir().unwrap()).unwrap();
+    let options = ExecOptions { sandbox: SandboxMode::Bubblewrap, ..options(&dir) };
+    let command = format!("echo inside > inside.txt; echo outside > {}/outside.txt", outside.path().display());
+
+    let output = run_command(&command, &options).await.unwrap();
+
+    assert_ne!(output.exit, CommandExit::Code(0));
+    assert!(dir.path().join("inside.txt").exists());
+    assert!(!outside.path().join("outside.txt").exists());
+}
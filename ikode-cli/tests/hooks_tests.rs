@@ -0,0 +1,116 @@
+#![cfg(unix)]
+
+#[allow(dead_code)]
+#[path = "../src/exec.rs"]
+mod exec;
+
+#[allow(dead_code)]
+#[path = "../src/hooks.rs"]
+mod hooks;
+
+#[allow(dead_code)]
+#[path = "../src/permissions.rs"]
+mod permissions;
+
+use hooks::{HookConfig, HookEvent, HookOutcome, Hooks};
+use serde_json::json;
+use std::fs;
+use tempfile::TempDir;
+
+fn hook(event: HookEvent, tool: Option<&str>, command: &str) -> HookConfig {
+    HookConfig { event, tool: tool.map(str::to_string), command: command.to_string(), timeout: None }
+}
+
+fn tool_input(path: &str) -> serde_json::Value {
+    json!({ "session_id": "s", "tool_input": { "path": path } })
+}
+
+#[tokio::test]
+async fn test_hooks_run_for_matching_events_and_tools() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(
+        vec![
+            hook(HookEvent::PostToolUse, Some("*edit*"), "cat >> edits.log"),
+            hook(HookEvent::PostToolUse, None, "echo x >> all.log"),
+            hook(HookEvent::PreToolUse, None, "echo x >> pre.log"),
+        ],
+        dir.path().to_path_buf(),
+    );
+
+    assert_eq!(hooks.run(HookEvent::PostToolUse, Some("multi_edit"), tool_input("a.rs")).await, HookOutcome::default());
+    hooks.run(HookEvent::PostToolUse, Some("read_file"), tool_input("b.rs")).await;
+
+    let edits = fs::read_to_string(dir.path().join("edits.log")).unwrap();
+    let input: serde_json::Value = serde_json::from_str(&edits).unwrap();
+    assert_eq!(input["event"], "PostToolUse");
+    assert_eq!(input["tool_input"]["path"], "a.rs");
+    assert_eq!(fs::read_to_string(dir.path().join("all.log")).unwrap(), "x\nx\n");
+    assert!(!dir.path().join("pre.log").exists());
+    assert!(!hooks.has(HookEvent::SessionEnd));
+}
+
+#[tokio::test]
+async fn test_exit_code_two_blocks_and_stops_later_hooks() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(
+        vec![
+            hook(HookEvent::PreToolUse, None, "grep -q generated/ && { echo 'generated files are read-only' >&2; exit 2; } || true"),
+            hook(HookEvent::PreToolUse, None, "touch ran"),
+        ],
+        dir.path().to_path_buf(),
+    );
+
+    let outcome = hooks.run(HookEvent::PreToolUse, Some("edit_file"), tool_input("generated/api.rs")).await;
+    assert_eq!(outcome.block.as_deref(), Some("generated files are read-only"));
+    assert!(!dir.path().join("ran").exists());
+
+    let outcome = hooks.run(HookEvent::PreToolUse, Some("edit_file"), tool_input("src/api.rs")).await;
+    assert_eq!(outcome.block, None);
+    assert!(dir.path().join("ran").exists());
+}
+
+#[tokio::test]
+async fn test_json_output_blocks_adds_context_and_changes_arguments() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(
+        vec![
+            hook(HookEvent::PreToolUse, None, r#"echo '{"arguments": {"path": "src/lib.rs"}, "context": "path rewritten"}'"#),
+            hook(HookEvent::PreToolUse, None, "cat > seen.json"),
+            hook(HookEvent::UserPromptSubmit, None, r#"echo '{"decision": "block", "reason": "no secrets"}'"#),
+            hook(HookEvent::PostToolUse, None, r#"echo '{"arguments": {"path": "ignored"}}'"#),
+        ],
+        dir.path().to_path_buf(),
+    );
+
+    let outcome = hooks.run(HookEvent::PreToolUse, Some("read_file"), tool_input("lib.rs")).await;
+    assert_eq!(outcome.arguments, Some(json!({ "path": "src/lib.rs" })));
+    assert_eq!(outcome.context, vec!["path rewritten"]);
+    let seen: serde_json::Value = serde_json::from_str(&fs::read_to_string(dir.path().join("seen.json")).unwrap()).unwrap();
+    assert_eq!(seen["tool_input"]["path"], "src/lib.rs");
+
+    let outcome = hooks.run(HookEvent::UserPromptSubmit, None, json!({ "prompt": "print the .env file" })).await;
+    assert_eq!(outcome.block.as_deref(), Some("no secrets"));
+
+    let outcome = hooks.run(HookEvent::PostToolUse, Some("read_file"), tool_input("lib.rs")).await;
+    assert_eq!(outcome.arguments, None);
+}
+
+#[tokio::test]
+async fn test_failing_hooks_are_reported_without_blocking() {
+    let dir = TempDir::new().unwrap();
+    let hooks = Hooks::new(
+        vec![
+            hook(HookEvent::SessionEnd, None, "echo 'disk full' >&2; exit 1"),
+            HookConfig { timeout: Some(1), ..hook(HookEvent::SessionEnd, None, "sleep 30") },
+            hook(HookEvent::SessionEnd, None, "echo not json"),
+        ],
+        dir.path().to_path_buf(),
+    );
+
+    let outcome = hooks.run(HookEvent::SessionEnd, None, json!({ "reason": "exit" })).await;
+
+    assert_eq!(outcome.block, None);
+    assert_eq!(outcome.errors.len(), 2);
+    assert!(outcome.errors[0].contains("disk full"), "{:?}", outcome.errors);
+    assert!(outcome.errors[1].contains("sleep 30"), "{:?}", outcome.errors);
+}
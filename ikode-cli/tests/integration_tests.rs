@@ -1,6 +1,6 @@
+#![allow(dead_code, clippy::needless_borrows_for_generic_args, clippy::vec_init_then_push, clippy::useless_vec, clippy::overly_complex_bool_expr)]
+
 use std::process::Command;
-use std::fs;
-use tempfile::TempDir;
 
 #[test]
 fn test_cli_version_flag() {
@@ -26,6 +26,7 @@ fn test_cli_help_flag() {
     assert!(stdout.contains("Usage:"));
     assert!(stdout.contains("--model"));
     assert!(stdout.contains("--brave"));
+    assert!(stdout.contains("--no-stream"));
 }
 
 #[test]
@@ -0,0 +1,166 @@
+#![cfg(unix)]
+
+#[allow(dead_code)]
+#[path = "../src/exec.rs"]
+mod exec;
+
+#[allow(dead_code)]
+#[path = "../src/jobs.rs"]
+mod jobs;
+
+use exec::ExecOptions;
+use jobs::{JobRegistry, JobStatus};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn options(dir: &TempDir) -> ExecOptions {
+    ExecOptions::new(dir.path().to_path_buf())
+}
+
+/// Polls until `path` exists, so tests do not depend on exact timings.
+async fn wait_for_file(path: &Path) {
+    let started = Instant::now();
+    while !path.exists() {
+        assert!(started.elapsed() < Duration::from_secs(10), "{} never appeared", path.display());
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn test_output_is_read_incrementally() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+
+    let id = registry
+        .start("echo first; touch first.done; while [ ! -f go ]; do sleep 0.05; done; echo second; touch second.done; sleep 30", &options(&dir))
+        .unwrap();
+    wait_for_file(&dir.path().join("first.done")).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let first = registry.read(id).unwrap();
+    assert!(first.contains("is running"));
+    assert!(first.contains("New output:\nfirst\n"));
+    assert!(registry.read(id).unwrap().ends_with("No new output."));
+
+    std::fs::write(dir.path().join("go"), "").unwrap();
+    wait_for_file(&dir.path().join("second.done")).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let second = registry.read(id).unwrap();
+    assert_eq!(second.split_once("New output:\n").unwrap().1, "second\n");
+
+    assert_eq!(registry.kill(id).await.unwrap(), "Job 1 killed.");
+}
+
+#[tokio::test]
+async fn test_finished_job_reports_exit_code() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+
+    let id = registry.start("echo bye; exit 4", &options(&dir)).unwrap();
+    let started = Instant::now();
+    while registry.jobs()[0].status == JobStatus::Running {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(registry.jobs()[0].status, JobStatus::Exited(4));
+    assert!(registry.read(id).unwrap().contains("exited with code 4"));
+    assert_eq!(registry.kill(id).await.unwrap(), "Job 1 has already exited with code 4.");
+}
+
+#[tokio::test]
+async fn test_kill_stops_the_job_and_its_children() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+
+    let id = registry
+        .start("(sleep 1; touch child-survived) & sleep 30", &options(&dir))
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    registry.kill(id).await.unwrap();
+
+    assert_eq!(registry.jobs()[0].status, JobStatus::Killed);
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!dir.path().join("child-survived").exists());
+}
+
+#[tokio::test]
+async fn test_kill_all_and_drop_clean_up() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+    registry.start("sleep 30", &options(&dir)).unwrap();
+    registry.start("sleep 30", &options(&dir)).unwrap();
+    registry.start("true", &options(&dir)).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(registry.kill_all().await, 2);
+    assert!(registry.jobs().iter().all(|job| job.status != JobStatus::Running));
+
+    let mut dropped = JobRegistry::new();
+    dropped.start("(sleep 1; touch survived) & sleep 30", &options(&dir)).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    drop(dropped);
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!dir.path().join("survived").exists());
+}
+
+#[tokio::test]
+async fn test_finished_jobs_forget_their_process_group() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+    let groups = registry.process_groups();
+    registry.start("true", &options(&dir)).unwrap();
+    registry.start("sleep 30", &options(&dir)).unwrap();
+    assert_eq!(groups.lock().unwrap().len(), 2);
+
+    let started = Instant::now();
+    while registry.jobs()[0].status == JobStatus::Running {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // Only the running job's group is left for kill_all and the Ctrl-C handler to kill.
+    assert_eq!(groups.lock().unwrap().len(), 1);
+    assert_eq!(registry.kill_all().await, 1);
+    assert!(groups.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_processes_left_by_finished_jobs_are_still_killed() {
+    let dir = TempDir::new().unwrap();
+    let mut registry = JobRegistry::new();
+    let groups = registry.process_groups();
+    registry.start("(sleep 1; touch killed-survived) & echo started", &options(&dir)).unwrap();
+    let started = Instant::now();
+    while registry.jobs()[0].status == JobStatus::Running {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    // The job exited, but its group keeps the background sleep until it is killed.
+    assert_eq!(groups.lock().unwrap().len(), 1);
+    assert_eq!(registry.kill_all().await, 1);
+    assert!(groups.lock().unwrap().is_empty());
+    assert_eq!(registry.jobs()[0].status, JobStatus::Exited(0));
+
+    let mut dropped = JobRegistry::new();
+    dropped.start("(sleep 1; touch dropped-survived) & echo started", &options(&dir)).unwrap();
+    let started = Instant::now();
+    while dropped.jobs()[0].status == JobStatus::Running {
+        assert!(started.elapsed() < Duration::from_secs(10));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    drop(dropped);
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!dir.path().join("killed-survived").exists());
+    assert!(!dir.path().join("dropped-survived").exists());
+}
+
+#[test]
+fn test_unknown_job_id() {
+    let mut registry = JobRegistry::new();
+    assert_eq!(registry.read(7).unwrap_err().to_string(), "No background job with id 7");
+}
@@ -0,0 +1,145 @@
+#[allow(dead_code)]
+#[path = "../src/mcp.rs"]
+mod mcp;
+
+#[allow(dead_code)]
+#[path = "../src/tools.rs"]
+mod tools;
+
+use mcp::{McpServerConfig, McpServerState, McpServers, expand_env, format_result, qualified_name};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use tools::parameter_from_schema;
+
+/// A server that answers the requests ikode sends, in order, with canned responses.
+const FAKE_SERVER: &str = r#"
+read -r line
+echo '{"jsonrpc":"2.0","id":1,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"1.0"}}}'
+read -r line
+read -r line
+echo 'starting up...'
+echo '{"jsonrpc":"2.0","id":2,"result":{"tools":[{"name":"echo","description":"Echoes text","inputSchema":{"type":"object","properties":{"text":{"type":"string"}},"required":["text"]}}],"nextCursor":"2"}}'
+read -r line
+echo '{"jsonrpc":"2.0","id":3,"result":{"tools":[{"name":"add.numbers","inputSchema":{"type":"object"}}]}}'
+read -r line
+echo '{"jsonrpc":"2.0","method":"notifications/message","params":{"level":"info","data":"working"}}'
+case "$line" in *'"name":"echo"'*) ;; *) line='' ;; esac
+case "$line" in
+  *'"text":"hi"'*) echo '{"jsonrpc":"2.0","id":4,"result":{"content":[{"type":"text","text":"hi"}]}}' ;;
+  *) echo '{"jsonrpc":"2.0","id":4,"result":{"content":[{"type":"text","text":"unexpected request"}],"isError":true}}' ;;
+esac
+read -r line
+echo '{"jsonrpc":"2.0","id":5,"error":{"code":-32602,"message":"Unknown tool"}}'
+read -r line
+"#;
+
+fn server(command: &str, args: &[&str], timeout: Option<u64>) -> McpServerConfig {
+    McpServerConfig {
+        command: command.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        env: HashMap::new(),
+        timeout,
+    }
+}
+
+#[test]
+fn test_qualified_names() {
+    assert_eq!(qualified_name("github", "list_issues"), "mcp__github__list_issues");
+    assert_eq!(qualified_name("my server", "add.numbers"), "mcp__my_server__add_numbers");
+    assert_eq!(qualified_name("s", &"x".repeat(100)).len(), 64);
+}
+
+#[test]
+fn test_format_result() {
+    let result = json!({
+        "content": [
+            { "type": "text", "text": "Found 2 issues" },
+            { "type": "image", "data": "...", "mimeType": "image/png" },
+            { "type": "resource", "resource": { "uri": "file:///a.txt", "text": "contents of a" } },
+            { "type": "resource_link", "uri": "file:///b.txt", "name": "b" }
+        ]
+    });
+    assert_eq!(format_result(&result), "Found 2 issues\n[image content: image/png]\ncontents of a\n[resource: file:///b.txt]");
+
+    let error = json!({ "content": [{ "type": "text", "text": "rate limited" }], "isError": true });
+    assert_eq!(format_result(&error), "Error: rate limited");
+    assert_eq!(format_result(&json!({ "content": [] })), "(no output)");
+
+    let long = json!({ "content": [{ "type": "text", "text": "x".repeat(200 * 1024) }] });
+    assert!(format_result(&long).ends_with("[102400 bytes omitted]"));
+}
+
+#[test]
+fn test_parameters_from_json_schema() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "state": { "type": "string", "enum": ["open", "closed"], "default": "open" },
+            "labels": { "type": "array", "items": { "type": "string" }, "description": "Labels to filter by" },
+            "limit": { "type": ["integer", "null"] }
+        },
+        "required": ["state"]
+    });
+
+    let parameter = parameter_from_schema(&schema);
+
+    let properties = parameter.properties.unwrap();
+    assert_eq!(parameter.required, Some(vec!["state".to_string()]));
+    assert_eq!(properties["state"].description.as_deref(), Some("One of: \"open\", \"closed\". Default: \"open\"."));
+    assert_eq!(properties["labels"].items.as_ref().unwrap().r#type.as_deref(), Some("string"));
+    assert_eq!(properties["limit"].r#type.as_deref(), Some("integer"));
+    assert!(parameter_from_schema(&json!({ "type": "object" })).properties.is_some_and(|p| p.is_empty()));
+}
+
+#[test]
+fn test_expand_env() {
+    std::env::set_var("IKODE_MCP_TEST_TOKEN", "secret");
+    assert_eq!(expand_env("Bearer ${IKODE_MCP_TEST_TOKEN}"), "Bearer secret");
+    assert_eq!(expand_env("${IKODE_MCP_TEST_UNSET}"), "");
+    assert_eq!(expand_env("$HOME stays"), "$HOME stays");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_discovers_and_calls_tools() {
+    let configs = BTreeMap::from([("fake".to_string(), server("sh", &["-c", FAKE_SERVER], Some(5)))]);
+    let mut servers = McpServers::start(configs, &std::env::temp_dir()).await;
+
+    let names: Vec<String> = servers.tools().into_iter().map(|tool| tool.name).collect();
+    assert_eq!(names, vec!["mcp__fake__echo", "mcp__fake__add_numbers"]);
+    assert_eq!(servers.find("mcp__fake__add_numbers"), Some(("fake", "add.numbers")));
+    assert_eq!(servers.find("mcp__fake__missing"), None);
+    match &servers.servers()[0].state {
+        McpServerState::Running { client, .. } => assert_eq!(client.server_info, "fake 1.0"),
+        McpServerState::Failed(e) => panic!("server failed: {}", e),
+    }
+
+    assert_eq!(servers.call("mcp__fake__echo", json!({ "text": "hi" })).await.unwrap(), "hi");
+    let error = servers.call("mcp__fake__add_numbers", json!({})).await.unwrap_err();
+    assert_eq!(error.to_string(), "Server error -32602: Unknown tool");
+
+    servers.shutdown().await;
+    assert!(servers.tools().is_empty());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_failing_servers_are_reported() {
+    let configs = BTreeMap::from([
+        ("missing".to_string(), server("ikode-no-such-mcp-server", &[], None)),
+        ("silent".to_string(), server("sh", &["-c", "sleep 10"], Some(1))),
+    ]);
+    let mut servers = McpServers::start(configs, &std::env::temp_dir()).await;
+
+    let errors: Vec<String> = servers
+        .servers()
+        .iter()
+        .map(|server| match &server.state {
+            McpServerState::Failed(e) => e.clone(),
+            McpServerState::Running { .. } => panic!("{} should have failed", server.name),
+        })
+        .collect();
+    assert!(errors[0].starts_with("Could not start `ikode-no-such-mcp-server`"));
+    assert_eq!(errors[1], "The server did not answer initialize within 1s");
+    assert!(servers.tools().is_empty());
+}
@@ -0,0 +1,140 @@
+#[allow(dead_code)]
+#[path = "../src/patch.rs"]
+mod patch;
+
+use patch::{apply_edits, parse_patch, replace_unique};
+
+const SOURCE: &str = "fn main() {\n    let a = 1;\n    let b = 2;\n    println!(\"{}\", a + b);\n}\n";
+
+#[test]
+fn test_replace_unique() {
+    assert_eq!(replace_unique("a b c", "b", "x").unwrap(), "a x c");
+    assert!(replace_unique("a b c", "z", "x").unwrap_err().contains("not found"));
+    assert!(replace_unique("a b b", "b", "x").unwrap_err().contains("matches 2 locations"));
+}
+
+#[test]
+fn test_multi_edit_applies_in_order() {
+    let edits = [("let a = 1;", "let a = 10;"), ("let a = 10;\n    let b = 2;", "let a = 10;\n    let b = 20;"), ("a + b", "a * b")];
+
+    let result = apply_edits(SOURCE, edits).unwrap();
+
+    assert_eq!(result, "fn main() {\n    let a = 10;\n    let b = 20;\n    println!(\"{}\", a * b);\n}\n");
+}
+
+#[test]
+fn test_multi_edit_fails_as_a_whole() {
+    let edits = [("let a = 1;", "let a = 10;"), ("let c = 3;", "let c = 30;")];
+
+    let err = apply_edits(SOURCE, edits).unwrap_err();
+
+    assert!(err.starts_with("Edit 2: old_text not found"));
+}
+
+#[test]
+fn test_patch_across_files() {
+    let patch = "diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,4 +1,4 @@
+ fn main() {
+-    let a = 1;
++    let a = 10;
+     let b = 2;
+     println!(\"{}\", a + b);
+--- /dev/null
++++ b/NOTES.md
+@@ -0,0 +1,2 @@
++# Notes
++a is ten now
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+    let files = parse_patch(patch).unwrap();
+
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0].path(), "src/main.rs");
+    assert_eq!(files[0].apply(Some(SOURCE)).unwrap().unwrap(), SOURCE.replace("a = 1;", "a = 10;"));
+    assert!(files[1].is_creation());
+    assert_eq!(files[1].apply(None).unwrap().unwrap(), "# Notes\na is ten now\n");
+    assert!(files[2].is_deletion());
+    assert_eq!(files[2].apply(Some("bye\n")).unwrap(), None);
+}
+
+#[test]
+fn test_hunks_are_found_when_line_numbers_are_off() {
+    let original: String = (1..=20).map(|i| format!("line {}\n", i)).collect();
+    let patch = "--- a/f.txt
++++ b/f.txt
+@@ -2,3 +2,3 @@
+ line 5
+-line 6
++line six
+ line 7
+@@ -30,2 +30,3 @@
+ line 18
++line 18.5
+ line 19
+";
+    let files = parse_patch(patch).unwrap();
+
+    let result = files[0].apply(Some(&original)).unwrap().unwrap();
+
+    assert!(result.contains("line 5\nline six\nline 7\n"));
+    assert!(result.contains("line 18\nline 18.5\nline 19\nline 20\n"));
+}
+
+#[test]
+fn test_every_failing_hunk_is_reported() {
+    let patch = "--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,2 +1,2 @@
+ fn main() {
+-    let a = 1;
++    let a = 10;
+@@ -3,2 +3,2 @@
+-    let c = 3;
++    let c = 30;
+@@ -4,2 +4,2 @@
+-    println!(\"missing\");
++    println!(\"still missing\");
+";
+    let files = parse_patch(patch).unwrap();
+
+    let errors = files[0].apply(Some(SOURCE)).unwrap_err();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].starts_with("src/main.rs: hunk 2 (@@ -3,2 +3,2 @@) does not match the file: expected `let c = 3;`"));
+    assert!(errors[1].starts_with("src/main.rs: hunk 3"));
+}
+
+#[test]
+fn test_creation_and_deletion_preconditions() {
+    let create = parse_patch("--- /dev/null\n+++ b/new.txt\n@@ -0,0 +1 @@\n+hi\n").unwrap();
+    assert_eq!(create[0].apply(Some("exists\n")).unwrap_err(), vec!["new.txt: file already exists".to_string()]);
+
+    let modify = parse_patch("--- a/gone.txt\n+++ b/gone.txt\n@@ -1 +1 @@\n-a\n+b\n").unwrap();
+    assert_eq!(modify[0].apply(None).unwrap_err(), vec!["gone.txt: file not found".to_string()]);
+
+    let partial_delete = parse_patch("--- a/f.txt\n+++ /dev/null\n@@ -1 +0,0 @@\n-a\n").unwrap();
+    assert!(partial_delete[0].apply(Some("a\nb\n")).is_err());
+}
+
+#[test]
+fn test_final_newline_and_crlf() {
+    let patch = parse_patch("--- a/f.txt\n+++ b/f.txt\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\\ No newline at end of file\n").unwrap();
+    assert_eq!(patch[0].apply(Some("a\nb\n")).unwrap().unwrap(), "a\nc");
+
+    let patch = parse_patch("--- a/f.txt\n+++ b/f.txt\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n").unwrap();
+    assert_eq!(patch[0].apply(Some("a\r\nb\r\n")).unwrap().unwrap(), "a\r\nc\r\n");
+}
+
+#[test]
+fn test_malformed_patches() {
+    assert!(parse_patch("just some text").is_err());
+    assert!(parse_patch("@@ -1 +1 @@\n-a\n+b\n").unwrap_err().to_string().contains("before any"));
+    assert!(parse_patch("--- a/f\n+++ b/f\n@@ bogus @@\n").unwrap_err().to_string().contains("Invalid hunk header"));
+}
@@ -1,3 +1,7 @@
+#[allow(dead_code)]
+#[path = "../src/paths.rs"]
+mod paths;
+
 use std::path::PathBuf;
 use std::fs;
 use tempfile::TempDir;
@@ -12,25 +16,9 @@ struct TestApp {
 
 impl TestApp {
     fn validate_path(&self, path: &str) -> Result<PathBuf, String> {
-        let requested_path = std::path::Path::new(path);
         let canonical_wd = self.working_directory.canonicalize()
             .unwrap_or_else(|_| self.working_directory.clone());
-
-        let canonical_path = if requested_path.is_absolute() {
-            requested_path.canonicalize().unwrap_or_else(|_| requested_path.to_path_buf())
-        } else {
-            let joined = self.working_directory.join(requested_path);
-            joined.canonicalize().unwrap_or_else(|_| joined)
-        };
-
-        if !canonical_path.starts_with(&canonical_wd) {
-            return Err(format!(
-                "Path '{}' is outside the working directory. For security reasons, file operations are restricted to the working directory and its subdirectories.",
-                path
-            ));
-        }
-
-        Ok(canonical_path)
+        paths::validate_path(&canonical_wd, path).map_err(|e| e.to_string())
     }
 }
 
@@ -111,6 +99,47 @@ fn test_rejects_symlink_escape() {
     }
 }
 
+#[cfg(unix)]
+#[test]
+fn test_rejects_new_paths_under_a_symlinked_directory_outside() {
+    use std::os::unix::fs::symlink;
+    let temp_dir = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    symlink(outside.path(), temp_dir.path().join("link")).unwrap();
+    let app = create_test_app(temp_dir.path().to_path_buf());
+
+    for path in ["link/new.txt", "link/a/b/new.txt", "./link/../link/new.txt"] {
+        let result = app.validate_path(path);
+        assert!(result.unwrap_err().contains("outside the working directory"), "{}", path);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rejects_dangling_symlinks() {
+    use std::os::unix::fs::symlink;
+    let temp_dir = TempDir::new().unwrap();
+    let outside = TempDir::new().unwrap();
+    symlink(outside.path().join("created.txt"), temp_dir.path().join("dangling")).unwrap();
+    let app = create_test_app(temp_dir.path().to_path_buf());
+
+    assert!(app.validate_path("dangling").is_err());
+    assert!(app.validate_path("dangling/new.txt").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_allows_new_paths_under_a_symlinked_directory_inside() {
+    use std::os::unix::fs::symlink;
+    let temp_dir = TempDir::new().unwrap();
+    let canonical_temp = temp_dir.path().canonicalize().unwrap();
+    fs::create_dir(temp_dir.path().join("real")).unwrap();
+    symlink(canonical_temp.join("real"), temp_dir.path().join("link")).unwrap();
+    let app = create_test_app(canonical_temp.clone());
+
+    assert_eq!(app.validate_path("link/new/file.txt").unwrap(), canonical_temp.join("real/new/file.txt"));
+}
+
 #[test]
 fn test_allows_dot_slash_prefix() {
     let temp_dir = TempDir::new().unwrap();
@@ -165,3 +194,11 @@ fn test_allows_current_directory() {
     let result = app.validate_path(".");
     assert!(result.is_ok());
 }
+
+#[test]
+fn test_normalize_path_resolves_dot_segments() {
+    assert_eq!(
+        paths::normalize_path(std::path::Path::new("/work/./src/../lib/a.rs")),
+        PathBuf::from("/work/lib/a.rs")
+    );
+}
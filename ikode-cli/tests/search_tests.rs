@@ -0,0 +1,110 @@
+#[allow(dead_code)]
+mod common;
+
+#[allow(dead_code)]
+#[path = "../src/search.rs"]
+mod search;
+
+use common::TestFixture;
+use search::{GrepOptions, glob, grep, list_directory};
+
+fn project() -> TestFixture {
+    let fixture = TestFixture::new();
+    fixture.create_file(".gitignore", "target/\n*.log\n");
+    fixture.create_file("src/main.rs", "fn main() {\n    let answer = 42;\n    println!(\"{}\", answer);\n}\n");
+    fixture.create_file("src/lib.rs", "pub fn answer() -> u32 {\n    42\n}\n");
+    fixture.create_file("docs/notes.md", "The Answer is 42.\n");
+    fixture.create_file("target/debug/out.rs", "let answer = 42;\n");
+    fixture.create_file("build.log", "answer 42\n");
+    fixture.create_file(".git/config", "answer = 42\n");
+    fixture
+}
+
+#[test]
+fn test_grep_respects_gitignore_and_skips_git_dir() {
+    let fixture = project();
+    let root = fixture.path();
+
+    let result = grep(root, root, &GrepOptions::new("answer")).unwrap();
+
+    assert_eq!(result, "src/lib.rs:1:pub fn answer() -> u32 {\nsrc/main.rs:2:    let answer = 42;\nsrc/main.rs:3:    println!(\"{}\", answer);");
+}
+
+#[test]
+fn test_grep_include_case_and_path() {
+    let fixture = project();
+    let root = fixture.path();
+
+    let options = GrepOptions { include: Some("*.md".to_string()), case_insensitive: true, ..GrepOptions::new("answer") };
+    assert_eq!(grep(root, root, &options).unwrap(), "No matches found.");
+
+    let options = GrepOptions { include: Some("**/*.md".to_string()), case_insensitive: true, ..GrepOptions::new("answer") };
+    assert_eq!(grep(root, root, &options).unwrap(), "docs/notes.md:1:The Answer is 42.");
+
+    let result = grep(root, &root.join("src/lib.rs"), &GrepOptions::new(r"\d+")).unwrap();
+    assert_eq!(result, "src/lib.rs:1:pub fn answer() -> u32 {\nsrc/lib.rs:2:    42");
+}
+
+#[test]
+fn test_grep_context_lines() {
+    let fixture = TestFixture::new();
+    fixture.create_file("a.txt", "one\ntwo\nthree\nfour\nfive\nsix\nseven\neight\n");
+    let root = fixture.path();
+
+    let options = GrepOptions { context: 1, ..GrepOptions::new("^(two|seven)$") };
+    let result = grep(root, root, &options).unwrap();
+
+    assert_eq!(result, "a.txt-1-one\na.txt:2:two\na.txt-3-three\n--\na.txt-6-six\na.txt:7:seven\na.txt-8-eight");
+}
+
+#[test]
+fn test_grep_result_cap_and_invalid_regex() {
+    let fixture = TestFixture::new();
+    fixture.create_file("many.txt", &"match\n".repeat(50));
+    let root = fixture.path();
+
+    let options = GrepOptions { max_results: 3, ..GrepOptions::new("match") };
+    let result = grep(root, root, &options).unwrap();
+    assert_eq!(result.lines().count(), 4);
+    assert!(result.ends_with("[Stopped after 3 matches. Narrow the search with path or include, or raise max_results.]"));
+
+    assert!(grep(root, root, &GrepOptions::new("(unclosed")).unwrap_err().to_string().contains("Invalid regex"));
+}
+
+#[test]
+fn test_grep_skips_binary_files_and_shortens_long_lines() {
+    let fixture = TestFixture::new();
+    std::fs::write(fixture.path().join("blob.bin"), b"needle\0\x01\x02").unwrap();
+    fixture.create_file("min.js", &format!("needle{}", "x".repeat(1000)));
+    let root = fixture.path();
+
+    let result = grep(root, root, &GrepOptions::new("needle")).unwrap();
+
+    assert!(result.starts_with("min.js:1:needle"));
+    assert!(result.ends_with('…'));
+    assert!(result.len() < 400);
+}
+
+#[test]
+fn test_glob() {
+    let fixture = project();
+    let root = fixture.path();
+
+    assert_eq!(glob(root, root, "**/*.rs", 500).unwrap(), "src/lib.rs\nsrc/main.rs");
+    assert_eq!(glob(root, root, "*.rs", 500).unwrap(), "No files match '*.rs'.");
+    assert_eq!(glob(root, &root.join("src"), "*.rs", 500).unwrap(), "src/lib.rs\nsrc/main.rs");
+    assert!(glob(root, root, "**/*", 1).unwrap().ends_with("[Stopped after 1 files. Use a narrower pattern or path.]"));
+}
+
+#[test]
+fn test_list_directory() {
+    let fixture = project();
+    let root = fixture.path();
+
+    assert_eq!(list_directory(root, 1).unwrap(), ".gitignore (14 bytes)\ndocs/\nsrc/");
+    assert_eq!(
+        list_directory(root, 2).unwrap(),
+        ".gitignore (14 bytes)\ndocs/\n  notes.md (18 bytes)\nsrc/\n  lib.rs (34 bytes)\n  main.rs (63 bytes)"
+    );
+    assert!(list_directory(&root.join("src/main.rs"), 1).is_err());
+}
@@ -0,0 +1,193 @@
+#[allow(dead_code)]
+#[path = "../src/compaction.rs"]
+mod compaction;
+
+#[allow(dead_code)]
+#[path = "../src/cost.rs"]
+mod cost;
+
+#[allow(dead_code)]
+#[path = "../src/session.rs"]
+mod session;
+
+use chrono::{Duration, Utc};
+use gaise_core::contracts::{GaiseContent, GaiseMessage, OneOrMany};
+use session::{Session, SessionStore, Todo, SESSION_FORMAT_VERSION, project_key};
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn text_message(role: &str, text: &str) -> GaiseMessage {
+    GaiseMessage {
+        role: role.to_string(),
+        content: Some(OneOrMany::One(GaiseContent::Text { text: text.to_string() })),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+fn sample_session(id: &str, prompt: &str, minutes_ago: i64) -> Session {
+    let updated_at = Utc::now() - Duration::minutes(minutes_ago);
+    Session {
+        version: SESSION_FORMAT_VERSION,
+        id: id.to_string(),
+        working_directory: PathBuf::from("/tmp/project"),
+        model: "openai::gpt-4o".to_string(),
+        created_at: updated_at,
+        updated_at,
+        session_cache_key: "cache-key".to_string(),
+        history: vec![
+            text_message("system", "You are iKode."),
+            text_message("user", prompt),
+            text_message("assistant", "Done."),
+        ],
+        todos: vec![Todo { id: 1, task: "Write tests".to_string(), completed: false }],
+        compaction: None,
+        usage: cost::UsageTotals::default(),
+    }
+}
+
+#[test]
+fn test_save_and_load_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().join("sessions"));
+
+    store.save(&sample_session("abc123", "Fix the parser", 0)).unwrap();
+    let loaded = store.load("abc123").unwrap();
+
+    assert_eq!(loaded.id, "abc123");
+    assert_eq!(loaded.history.len(), 3);
+    assert_eq!(loaded.todos.len(), 1);
+    assert_eq!(loaded.todos[0].task, "Write tests");
+    assert_eq!(loaded.session_cache_key, "cache-key");
+    assert!(!temp_dir.path().join("sessions/abc123.json.tmp").exists());
+}
+
+#[test]
+fn test_compaction_summary_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    let mut session = sample_session("compacted", "hello", 0);
+    session.compaction = Some(compaction::CompactionSummary {
+        covered_until: 2,
+        compacted_messages: 1,
+        summary: "## Goal\nSay hello".to_string(),
+    });
+    store.save(&session).unwrap();
+
+    let loaded = store.load("compacted").unwrap();
+    let summary = loaded.compaction.unwrap();
+    assert_eq!(summary.covered_until, 2);
+    assert_eq!(summary.summary, "## Goal\nSay hello");
+}
+
+#[test]
+fn test_load_by_unique_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    store.save(&sample_session("abc123", "one", 0)).unwrap();
+    store.save(&sample_session("def456", "two", 0)).unwrap();
+
+    assert_eq!(store.load("def").unwrap().id, "def456");
+}
+
+#[test]
+fn test_load_ambiguous_prefix_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    store.save(&sample_session("abc123", "one", 0)).unwrap();
+    store.save(&sample_session("abc456", "two", 0)).unwrap();
+
+    let err = store.load("abc").unwrap_err().to_string();
+    assert!(err.contains("ambiguous"));
+}
+
+#[test]
+fn test_load_missing_session_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    let err = store.load("nope").unwrap_err().to_string();
+    assert!(err.contains("No saved session"));
+}
+
+#[test]
+fn test_load_rejects_ids_outside_the_store() {
+    let temp_dir = TempDir::new().unwrap();
+    let sessions_dir = temp_dir.path().join("projects").join("p").join("sessions");
+    let store = SessionStore::new(sessions_dir);
+
+    // A session file two levels above the store must not be reachable.
+    SessionStore::new(temp_dir.path().join("projects")).save(&sample_session("x", "escape", 0)).unwrap();
+
+    for id in ["../../x", "../x", "a/b", "a\\b", ".."] {
+        let err = store.load(id).unwrap_err().to_string();
+        assert!(err.contains("Invalid session id"), "{}: {}", id, err);
+    }
+}
+
+#[test]
+fn test_list_orders_by_most_recent_and_latest() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    store.save(&sample_session("old", "Old prompt", 60)).unwrap();
+    store.save(&sample_session("new", "New prompt\nsecond line", 1)).unwrap();
+    store.save(&sample_session("mid", "Mid prompt", 30)).unwrap();
+
+    let summaries = store.list().unwrap();
+    let ids: Vec<&str> = summaries.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids, vec!["new", "mid", "old"]);
+    assert_eq!(summaries[0].title, "New prompt");
+    assert_eq!(summaries[0].message_count, 3);
+
+    assert_eq!(store.latest().unwrap().unwrap().id, "new");
+}
+
+#[test]
+fn test_list_empty_when_directory_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().join("does-not-exist"));
+
+    assert!(store.list().unwrap().is_empty());
+    assert!(store.latest().unwrap().is_none());
+}
+
+#[test]
+fn test_rejects_newer_format_version() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf());
+
+    let mut session = sample_session("future", "hello", 0);
+    session.version = SESSION_FORMAT_VERSION + 1;
+    store.save(&session).unwrap();
+
+    let err = store.load("future").unwrap_err().to_string();
+    assert!(err.contains("newer version"));
+}
+
+#[test]
+fn test_project_key_flattens_path() {
+    let key = project_key(std::path::Path::new("/home/me/my project"));
+    assert!(key.starts_with("-home-me-my-project-"));
+    assert_eq!(key.len(), "-home-me-my-project-".len() + 12);
+    // Paths that flatten to the same name still get their own directories.
+    assert_ne!(project_key(std::path::Path::new("/a/b-c")), project_key(std::path::Path::new("/a-b/c")));
+}
+
+#[test]
+fn test_list_and_latest_skip_other_projects() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = SessionStore::new(temp_dir.path().to_path_buf()).only_for(std::path::Path::new("/tmp/project"));
+
+    store.save(&sample_session("ours", "Our prompt", 10)).unwrap();
+    let mut other = sample_session("theirs", "Their prompt", 1);
+    other.working_directory = PathBuf::from("/tmp/other");
+    store.save(&other).unwrap();
+
+    let ids: Vec<String> = store.list().unwrap().into_iter().map(|s| s.id).collect();
+    assert_eq!(ids, vec!["ours"]);
+    assert_eq!(store.latest().unwrap().unwrap().id, "ours");
+}
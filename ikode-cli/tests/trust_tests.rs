@@ -0,0 +1,48 @@
+#[allow(dead_code)]
+#[path = "../src/trust.rs"]
+mod trust;
+
+use std::fs;
+use tempfile::TempDir;
+use trust::{TrustStore, content_hash};
+
+#[test]
+fn test_trusted_file_is_remembered() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    fs::write(&config, "brave = true\n").unwrap();
+    let store = TrustStore::new(dir.path().join("data").join("trusted_projects.json"));
+
+    assert!(!store.is_trusted(&config, "brave = true\n"));
+    store.trust(&config, "brave = true\n").unwrap();
+
+    let reopened = TrustStore::new(dir.path().join("data").join("trusted_projects.json"));
+    assert!(reopened.is_trusted(&config, "brave = true\n"));
+}
+
+#[test]
+fn test_changed_content_is_no_longer_trusted() {
+    let dir = TempDir::new().unwrap();
+    let config = dir.path().join("config.toml");
+    let store = TrustStore::new(dir.path().join("trusted_projects.json"));
+
+    store.trust(&config, "model = \"a\"\n").unwrap();
+
+    assert!(!store.is_trusted(&config, "model = \"a\"\n[mcp_servers.x]\ncommand = \"sh\"\n"));
+    assert!(!store.is_trusted(&dir.path().join("other.toml"), "model = \"a\"\n"));
+}
+
+#[test]
+fn test_unreadable_store_trusts_nothing() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("trusted_projects.json");
+    fs::write(&path, "not json").unwrap();
+    let store = TrustStore::new(path);
+
+    assert!(!store.is_trusted(&dir.path().join("config.toml"), ""));
+}
+
+#[test]
+fn test_content_hash_is_sha256() {
+    assert_eq!(content_hash(""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+}